@@ -0,0 +1,197 @@
+//! Benchmarks of the dex module
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+use primitives::currency::DOLLARS;
+
+use crate::Trait as Config;
+
+// the longest path of the swaps, each hop of it is a listed pair
+const PATH: [CurrencyId; 4] = [CurrencyId::CLV, CurrencyId::CUSDT, CurrencyId::DOT, CurrencyId::CETH];
+const MAX_HOPS: u32 = 3;
+const SEED: u32 = 0;
+const STABLE_CURRENCIES: [CurrencyId; 2] = [CurrencyId::CUSDT, CurrencyId::DOT];
+
+type Dex<T> = Module<T>;
+
+fn fund<T: Config>(who: &T::AccountId, currency_id: CurrencyId, amount: Balance) -> DispatchResult {
+  T::Currency::deposit(currency_id, who, amount)
+}
+
+fn list_pair<T: Config>(currency_a: CurrencyId, currency_b: CurrencyId) -> Result<TradingPair, &'static str> {
+  let pair = TradingPair::from_currencies(currency_a, currency_b).ok_or("invalid pair")?;
+  TradingPairs::insert(pair, true);
+  Ok(pair)
+}
+
+/// list the pair and add the liquidity of `amount` for each currency by a liquidity provider
+fn seed_pair<T: Config>(currency_a: CurrencyId, currency_b: CurrencyId, amount: Balance) -> DispatchResult {
+  list_pair::<T>(currency_a, currency_b)?;
+  let provider: T::AccountId = account("provider", 0, SEED);
+  fund::<T>(&provider, currency_a, amount)?;
+  fund::<T>(&provider, currency_b, amount)?;
+  Dex::<T>::add_liquidity(RawOrigin::Signed(provider).into(), currency_a, currency_b, amount, amount)
+}
+
+/// seed each hop of the path of `hops` hops
+fn seed_path<T: Config>(hops: u32) -> Result<Vec<CurrencyId>, &'static str> {
+  let path = PATH[..=hops as usize].to_vec();
+  for pair in path.windows(2) {
+    seed_pair::<T>(pair[0], pair[1], 1_000 * DOLLARS)?;
+  }
+  Ok(path)
+}
+
+/// create `count` stable pools of the first hop of the path with the liquidity of `amount` for each currency,
+/// so the first hop of the swaps checks all of them
+fn seed_hop_stable_pools<T: Config>(count: u32, amount: Balance) -> DispatchResult {
+  let provider: T::AccountId = account("provider", 0, SEED);
+  for _ in 0..count {
+    let pool_index = Dex::<T>::next_stable_pool_index();
+    Dex::<T>::create_stable_pool(T::UpdateOrigin::successful_origin(), PATH[..2].to_vec(), 100,
+                                 Rate::saturating_from_rational(4, 10000))?;
+    fund::<T>(&provider, PATH[0], amount)?;
+    fund::<T>(&provider, PATH[1], amount)?;
+    Dex::<T>::add_stable_liquidity(RawOrigin::Signed(provider.clone()).into(), pool_index,
+                                   vec![amount, amount], Zero::zero())?;
+  }
+  Ok(())
+}
+
+/// create a stable pool of the stable currencies and returns the index of it
+fn create_stable_pool<T: Config>() -> Result<StablePoolIndex, &'static str> {
+  let pool_index = Dex::<T>::next_stable_pool_index();
+  let origin = T::UpdateOrigin::successful_origin();
+  Dex::<T>::create_stable_pool(origin, STABLE_CURRENCIES.to_vec(), 100, Rate::saturating_from_rational(4, 10000))?;
+  Ok(pool_index)
+}
+
+/// returns a caller who added `amount` of each currency to the stable pool
+fn stable_provider<T: Config>(pool_index: StablePoolIndex, amount: Balance) -> Result<T::AccountId, &'static str> {
+  let caller: T::AccountId = whitelisted_caller();
+  for currency_id in STABLE_CURRENCIES.iter() {
+    fund::<T>(&caller, *currency_id, amount)?;
+  }
+  Dex::<T>::add_stable_liquidity(RawOrigin::Signed(caller.clone()).into(), pool_index,
+                                 vec![amount; STABLE_CURRENCIES.len()], Zero::zero())?;
+  Ok(caller)
+}
+
+benchmarks! {
+  _ { }
+
+  add_liquidity {
+    seed_pair::<T>(CurrencyId::CLV, CurrencyId::CUSDT, 1_000 * DOLLARS)?;
+    let caller: T::AccountId = whitelisted_caller();
+    fund::<T>(&caller, CurrencyId::CLV, 100 * DOLLARS)?;
+    fund::<T>(&caller, CurrencyId::CUSDT, 100 * DOLLARS)?;
+  }: _(RawOrigin::Signed(caller.clone()), CurrencyId::CLV, CurrencyId::CUSDT, 100 * DOLLARS, 100 * DOLLARS)
+  verify {
+    let pair = TradingPair::from_currencies(CurrencyId::CLV, CurrencyId::CUSDT).ok_or("invalid pair")?;
+    assert!(!Dex::<T>::shares(pair, &caller).is_zero());
+  }
+
+  remove_liquidity {
+    list_pair::<T>(CurrencyId::CLV, CurrencyId::CUSDT)?;
+    let caller: T::AccountId = whitelisted_caller();
+    fund::<T>(&caller, CurrencyId::CLV, 100 * DOLLARS)?;
+    fund::<T>(&caller, CurrencyId::CUSDT, 100 * DOLLARS)?;
+    Dex::<T>::add_liquidity(RawOrigin::Signed(caller.clone()).into(),
+                            CurrencyId::CLV, CurrencyId::CUSDT, 100 * DOLLARS, 100 * DOLLARS)?;
+    let pair = TradingPair::from_currencies(CurrencyId::CLV, CurrencyId::CUSDT).ok_or("invalid pair")?;
+    let shares = Dex::<T>::shares(pair, &caller);
+  }: _(RawOrigin::Signed(caller.clone()), CurrencyId::CLV, CurrencyId::CUSDT, shares / 2)
+  verify {
+    assert_eq!(Dex::<T>::shares(pair, &caller), shares - shares / 2);
+  }
+
+  swap_with_exact_supply {
+    let h in 1 .. MAX_HOPS;
+    let s in 0 .. T::MaxStablePoolsPerPair::get();
+    let path = seed_path::<T>(h)?;
+    seed_hop_stable_pools::<T>(s, 1_000 * DOLLARS)?;
+    let caller: T::AccountId = whitelisted_caller();
+    fund::<T>(&caller, path[0], 10 * DOLLARS)?;
+  }: swap_with_exact_supply_by_path(RawOrigin::Signed(caller.clone()), path.clone(), 10 * DOLLARS, Zero::zero())
+  verify {
+    assert!(!T::Currency::free_balance(path[h as usize], &caller).is_zero());
+  }
+
+  swap_with_exact_target {
+    let h in 1 .. MAX_HOPS;
+    let s in 0 .. T::MaxStablePoolsPerPair::get();
+    let path = seed_path::<T>(h)?;
+    seed_hop_stable_pools::<T>(s, 1_000 * DOLLARS)?;
+    let caller: T::AccountId = whitelisted_caller();
+    fund::<T>(&caller, path[0], 100 * DOLLARS)?;
+  }: swap_with_exact_target_by_path(RawOrigin::Signed(caller.clone()), path.clone(), DOLLARS, 100 * DOLLARS)
+  verify {
+    assert_eq!(T::Currency::free_balance(path[h as usize], &caller), DOLLARS);
+  }
+
+  list_trading_pair {
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, CurrencyId::CLV, CurrencyId::CUSDT)
+  verify {
+    let pair = TradingPair::from_currencies(CurrencyId::CLV, CurrencyId::CUSDT).ok_or("invalid pair")?;
+    assert!(Dex::<T>::trading_pairs(pair));
+  }
+
+  set_exchange_fee {
+    let origin = T::UpdateOrigin::successful_origin();
+    let exchange_fee = Rate::saturating_from_rational(3, 1000);
+  }: _<T::Origin>(origin, exchange_fee)
+  verify {
+    assert_eq!(Dex::<T>::exchange_fee(), exchange_fee);
+  }
+
+  create_stable_pool {
+    let origin = T::UpdateOrigin::successful_origin();
+    let currencies = PATH.to_vec();
+  }: _<T::Origin>(origin, currencies, 100, Rate::saturating_from_rational(4, 10000))
+  verify {
+    assert!(Dex::<T>::stable_pools(0).is_some());
+  }
+
+  ramp_amplification {
+    let pool_index = create_stable_pool::<T>()?;
+    let future_block = frame_system::Module::<T>::block_number() + T::MinAmplificationRampBlocks::get();
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool_index, 1_000, future_block)
+  verify {
+    assert_eq!(Dex::<T>::stable_pools(pool_index).map(|pool| pool.future_amplification), Some(1_000));
+  }
+
+  stop_amplification_ramp {
+    let pool_index = create_stable_pool::<T>()?;
+    let future_block = frame_system::Module::<T>::block_number() + T::MinAmplificationRampBlocks::get();
+    Dex::<T>::ramp_amplification(T::UpdateOrigin::successful_origin(), pool_index, 1_000, future_block)?;
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool_index)
+  verify {
+    assert_eq!(Dex::<T>::stable_pools(pool_index).map(|pool| pool.future_amplification), Some(100));
+  }
+
+  add_stable_liquidity {
+    let pool_index = create_stable_pool::<T>()?;
+    stable_provider::<T>(pool_index, 1_000 * DOLLARS)?;
+    let caller: T::AccountId = account("caller", 0, SEED);
+    fund::<T>(&caller, STABLE_CURRENCIES[0], 100 * DOLLARS)?;
+    fund::<T>(&caller, STABLE_CURRENCIES[1], 50 * DOLLARS)?;
+  }: _(RawOrigin::Signed(caller.clone()), pool_index, vec![100 * DOLLARS, 50 * DOLLARS], Zero::zero())
+  verify {
+    assert!(!Dex::<T>::stable_shares(pool_index, &caller).is_zero());
+  }
+
+  remove_stable_liquidity {
+    let pool_index = create_stable_pool::<T>()?;
+    let caller = stable_provider::<T>(pool_index, 1_000 * DOLLARS)?;
+    let shares = Dex::<T>::stable_shares(pool_index, &caller);
+  }: _(RawOrigin::Signed(caller.clone()), pool_index, shares / 2, vec![Zero::zero(); STABLE_CURRENCIES.len()])
+  verify {
+    assert_eq!(Dex::<T>::stable_shares(pool_index, &caller), shares - shares / 2);
+  }
+}
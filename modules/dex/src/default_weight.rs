@@ -0,0 +1,70 @@
+//! Default weights of the dex extrinsics, estimated from the storage access of each call.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(clippy::unnecessary_cast)]
+
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+
+impl crate::WeightInfo for () {
+	fn add_liquidity() -> Weight {
+		(180_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(11 as Weight))
+			.saturating_add(DbWeight::get().writes(10 as Weight))
+	}
+	fn remove_liquidity() -> Weight {
+		(180_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(11 as Weight))
+			.saturating_add(DbWeight::get().writes(10 as Weight))
+	}
+	fn swap_with_exact_supply(h: u32, s: u32, ) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(h as Weight))
+			.saturating_add((60_000_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(DbWeight::get().reads((6 as Weight).saturating_mul(h as Weight)))
+			.saturating_add(DbWeight::get().reads(s as Weight))
+			.saturating_add(DbWeight::get().writes((5 as Weight).saturating_mul(h as Weight)))
+	}
+	fn swap_with_exact_target(h: u32, s: u32, ) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(h as Weight))
+			.saturating_add((60_000_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(DbWeight::get().reads((6 as Weight).saturating_mul(h as Weight)))
+			.saturating_add(DbWeight::get().reads(s as Weight))
+			.saturating_add(DbWeight::get().writes((5 as Weight).saturating_mul(h as Weight)))
+	}
+	fn list_trading_pair() -> Weight {
+		(25_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn set_exchange_fee() -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn create_stable_pool() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	fn ramp_amplification() -> Weight {
+		(25_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn stop_amplification_ramp() -> Weight {
+		(25_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn add_stable_liquidity() -> Weight {
+		(400_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(15 as Weight))
+			.saturating_add(DbWeight::get().writes(14 as Weight))
+	}
+	fn remove_stable_liquidity() -> Weight {
+		(250_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(15 as Weight))
+			.saturating_add(DbWeight::get().writes(14 as Weight))
+	}
+}
@@ -0,0 +1,1082 @@
+//! Clover Dex Module
+//!
+//! ##Overview
+//! Constant product (x * y = k) automated market maker of the listed currency pairs.
+//! The liquidity of all the pairs is kept in the module account, the liquidity providers get the shares
+//! of the pair, which are reported to the incentives so the shares accrue the rewards of the incentive pool.
+//! The exchange fee is charged from the supply amount of the swaps and kept in the pool for the providers.
+//! The swaps could go through several pairs, the best path is searched among the listed pairs up to `MaxSwapHops` hops.
+//!
+//! The stable pools of 2 to 4 pegged currencies use the StableSwap invariant instead of the constant product,
+//! the amplification of them could be ramped by the governance. Each hop of a swap path goes through
+//! the pair or the stable pool which gives the best price, and the shares of the stable pools are reported
+//! to the incentives by the pool index. Each pair of currencies is in at most `MaxStablePoolsPerPair` stable pools,
+//! which bounds the stable pools checked by each hop.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{
+  decl_error, decl_event, decl_module, decl_storage, ensure,
+  traits::{EnsureOrigin, Get},
+  weights::Weight,
+  IterableStorageMap,
+};
+use frame_system::ensure_signed;
+use orml_traits::MultiCurrency;
+use orml_utilities::with_transaction_result;
+use sp_core::U256;
+use sp_runtime::{
+  traits::{AccountIdConversion, AtLeast32BitUnsigned, One, Zero},
+  DispatchError, DispatchResult,
+  FixedPointNumber,
+  ModuleId, RuntimeDebug, SaturatedConversion,
+};
+use sp_std::{collections::btree_set::BTreeSet, convert::TryFrom, prelude::*};
+
+use primitives::{Balance, CurrencyId, Rate, Share};
+use clover_traits::IncentiveOps;
+
+mod default_weight;
+#[cfg(feature = "runtime-benchmarks")]
+pub mod benchmarking;
+mod mock;
+pub mod stable_swap;
+mod tests;
+
+pub trait WeightInfo {
+  fn add_liquidity() -> Weight;
+  fn remove_liquidity() -> Weight;
+  fn swap_with_exact_supply(h: u32, s: u32, ) -> Weight;
+  fn swap_with_exact_target(h: u32, s: u32, ) -> Weight;
+  fn list_trading_pair() -> Weight;
+  fn set_exchange_fee() -> Weight;
+  fn create_stable_pool() -> Weight;
+  fn ramp_amplification() -> Weight;
+  fn stop_amplification_ramp() -> Weight;
+  fn add_stable_liquidity() -> Weight;
+  fn remove_stable_liquidity() -> Weight;
+}
+
+/// min and max number of the currencies of a stable pool
+pub const MIN_STABLE_CURRENCIES: usize = 2;
+pub const MAX_STABLE_CURRENCIES: usize = 4;
+/// max amplification of the stable pools
+pub const MAX_AMPLIFICATION: u128 = 1_000_000;
+/// the amplification could be changed by at most 10 times in a ramp
+pub const MAX_AMPLIFICATION_CHANGE: u128 = 10;
+
+/// A pair of different currencies, the currencies are sorted
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub struct TradingPair(CurrencyId, CurrencyId);
+
+impl TradingPair {
+  pub fn from_currencies(first: CurrencyId, second: CurrencyId) -> Option<Self> {
+    if first == second {
+      None
+    } else if first < second {
+      Some(TradingPair(first, second))
+    } else {
+      Some(TradingPair(second, first))
+    }
+  }
+
+  pub fn first(&self) -> CurrencyId {
+    self.0
+  }
+
+  pub fn second(&self) -> CurrencyId {
+    self.1
+  }
+}
+
+/// The index of the stable pools
+pub type StablePoolIndex = u32;
+
+/// A stable pool of 2 to 4 pegged currencies
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct StablePool<BlockNumber> {
+  /// the currencies of the pool, the amounts of the pool are in the same order
+  pub currencies: Vec<CurrencyId>,
+  /// the balances of the currencies
+  pub balances: Vec<Balance>,
+  pub total_shares: Share,
+  /// fee of the swaps and the imbalanced liquidity
+  pub fee: Rate,
+  /// the amplification is ramped linearly from the initial amplification at the initial block
+  /// to the future amplification at the future block
+  pub initial_amplification: u128,
+  pub future_amplification: u128,
+  pub initial_block: BlockNumber,
+  pub future_block: BlockNumber,
+}
+
+impl<BlockNumber: AtLeast32BitUnsigned + Copy> StablePool<BlockNumber> {
+  /// the amplification at the block
+  pub fn amplification(&self, now: BlockNumber) -> u128 {
+    if now >= self.future_block || self.future_block <= self.initial_block {
+      return self.future_amplification;
+    }
+    let elapsed: u128 = now.saturating_sub(self.initial_block).saturated_into();
+    let duration: u128 = (self.future_block - self.initial_block).saturated_into();
+    if self.future_amplification > self.initial_amplification {
+      let change = (self.future_amplification - self.initial_amplification).saturating_mul(elapsed) / duration;
+      self.initial_amplification.saturating_add(change)
+    } else {
+      let change = (self.initial_amplification - self.future_amplification).saturating_mul(elapsed) / duration;
+      self.initial_amplification.saturating_sub(change)
+    }
+  }
+
+  pub fn index_of(&self, currency_id: CurrencyId) -> Option<usize> {
+    self.currencies.iter().position(|c| *c == currency_id)
+  }
+
+  /// the pool could be swapped only if all the balances are not zero
+  pub fn is_active(&self) -> bool {
+    !self.balances.is_empty() && self.balances.iter().all(|balance| !balance.is_zero())
+  }
+}
+
+/// The pool which a hop of the swap path goes through
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum SwapPool {
+  /// the constant product pool of the pair
+  Pair(TradingPair),
+  /// the stable pool
+  Stable(StablePoolIndex),
+}
+
+pub trait Trait: frame_system::Config {
+  type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+
+  /// The currencies of the pairs
+  type Currency: MultiCurrency<Self::AccountId, CurrencyId = CurrencyId, Balance = Balance>;
+
+  /// The dex module id, the liquidity of all the pairs is kept in the module account
+  type ModuleId: Get<ModuleId>;
+
+  /// The incentives of the liquidity shares
+  type IncentiveOps: IncentiveOps<Self::AccountId, CurrencyId, Share, Balance, Self::BlockNumber>;
+
+  /// The max hops of a swap path
+  type MaxSwapHops: Get<u32>;
+
+  /// The min blocks of the amplification ramp of the stable pools
+  type MinAmplificationRampBlocks: Get<Self::BlockNumber>;
+
+  /// The max stable pools of a pair of currencies, each hop of a swap checks at most this number of stable pools
+  type MaxStablePoolsPerPair: Get<u32>;
+
+  /// The origin which may list the trading pairs, update the exchange fee and manage the stable pools
+  type UpdateOrigin: EnsureOrigin<Self::Origin>;
+
+  /// Weight information for the extrinsics in this module.
+  type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+  trait Store for Module<T: Trait> as Dex {
+    /// Listed trading pairs, the liquidity could only be added to the listed pairs
+    pub TradingPairs get(fn trading_pairs): map hasher(twox_64_concat) TradingPair => bool;
+
+    /// Liquidity of the pair: (balance of the first currency, balance of the second currency)
+    pub LiquidityPool get(fn liquidity_pool): map hasher(twox_64_concat) TradingPair => (Balance, Balance);
+
+    /// Total shares of the pair
+    pub TotalShares get(fn total_shares): map hasher(twox_64_concat) TradingPair => Share;
+
+    /// Shares of the account in the pair
+    pub Shares get(fn shares): double_map hasher(twox_64_concat) TradingPair, hasher(twox_64_concat) T::AccountId => Share;
+
+    /// Exchange fee which is charged from the supply amount of the swaps
+    pub ExchangeFee get(fn exchange_fee) config(): Rate;
+
+    /// The index of the next stable pool
+    pub NextStablePoolIndex get(fn next_stable_pool_index): StablePoolIndex;
+
+    /// The stable pools
+    pub StablePools get(fn stable_pools): map hasher(twox_64_concat) StablePoolIndex => Option<StablePool<T::BlockNumber>>;
+
+    /// The stable pools of each pair of their currencies
+    pub PairStablePools get(fn pair_stable_pools): map hasher(twox_64_concat) TradingPair => Vec<StablePoolIndex>;
+
+    /// Shares of the account in the stable pool
+    pub StableShares get(fn stable_shares): double_map hasher(twox_64_concat) StablePoolIndex, hasher(twox_64_concat) T::AccountId => Share;
+  }
+
+  add_extra_genesis {
+    config(trading_pairs): Vec<(CurrencyId, CurrencyId)>;
+
+    build(|config: &GenesisConfig| {
+      assert!(config.exchange_fee < Rate::one(), "exchange fee should be less than one");
+      for (first, second) in &config.trading_pairs {
+        let pair = TradingPair::from_currencies(*first, *second).expect("invalid trading pair");
+        TradingPairs::insert(pair, true);
+      }
+    })
+  }
+}
+
+decl_event!(
+  pub enum Event<T> where
+    <T as frame_system::Config>::AccountId,
+    <T as frame_system::Config>::BlockNumber,
+  {
+    /// Trading pair listed. \[first_currency, second_currency\]
+    TradingPairListed(CurrencyId, CurrencyId),
+    /// Liquidity added. \[who, first_currency, first_amount, second_currency, second_amount, shares\]
+    AddLiquidity(AccountId, CurrencyId, Balance, CurrencyId, Balance, Share),
+    /// Liquidity removed. \[who, first_currency, first_amount, second_currency, second_amount, shares\]
+    RemoveLiquidity(AccountId, CurrencyId, Balance, CurrencyId, Balance, Share),
+    /// Currency swapped, a swap path emits one event for each hop. \[who, supply_currency, supply_amount, target_currency, target_amount\]
+    Swap(AccountId, CurrencyId, Balance, CurrencyId, Balance),
+    /// Exchange fee updated. \[exchange_fee\]
+    ExchangeFeeUpdated(Rate),
+    /// Stable pool created. \[pool_index, currencies, amplification, fee\]
+    StablePoolCreated(StablePoolIndex, Vec<CurrencyId>, u128, Rate),
+    /// Amplification ramp of the stable pool started. \[pool_index, initial_amplification, future_amplification, future_block\]
+    AmplificationRamped(StablePoolIndex, u128, u128, BlockNumber),
+    /// Amplification ramp of the stable pool stopped. \[pool_index, amplification\]
+    AmplificationRampStopped(StablePoolIndex, u128),
+    /// Liquidity added to the stable pool. \[who, pool_index, amounts, shares\]
+    AddStableLiquidity(AccountId, StablePoolIndex, Vec<Balance>, Share),
+    /// Liquidity removed from the stable pool. \[who, pool_index, amounts, shares\]
+    RemoveStableLiquidity(AccountId, StablePoolIndex, Vec<Balance>, Share),
+    /// Currency swapped through the stable pool, a swap path emits one event for each hop.
+    /// \[who, pool_index, supply_currency, supply_amount, target_currency, target_amount\]
+    StableSwap(AccountId, StablePoolIndex, CurrencyId, Balance, CurrencyId, Balance),
+  }
+);
+
+decl_error! {
+  /// Error for dex module.
+  pub enum Error for Module<T: Trait> {
+    /// the currencies of the pair should be different
+    InvalidTradingPair,
+    /// trading pair is not listed
+    TradingPairNotListed,
+    /// trading pair is listed already
+    TradingPairAlreadyListed,
+    /// amount should be greater than zero
+    InvalidAmount,
+    /// the added liquidity is too small to get any shares
+    InvalidLiquidityIncrement,
+    /// not enough liquidity in the pair for the swap
+    InsufficientLiquidity,
+    /// account doesn't have enough shares of the pair
+    InsufficientShares,
+    /// target amount is less than the min target amount, or supply amount is greater than the max supply amount
+    SlippageExceeded,
+    /// exchange fee should be less than one
+    InvalidExchangeFee,
+    /// the swap path should have 1 to `MaxSwapHops` hops and shouldn't pass any currency twice
+    InvalidSwapPath,
+    /// a stable pool should have 2 to 4 different currencies
+    InvalidStablePoolCurrencies,
+    /// stable pool doesn't exist
+    StablePoolNotFound,
+    /// a pair of the currencies is already in `MaxStablePoolsPerPair` stable pools
+    TooManyStablePools,
+    /// the amounts should match the currencies of the stable pool, and the first liquidity should have all of them
+    InvalidStableAmounts,
+    /// amplification should be greater than zero and not greater than `MAX_AMPLIFICATION`
+    InvalidAmplification,
+    /// the ramp should last at least `MinAmplificationRampBlocks`, and change the amplification by at most 10 times
+    InvalidAmplificationRamp,
+    /// overflow in the calculation
+    ArithmeticOverflow,
+  }
+}
+
+decl_module! {
+  pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+    type Error = Error<T>;
+    fn deposit_event() = default;
+
+    const MaxSwapHops: u32 = T::MaxSwapHops::get();
+
+    const MinAmplificationRampBlocks: T::BlockNumber = T::MinAmplificationRampBlocks::get();
+
+    const MaxStablePoolsPerPair: u32 = T::MaxStablePoolsPerPair::get();
+
+    /// Add liquidity to the pair, the amounts are added in the ratio of the pair,
+    /// and the amounts are used as is for the first liquidity.
+    #[weight = T::WeightInfo::add_liquidity()]
+    pub fn add_liquidity(origin,
+                         currency_a: CurrencyId,
+                         currency_b: CurrencyId,
+                         #[compact] max_amount_a: Balance,
+                         #[compact] max_amount_b: Balance) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_add_liquidity(&who, currency_a, currency_b, max_amount_a, max_amount_b)?;
+        Ok(())
+      })?;
+    }
+
+    /// Remove liquidity from the pair, the currencies of the shares are sent to the account.
+    #[weight = T::WeightInfo::remove_liquidity()]
+    pub fn remove_liquidity(origin, currency_a: CurrencyId, currency_b: CurrencyId, #[compact] shares: Share) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_remove_liquidity(&who, currency_a, currency_b, shares)?;
+        Ok(())
+      })?;
+    }
+
+    /// Swap the exact supply amount for at least `min_target_amount` of the target currency.
+    #[weight = T::WeightInfo::swap_with_exact_supply(1, T::MaxStablePoolsPerPair::get())]
+    pub fn swap_with_exact_supply(origin,
+                                  supply_currency: CurrencyId,
+                                  target_currency: CurrencyId,
+                                  #[compact] supply_amount: Balance,
+                                  #[compact] min_target_amount: Balance) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_swap_with_exact_supply(&who, &[supply_currency, target_currency], supply_amount, min_target_amount)?;
+        Ok(())
+      })?;
+    }
+
+    /// Swap the exact supply amount of the first currency of the path through each hop of the path,
+    /// for at least `min_target_amount` of the last currency of the path.
+    #[weight = {
+      let hops = path.len().saturating_sub(1) as u32;
+      T::WeightInfo::swap_with_exact_supply(hops, hops.saturating_mul(T::MaxStablePoolsPerPair::get()))
+    }]
+    pub fn swap_with_exact_supply_by_path(origin,
+                                          path: Vec<CurrencyId>,
+                                          #[compact] supply_amount: Balance,
+                                          #[compact] min_target_amount: Balance) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_swap_with_exact_supply(&who, &path, supply_amount, min_target_amount)?;
+        Ok(())
+      })?;
+    }
+
+    /// Swap at most `max_supply_amount` of the supply currency for the exact target amount.
+    #[weight = T::WeightInfo::swap_with_exact_target(1, T::MaxStablePoolsPerPair::get())]
+    pub fn swap_with_exact_target(origin,
+                                  supply_currency: CurrencyId,
+                                  target_currency: CurrencyId,
+                                  #[compact] target_amount: Balance,
+                                  #[compact] max_supply_amount: Balance) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_swap_with_exact_target(&who, &[supply_currency, target_currency], target_amount, max_supply_amount)?;
+        Ok(())
+      })?;
+    }
+
+    /// Swap at most `max_supply_amount` of the first currency of the path through each hop of the path,
+    /// for the exact target amount of the last currency of the path.
+    #[weight = {
+      let hops = path.len().saturating_sub(1) as u32;
+      T::WeightInfo::swap_with_exact_target(hops, hops.saturating_mul(T::MaxStablePoolsPerPair::get()))
+    }]
+    pub fn swap_with_exact_target_by_path(origin,
+                                          path: Vec<CurrencyId>,
+                                          #[compact] target_amount: Balance,
+                                          #[compact] max_supply_amount: Balance) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_swap_with_exact_target(&who, &path, target_amount, max_supply_amount)?;
+        Ok(())
+      })?;
+    }
+
+    /// List the trading pair, so the liquidity could be added to it.
+    #[weight = T::WeightInfo::list_trading_pair()]
+    pub fn list_trading_pair(origin, currency_a: CurrencyId, currency_b: CurrencyId) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      let pair = Self::get_trading_pair(currency_a, currency_b)?;
+      ensure!(!Self::trading_pairs(pair), Error::<T>::TradingPairAlreadyListed);
+
+      TradingPairs::insert(pair, true);
+      Self::deposit_event(RawEvent::TradingPairListed(pair.first(), pair.second()));
+    }
+
+    /// Set the exchange fee of the swaps.
+    #[weight = T::WeightInfo::set_exchange_fee()]
+    pub fn set_exchange_fee(origin, exchange_fee: Rate) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      ensure!(exchange_fee < Rate::one(), Error::<T>::InvalidExchangeFee);
+
+      ExchangeFee::put(exchange_fee);
+      Self::deposit_event(RawEvent::ExchangeFeeUpdated(exchange_fee));
+    }
+
+    /// Create a stable pool of 2 to 4 different pegged currencies with the amplification and the fee.
+    #[weight = T::WeightInfo::create_stable_pool()]
+    pub fn create_stable_pool(origin, currencies: Vec<CurrencyId>, amplification: u128, fee: Rate) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      ensure!(currencies.len() >= MIN_STABLE_CURRENCIES && currencies.len() <= MAX_STABLE_CURRENCIES,
+              Error::<T>::InvalidStablePoolCurrencies);
+      for (i, currency_id) in currencies.iter().enumerate() {
+        ensure!(!currencies[i + 1..].contains(currency_id), Error::<T>::InvalidStablePoolCurrencies);
+      }
+      ensure!(!amplification.is_zero() && amplification <= MAX_AMPLIFICATION, Error::<T>::InvalidAmplification);
+      ensure!(fee < Rate::one(), Error::<T>::InvalidExchangeFee);
+      let pairs = Self::stable_pairs(&currencies);
+      for pair in pairs.iter() {
+        ensure!((Self::pair_stable_pools(pair).len() as u32) < T::MaxStablePoolsPerPair::get(),
+                Error::<T>::TooManyStablePools);
+      }
+
+      let pool_index = NextStablePoolIndex::try_mutate(|index| -> Result<StablePoolIndex, DispatchError> {
+        let current = *index;
+        *index = index.checked_add(1).ok_or(Error::<T>::ArithmeticOverflow)?;
+        Ok(current)
+      })?;
+      let now = frame_system::Module::<T>::block_number();
+      <StablePools<T>>::insert(pool_index, StablePool {
+        currencies: currencies.clone(),
+        balances: vec![Zero::zero(); currencies.len()],
+        total_shares: Zero::zero(),
+        fee,
+        initial_amplification: amplification,
+        future_amplification: amplification,
+        initial_block: now,
+        future_block: now,
+      });
+      for pair in pairs {
+        PairStablePools::append(pair, pool_index);
+      }
+      Self::deposit_event(RawEvent::StablePoolCreated(pool_index, currencies, amplification, fee));
+    }
+
+    /// Ramp the amplification of the stable pool linearly from the current amplification
+    /// to the future amplification at the future block.
+    #[weight = T::WeightInfo::ramp_amplification()]
+    pub fn ramp_amplification(origin, pool_index: StablePoolIndex, future_amplification: u128, future_block: T::BlockNumber) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      ensure!(!future_amplification.is_zero() && future_amplification <= MAX_AMPLIFICATION,
+              Error::<T>::InvalidAmplification);
+      let now = frame_system::Module::<T>::block_number();
+      ensure!(future_block >= now.saturating_add(T::MinAmplificationRampBlocks::get()), Error::<T>::InvalidAmplificationRamp);
+
+      <StablePools<T>>::try_mutate(pool_index, |maybe_pool| -> DispatchResult {
+        let pool = maybe_pool.as_mut().ok_or(Error::<T>::StablePoolNotFound)?;
+        let current = pool.amplification(now);
+        ensure!(future_amplification <= current.saturating_mul(MAX_AMPLIFICATION_CHANGE) &&
+                future_amplification.saturating_mul(MAX_AMPLIFICATION_CHANGE) >= current,
+                Error::<T>::InvalidAmplificationRamp);
+
+        pool.initial_amplification = current;
+        pool.future_amplification = future_amplification;
+        pool.initial_block = now;
+        pool.future_block = future_block;
+        Self::deposit_event(RawEvent::AmplificationRamped(pool_index, current, future_amplification, future_block));
+        Ok(())
+      })?;
+    }
+
+    /// Stop the amplification ramp of the stable pool at the current amplification.
+    #[weight = T::WeightInfo::stop_amplification_ramp()]
+    pub fn stop_amplification_ramp(origin, pool_index: StablePoolIndex) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      let now = frame_system::Module::<T>::block_number();
+
+      <StablePools<T>>::try_mutate(pool_index, |maybe_pool| -> DispatchResult {
+        let pool = maybe_pool.as_mut().ok_or(Error::<T>::StablePoolNotFound)?;
+        let current = pool.amplification(now);
+        pool.initial_amplification = current;
+        pool.future_amplification = current;
+        pool.initial_block = now;
+        pool.future_block = now;
+        Self::deposit_event(RawEvent::AmplificationRampStopped(pool_index, current));
+        Ok(())
+      })?;
+    }
+
+    /// Add liquidity to the stable pool, the amounts are in the order of the pool currencies,
+    /// the first liquidity should have all the currencies and the imbalanced amounts are charged the fee.
+    #[weight = T::WeightInfo::add_stable_liquidity()]
+    pub fn add_stable_liquidity(origin, pool_index: StablePoolIndex, amounts: Vec<Balance>, #[compact] min_shares: Share) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_add_stable_liquidity(&who, pool_index, amounts, min_shares)?;
+        Ok(())
+      })?;
+    }
+
+    /// Remove liquidity from the stable pool, the currencies are sent in the ratio of the pool balances.
+    #[weight = T::WeightInfo::remove_stable_liquidity()]
+    pub fn remove_stable_liquidity(origin, pool_index: StablePoolIndex, #[compact] shares: Share, min_amounts: Vec<Balance>) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_remove_stable_liquidity(&who, pool_index, shares, min_amounts)?;
+        Ok(())
+      })?;
+    }
+  }
+}
+
+impl<T: Trait> Module<T> {
+  pub fn account_id() -> T::AccountId {
+    T::ModuleId::get().into_account()
+  }
+
+  fn get_trading_pair(currency_a: CurrencyId, currency_b: CurrencyId) -> Result<TradingPair, DispatchError> {
+    TradingPair::from_currencies(currency_a, currency_b).ok_or_else(|| Error::<T>::InvalidTradingPair.into())
+  }
+
+  /// amount * numerator / denominator, rounded down
+  fn proportion(amount: Balance, numerator: Balance, denominator: Balance) -> Result<Balance, DispatchError> {
+    U256::from(amount).checked_mul(U256::from(numerator))
+      .and_then(|n| n.checked_div(U256::from(denominator)))
+      .and_then(|r| Balance::try_from(r).ok())
+      .ok_or_else(|| Error::<T>::ArithmeticOverflow.into())
+  }
+
+  /// returns the pair and the liquidity of the supply and the target currency
+  fn get_liquidity(supply_currency: CurrencyId, target_currency: CurrencyId)
+                   -> Result<(TradingPair, Balance, Balance), DispatchError> {
+    let pair = Self::get_trading_pair(supply_currency, target_currency)?;
+    let (pool_first, pool_second) = Self::liquidity_pool(pair);
+    if supply_currency == pair.first() {
+      Ok((pair, pool_first, pool_second))
+    } else {
+      Ok((pair, pool_second, pool_first))
+    }
+  }
+
+  /// the target amount of the supply amount after the exchange fee,
+  /// zero if there is no liquidity or the calculation overflows
+  pub fn get_target_amount(supply_pool: Balance, target_pool: Balance, supply_amount: Balance) -> Balance {
+    if supply_pool.is_zero() || target_pool.is_zero() || supply_amount.is_zero() {
+      return Zero::zero();
+    }
+
+    let accuracy = U256::from(Rate::accuracy());
+    let calc_amount = || -> Option<Balance> {
+      let supply_after_fee = U256::from(supply_amount)
+        .checked_mul(accuracy.checked_sub(U256::from(Self::exchange_fee().into_inner()))?)?;
+      let numerator = supply_after_fee.checked_mul(U256::from(target_pool))?;
+      let denominator = U256::from(supply_pool).checked_mul(accuracy)?.checked_add(supply_after_fee)?;
+      Balance::try_from(numerator.checked_div(denominator)?).ok()
+    };
+    calc_amount().unwrap_or_else(Zero::zero)
+  }
+
+  /// the supply amount needed for the target amount after the exchange fee, rounded up,
+  /// zero if there is not enough liquidity or the calculation overflows
+  pub fn get_supply_amount(supply_pool: Balance, target_pool: Balance, target_amount: Balance) -> Balance {
+    if supply_pool.is_zero() || target_amount.is_zero() || target_amount >= target_pool {
+      return Zero::zero();
+    }
+
+    let accuracy = U256::from(Rate::accuracy());
+    let calc_amount = || -> Option<Balance> {
+      let numerator = U256::from(supply_pool)
+        .checked_mul(U256::from(target_amount))?
+        .checked_mul(accuracy)?;
+      let denominator = U256::from(target_pool - target_amount)
+        .checked_mul(accuracy.checked_sub(U256::from(Self::exchange_fee().into_inner()))?)?;
+      Balance::try_from(numerator.checked_div(denominator)?).ok()?.checked_add(One::one())
+    };
+    calc_amount().unwrap_or_else(Zero::zero)
+  }
+
+  fn do_add_liquidity(who: &T::AccountId,
+                      currency_a: CurrencyId,
+                      currency_b: CurrencyId,
+                      max_amount_a: Balance,
+                      max_amount_b: Balance) -> Result<Share, DispatchError> {
+    let pair = Self::get_trading_pair(currency_a, currency_b)?;
+    ensure!(Self::trading_pairs(pair), Error::<T>::TradingPairNotListed);
+    ensure!(!max_amount_a.is_zero() && !max_amount_b.is_zero(), Error::<T>::InvalidAmount);
+
+    let (max_first, max_second) = if currency_a == pair.first() {
+      (max_amount_a, max_amount_b)
+    } else {
+      (max_amount_b, max_amount_a)
+    };
+    let (first, second, shares) = Self::calc_liquidity_increment(pair, max_first, max_second)?;
+    ensure!(!first.is_zero() && !second.is_zero() && !shares.is_zero(), Error::<T>::InvalidLiquidityIncrement);
+
+    let module_account = Self::account_id();
+    T::Currency::transfer(pair.first(), who, &module_account, first)?;
+    T::Currency::transfer(pair.second(), who, &module_account, second)?;
+
+    let (pool_first, pool_second) = Self::liquidity_pool(pair);
+    let pool_first = pool_first.checked_add(first).ok_or(Error::<T>::ArithmeticOverflow)?;
+    let pool_second = pool_second.checked_add(second).ok_or(Error::<T>::ArithmeticOverflow)?;
+    let total_shares = Self::total_shares(pair).checked_add(shares).ok_or(Error::<T>::ArithmeticOverflow)?;
+    LiquidityPool::insert(pair, (pool_first, pool_second));
+    TotalShares::insert(pair, total_shares);
+    <Shares<T>>::mutate(pair, who, |account_shares| *account_shares = account_shares.saturating_add(shares));
+
+    T::IncentiveOps::add_share(who, &pair.first(), &pair.second(), &shares)?;
+
+    Self::deposit_event(RawEvent::AddLiquidity(who.clone(), pair.first(), first, pair.second(), second, shares));
+    Ok(shares)
+  }
+
+  /// the amounts of the pair currencies which are added for the max amounts and the shares of them
+  fn calc_liquidity_increment(pair: TradingPair, max_first: Balance, max_second: Balance)
+                              -> Result<(Balance, Balance, Share), DispatchError> {
+    let (pool_first, pool_second) = Self::liquidity_pool(pair);
+    let total_shares = Self::total_shares(pair);
+
+    if total_shares.is_zero() {
+      // the first shares are the geometric mean of the amounts
+      let shares = U256::from(max_first).saturating_mul(U256::from(max_second)).integer_sqrt();
+      let shares = Share::try_from(shares).map_err(|_| Error::<T>::ArithmeticOverflow)?;
+      Ok((max_first, max_second, shares))
+    } else {
+      let second_needed = Self::proportion(max_first, pool_second, pool_first)?;
+      let (first, second) = if second_needed <= max_second {
+        (max_first, second_needed)
+      } else {
+        (Self::proportion(max_second, pool_first, pool_second)?, max_second)
+      };
+      // the shares of the both currencies are the same except the rounding, which is in favor of the pool
+      let shares = Self::proportion(first, total_shares, pool_first)?
+        .min(Self::proportion(second, total_shares, pool_second)?);
+      Ok((first, second, shares))
+    }
+  }
+
+  /// the shares of adding the liquidity and the total shares after it, (0, 0) for an invalid increment
+  pub fn to_add_liquidity(currency_a: CurrencyId, currency_b: CurrencyId,
+                          max_amount_a: Balance, max_amount_b: Balance) -> (Share, Share) {
+    let pair = match TradingPair::from_currencies(currency_a, currency_b) {
+      Some(pair) => pair,
+      None => return (Zero::zero(), Zero::zero()),
+    };
+    let (max_first, max_second) = if currency_a == pair.first() {
+      (max_amount_a, max_amount_b)
+    } else {
+      (max_amount_b, max_amount_a)
+    };
+    match Self::calc_liquidity_increment(pair, max_first, max_second) {
+      Ok((first, second, shares)) if !first.is_zero() && !second.is_zero() && !shares.is_zero() =>
+        (shares, Self::total_shares(pair).saturating_add(shares)),
+      _ => (Zero::zero(), Zero::zero()),
+    }
+  }
+
+  /// the liquidity of all the pairs with the shares of the account:
+  /// (first currency, second currency, first balance, second balance, total shares, account shares)
+  pub fn get_pools_liquidity(who: Option<&T::AccountId>)
+                             -> Vec<(CurrencyId, CurrencyId, Balance, Balance, Share, Share)> {
+    LiquidityPool::iter()
+      .map(|(pair, (pool_first, pool_second))| {
+        let account_shares = who.map(|who| Self::shares(pair, who)).unwrap_or_default();
+        (pair.first(), pair.second(), pool_first, pool_second, Self::total_shares(pair), account_shares)
+      })
+      .collect()
+  }
+
+  fn do_remove_liquidity(who: &T::AccountId,
+                         currency_a: CurrencyId,
+                         currency_b: CurrencyId,
+                         shares: Share) -> Result<(Balance, Balance), DispatchError> {
+    let pair = Self::get_trading_pair(currency_a, currency_b)?;
+    ensure!(!shares.is_zero(), Error::<T>::InvalidAmount);
+    let account_shares = Self::shares(pair, who);
+    ensure!(account_shares >= shares, Error::<T>::InsufficientShares);
+
+    let (pool_first, pool_second) = Self::liquidity_pool(pair);
+    let total_shares = Self::total_shares(pair);
+    let first = Self::proportion(shares, pool_first, total_shares)?;
+    let second = Self::proportion(shares, pool_second, total_shares)?;
+
+    T::IncentiveOps::remove_share(who, &pair.first(), &pair.second(), &shares)?;
+
+    let module_account = Self::account_id();
+    T::Currency::transfer(pair.first(), &module_account, who, first)?;
+    T::Currency::transfer(pair.second(), &module_account, who, second)?;
+
+    LiquidityPool::insert(pair, (pool_first - first, pool_second - second));
+    TotalShares::insert(pair, total_shares - shares);
+    if account_shares == shares {
+      <Shares<T>>::remove(pair, who);
+    } else {
+      <Shares<T>>::insert(pair, who, account_shares - shares);
+    }
+
+    Self::deposit_event(RawEvent::RemoveLiquidity(who.clone(), pair.first(), first, pair.second(), second, shares));
+    Ok((first, second))
+  }
+
+  /// the listed trading pairs
+  pub fn get_trading_pairs() -> Vec<(CurrencyId, CurrencyId)> {
+    TradingPairs::iter()
+      .filter(|(_, listed)| *listed)
+      .map(|(pair, _)| (pair.first(), pair.second()))
+      .collect()
+  }
+
+  /// all the paths from the supply currency to the target currency through the listed pairs with liquidity
+  /// and the currencies of the active stable pools,
+  /// the paths have at most `MaxSwapHops` hops and don't pass any currency twice
+  pub fn find_paths(supply_currency: CurrencyId, target_currency: CurrencyId) -> Vec<Vec<CurrencyId>> {
+    let mut paths = vec![];
+    if supply_currency == target_currency {
+      return paths;
+    }
+
+    let mut pairs: BTreeSet<TradingPair> = TradingPairs::iter()
+      .filter(|(pair, listed)| {
+        let (pool_first, pool_second) = Self::liquidity_pool(pair);
+        *listed && !pool_first.is_zero() && !pool_second.is_zero()
+      })
+      .map(|(pair, _)| pair)
+      .collect();
+    for (_, pool) in <StablePools<T>>::iter().filter(|(_, pool)| pool.is_active()) {
+      pairs.extend(Self::stable_pairs(&pool.currencies));
+    }
+    let pairs: Vec<TradingPair> = pairs.into_iter().collect();
+    let mut path = vec![supply_currency];
+    Self::search_paths(&pairs, target_currency, T::MaxSwapHops::get() as usize, &mut path, &mut paths);
+    paths
+  }
+
+  /// the pairs of each two currencies of a stable pool
+  fn stable_pairs(currencies: &[CurrencyId]) -> Vec<TradingPair> {
+    let mut pairs = vec![];
+    for (i, first) in currencies.iter().enumerate() {
+      for second in &currencies[i + 1..] {
+        pairs.extend(TradingPair::from_currencies(*first, *second));
+      }
+    }
+    pairs
+  }
+
+  /// the stable pools of the pair of the supply currency and the target currency,
+  /// the pools used by the other hops are skipped since their balances would be changed
+  fn hop_stable_pools(supply_currency: CurrencyId, target_currency: CurrencyId, used_pools: &[SwapPool])
+                      -> Vec<(StablePoolIndex, StablePool<T::BlockNumber>)> {
+    TradingPair::from_currencies(supply_currency, target_currency)
+      .map(Self::pair_stable_pools)
+      .unwrap_or_default()
+      .into_iter()
+      .filter(|pool_index| !used_pools.contains(&SwapPool::Stable(*pool_index)))
+      .filter_map(|pool_index| Self::stable_pools(pool_index).map(|pool| (pool_index, pool)))
+      .collect()
+  }
+
+  /// depth first search of the paths which extend the current path to the target currency
+  fn search_paths(pairs: &[TradingPair],
+                  target_currency: CurrencyId,
+                  max_hops: usize,
+                  path: &mut Vec<CurrencyId>,
+                  paths: &mut Vec<Vec<CurrencyId>>) {
+    // the hops of the path is path.len() - 1
+    let current = match path.last() {
+      Some(current) if path.len() <= max_hops => *current,
+      _ => return,
+    };
+
+    for pair in pairs {
+      let next = if pair.first() == current {
+        pair.second()
+      } else if pair.second() == current {
+        pair.first()
+      } else {
+        continue;
+      };
+      if path.contains(&next) {
+        continue;
+      }
+
+      path.push(next);
+      if next == target_currency {
+        paths.push(path.clone());
+      } else {
+        Self::search_paths(pairs, target_currency, max_hops, path, paths);
+      }
+      path.pop();
+    }
+  }
+
+  /// the amounts of each currency of the path for the exact supply amount,
+  /// None if there is not enough liquidity in any hop
+  pub fn get_target_amounts(path: &[CurrencyId], supply_amount: Balance) -> Option<Vec<Balance>> {
+    Self::get_target_route(path, supply_amount).map(|(amounts, _)| amounts)
+  }
+
+  /// the amounts of each currency of the path for the exact target amount,
+  /// None if there is not enough liquidity in any hop
+  pub fn get_supply_amounts(path: &[CurrencyId], target_amount: Balance) -> Option<Vec<Balance>> {
+    Self::get_supply_route(path, target_amount).map(|(amounts, _)| amounts)
+  }
+
+  /// the amounts of each currency of the path for the exact supply amount and the pool of each hop,
+  /// each hop goes through the pool with the max target amount
+  pub fn get_target_route(path: &[CurrencyId], supply_amount: Balance) -> Option<(Vec<Balance>, Vec<SwapPool>)> {
+    let mut amounts = vec![supply_amount];
+    let mut pools = vec![];
+    for hop in path.windows(2) {
+      let (pool, target_amount) = Self::best_target_pool(hop[0], hop[1], *amounts.last()?, &pools)?;
+      amounts.push(target_amount);
+      pools.push(pool);
+    }
+    Some((amounts, pools))
+  }
+
+  /// the amounts of each currency of the path for the exact target amount and the pool of each hop,
+  /// each hop goes through the pool with the min supply amount
+  pub fn get_supply_route(path: &[CurrencyId], target_amount: Balance) -> Option<(Vec<Balance>, Vec<SwapPool>)> {
+    let mut amounts = vec![target_amount];
+    let mut pools = vec![];
+    for hop in path.windows(2).rev() {
+      let (pool, supply_amount) = Self::best_supply_pool(hop[0], hop[1], *amounts.first()?, &pools)?;
+      amounts.insert(0, supply_amount);
+      pools.insert(0, pool);
+    }
+    Some((amounts, pools))
+  }
+
+  /// the pool with the max target amount of the hop, the pair is preferred if the amounts are the same
+  fn best_target_pool(supply_currency: CurrencyId, target_currency: CurrencyId, supply_amount: Balance,
+                      used_pools: &[SwapPool]) -> Option<(SwapPool, Balance)> {
+    let mut amounts = vec![];
+    if let Ok((pair, supply_pool, target_pool)) = Self::get_liquidity(supply_currency, target_currency) {
+      amounts.push((SwapPool::Pair(pair), Self::get_target_amount(supply_pool, target_pool, supply_amount)));
+    }
+    let now = frame_system::Module::<T>::block_number();
+    for (pool_index, pool) in Self::hop_stable_pools(supply_currency, target_currency, used_pools) {
+      if let (Some(i), Some(j)) = (pool.index_of(supply_currency), pool.index_of(target_currency)) {
+        let target_amount = stable_swap::get_target_amount(
+          &pool.balances, pool.amplification(now), pool.fee, i, j, supply_amount);
+        amounts.extend(target_amount.map(|amount| (SwapPool::Stable(pool_index), amount)));
+      }
+    }
+    amounts.into_iter()
+      .filter(|(_, amount)| !amount.is_zero())
+      .fold(None, |best, (pool, amount)| match best {
+        Some((_, best_amount)) if best_amount >= amount => best,
+        _ => Some((pool, amount)),
+      })
+  }
+
+  /// the pool with the min supply amount of the hop, the pair is preferred if the amounts are the same
+  fn best_supply_pool(supply_currency: CurrencyId, target_currency: CurrencyId, target_amount: Balance,
+                      used_pools: &[SwapPool]) -> Option<(SwapPool, Balance)> {
+    let mut amounts = vec![];
+    if let Ok((pair, supply_pool, target_pool)) = Self::get_liquidity(supply_currency, target_currency) {
+      amounts.push((SwapPool::Pair(pair), Self::get_supply_amount(supply_pool, target_pool, target_amount)));
+    }
+    let now = frame_system::Module::<T>::block_number();
+    for (pool_index, pool) in Self::hop_stable_pools(supply_currency, target_currency, used_pools) {
+      if let (Some(i), Some(j)) = (pool.index_of(supply_currency), pool.index_of(target_currency)) {
+        let supply_amount = stable_swap::get_supply_amount(
+          &pool.balances, pool.amplification(now), pool.fee, i, j, target_amount);
+        amounts.extend(supply_amount.map(|amount| (SwapPool::Stable(pool_index), amount)));
+      }
+    }
+    amounts.into_iter()
+      .filter(|(_, amount)| !amount.is_zero())
+      .fold(None, |best, (pool, amount)| match best {
+        Some((_, best_amount)) if best_amount <= amount => best,
+        _ => Some((pool, amount)),
+      })
+  }
+
+  /// the max target amount of the supply amount and its path, (0, []) if there is no path
+  /// the shorter path is preferred if the amounts are the same
+  pub fn target_amount_available(supply_currency: CurrencyId, target_currency: CurrencyId,
+                                 supply_amount: Balance) -> (Balance, Vec<CurrencyId>) {
+    Self::find_paths(supply_currency, target_currency).into_iter()
+      .filter_map(|path| {
+        let target_amount = *Self::get_target_amounts(&path, supply_amount)?.last()?;
+        Some((target_amount, path))
+      })
+      .max_by(|(a, path_a), (b, path_b)| a.cmp(b).then_with(|| path_b.len().cmp(&path_a.len())))
+      .unwrap_or_default()
+  }
+
+  /// the min supply amount of the target amount and its path, (0, []) if there is no path
+  /// the shorter path is preferred if the amounts are the same
+  pub fn supply_amount_needed(supply_currency: CurrencyId, target_currency: CurrencyId,
+                              target_amount: Balance) -> (Balance, Vec<CurrencyId>) {
+    Self::find_paths(supply_currency, target_currency).into_iter()
+      .filter_map(|path| {
+        let supply_amount = *Self::get_supply_amounts(&path, target_amount)?.first()?;
+        Some((supply_amount, path))
+      })
+      .min_by(|(a, path_a), (b, path_b)| a.cmp(b).then_with(|| path_a.len().cmp(&path_b.len())))
+      .unwrap_or_default()
+  }
+
+  fn ensure_valid_path(path: &[CurrencyId]) -> DispatchResult {
+    ensure!(path.len() >= 2 && path.len() <= (T::MaxSwapHops::get() as usize).saturating_add(1),
+            Error::<T>::InvalidSwapPath);
+    for hop in path.windows(2) {
+      Self::get_trading_pair(hop[0], hop[1])?;
+    }
+    for (i, currency_id) in path.iter().enumerate() {
+      ensure!(!path[i + 1..].contains(currency_id), Error::<T>::InvalidSwapPath);
+    }
+    Ok(())
+  }
+
+  fn do_swap_with_exact_supply(who: &T::AccountId,
+                               path: &[CurrencyId],
+                               supply_amount: Balance,
+                               min_target_amount: Balance) -> Result<Balance, DispatchError> {
+    ensure!(!supply_amount.is_zero(), Error::<T>::InvalidAmount);
+    Self::ensure_valid_path(path)?;
+    let (amounts, pools) = Self::get_target_route(path, supply_amount).ok_or(Error::<T>::InsufficientLiquidity)?;
+    let target_amount = amounts.last().cloned().unwrap_or_default();
+    ensure!(target_amount >= min_target_amount, Error::<T>::SlippageExceeded);
+
+    Self::do_swap_by_path(who, path, &amounts, &pools)?;
+    Ok(target_amount)
+  }
+
+  fn do_swap_with_exact_target(who: &T::AccountId,
+                               path: &[CurrencyId],
+                               target_amount: Balance,
+                               max_supply_amount: Balance) -> Result<Balance, DispatchError> {
+    ensure!(!target_amount.is_zero(), Error::<T>::InvalidAmount);
+    Self::ensure_valid_path(path)?;
+    let (amounts, pools) = Self::get_supply_route(path, target_amount).ok_or(Error::<T>::InsufficientLiquidity)?;
+    let supply_amount = amounts.first().cloned().unwrap_or_default();
+    ensure!(supply_amount <= max_supply_amount, Error::<T>::SlippageExceeded);
+
+    Self::do_swap_by_path(who, path, &amounts, &pools)?;
+    Ok(supply_amount)
+  }
+
+  /// swap each hop of the path through its pool, `amounts` are the amounts of each currency of the path
+  fn do_swap_by_path(who: &T::AccountId, path: &[CurrencyId], amounts: &[Balance], pools: &[SwapPool]) -> DispatchResult {
+    for (i, (hop, pool)) in path.windows(2).zip(pools.iter()).enumerate() {
+      match pool {
+        SwapPool::Pair(pair) => Self::do_swap(who, *pair, hop[0], amounts[i], hop[1], amounts[i + 1])?,
+        SwapPool::Stable(pool_index) =>
+          Self::do_stable_swap(who, *pool_index, hop[0], amounts[i], hop[1], amounts[i + 1])?,
+      }
+    }
+    Ok(())
+  }
+
+  /// move the currencies of the swap and update the liquidity of the pair
+  fn do_swap(who: &T::AccountId,
+             pair: TradingPair,
+             supply_currency: CurrencyId,
+             supply_amount: Balance,
+             target_currency: CurrencyId,
+             target_amount: Balance) -> DispatchResult {
+    let module_account = Self::account_id();
+    T::Currency::transfer(supply_currency, who, &module_account, supply_amount)?;
+    T::Currency::transfer(target_currency, &module_account, who, target_amount)?;
+
+    LiquidityPool::try_mutate(pair, |(pool_first, pool_second)| -> DispatchResult {
+      let (supply_pool, target_pool) = if supply_currency == pair.first() {
+        (pool_first, pool_second)
+      } else {
+        (pool_second, pool_first)
+      };
+      *supply_pool = supply_pool.checked_add(supply_amount).ok_or(Error::<T>::ArithmeticOverflow)?;
+      *target_pool = target_pool.checked_sub(target_amount).ok_or(Error::<T>::InsufficientLiquidity)?;
+      Ok(())
+    })?;
+
+    Self::deposit_event(RawEvent::Swap(who.clone(), supply_currency, supply_amount, target_currency, target_amount));
+    Ok(())
+  }
+
+  /// move the currencies of the swap and update the balances of the stable pool
+  fn do_stable_swap(who: &T::AccountId,
+                    pool_index: StablePoolIndex,
+                    supply_currency: CurrencyId,
+                    supply_amount: Balance,
+                    target_currency: CurrencyId,
+                    target_amount: Balance) -> DispatchResult {
+    let module_account = Self::account_id();
+    T::Currency::transfer(supply_currency, who, &module_account, supply_amount)?;
+    T::Currency::transfer(target_currency, &module_account, who, target_amount)?;
+
+    <StablePools<T>>::try_mutate(pool_index, |maybe_pool| -> DispatchResult {
+      let pool = maybe_pool.as_mut().ok_or(Error::<T>::StablePoolNotFound)?;
+      let i = pool.index_of(supply_currency).ok_or(Error::<T>::InvalidSwapPath)?;
+      let j = pool.index_of(target_currency).ok_or(Error::<T>::InvalidSwapPath)?;
+      pool.balances[i] = pool.balances[i].checked_add(supply_amount).ok_or(Error::<T>::ArithmeticOverflow)?;
+      pool.balances[j] = pool.balances[j].checked_sub(target_amount).ok_or(Error::<T>::InsufficientLiquidity)?;
+      Ok(())
+    })?;
+
+    Self::deposit_event(RawEvent::StableSwap(who.clone(), pool_index, supply_currency, supply_amount,
+                                             target_currency, target_amount));
+    Ok(())
+  }
+
+  fn do_add_stable_liquidity(who: &T::AccountId,
+                             pool_index: StablePoolIndex,
+                             amounts: Vec<Balance>,
+                             min_shares: Share) -> Result<Share, DispatchError> {
+    let mut pool = Self::stable_pools(pool_index).ok_or(Error::<T>::StablePoolNotFound)?;
+    ensure!(amounts.len() == pool.currencies.len(), Error::<T>::InvalidStableAmounts);
+    ensure!(amounts.iter().any(|amount| !amount.is_zero()), Error::<T>::InvalidAmount);
+    if pool.total_shares.is_zero() {
+      ensure!(amounts.iter().all(|amount| !amount.is_zero()), Error::<T>::InvalidStableAmounts);
+    }
+
+    let now = frame_system::Module::<T>::block_number();
+    let (shares, balances) = stable_swap::calc_add_liquidity(
+      &pool.balances, pool.amplification(now), pool.fee, pool.total_shares, &amounts)
+      .ok_or(Error::<T>::InvalidLiquidityIncrement)?;
+    ensure!(!shares.is_zero(), Error::<T>::InvalidLiquidityIncrement);
+    ensure!(shares >= min_shares, Error::<T>::SlippageExceeded);
+
+    let module_account = Self::account_id();
+    for (currency_id, amount) in pool.currencies.iter().zip(amounts.iter()) {
+      if !amount.is_zero() {
+        T::Currency::transfer(*currency_id, who, &module_account, *amount)?;
+      }
+    }
+
+    pool.balances = balances;
+    pool.total_shares = pool.total_shares.checked_add(shares).ok_or(Error::<T>::ArithmeticOverflow)?;
+    <StablePools<T>>::insert(pool_index, pool);
+    <StableShares<T>>::mutate(pool_index, who, |account_shares| *account_shares = account_shares.saturating_add(shares));
+
+    T::IncentiveOps::add_stable_share(who, pool_index, &shares)?;
+
+    Self::deposit_event(RawEvent::AddStableLiquidity(who.clone(), pool_index, amounts, shares));
+    Ok(shares)
+  }
+
+  fn do_remove_stable_liquidity(who: &T::AccountId,
+                                pool_index: StablePoolIndex,
+                                shares: Share,
+                                min_amounts: Vec<Balance>) -> Result<Vec<Balance>, DispatchError> {
+    ensure!(!shares.is_zero(), Error::<T>::InvalidAmount);
+    let mut pool = Self::stable_pools(pool_index).ok_or(Error::<T>::StablePoolNotFound)?;
+    ensure!(min_amounts.len() == pool.currencies.len(), Error::<T>::InvalidStableAmounts);
+    let account_shares = Self::stable_shares(pool_index, who);
+    ensure!(account_shares >= shares, Error::<T>::InsufficientShares);
+
+    let amounts = pool.balances.iter()
+      .map(|balance| Self::proportion(shares, *balance, pool.total_shares))
+      .collect::<Result<Vec<Balance>, DispatchError>>()?;
+    for (amount, min_amount) in amounts.iter().zip(min_amounts.iter()) {
+      ensure!(amount >= min_amount, Error::<T>::SlippageExceeded);
+    }
+
+    T::IncentiveOps::remove_stable_share(who, pool_index, &shares)?;
+
+    let module_account = Self::account_id();
+    for (i, amount) in amounts.iter().enumerate() {
+      if !amount.is_zero() {
+        T::Currency::transfer(pool.currencies[i], &module_account, who, *amount)?;
+      }
+      pool.balances[i] -= amount;
+    }
+    pool.total_shares -= shares;
+    <StablePools<T>>::insert(pool_index, pool);
+    if account_shares == shares {
+      <StableShares<T>>::remove(pool_index, who);
+    } else {
+      <StableShares<T>>::insert(pool_index, who, account_shares - shares);
+    }
+
+    Self::deposit_event(RawEvent::RemoveStableLiquidity(who.clone(), pool_index, amounts, shares));
+    Ok(amounts)
+  }
+}
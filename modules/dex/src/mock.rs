@@ -0,0 +1,344 @@
+#![cfg(test)]
+use super::*;
+use frame_support::{
+  impl_outer_event, impl_outer_origin, parameter_types,
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_std::{cell::RefCell, collections::btree_map::BTreeMap};
+use sp_runtime::{testing::Header, traits::IdentityLookup};
+
+pub use primitives::{
+  AccountId, Amount, Balance,
+  CurrencyId,
+  Rate, Share,
+  currency::*,
+};
+use clover_traits::incentive_ops::IncentivePoolAccountInfo;
+
+use orml_currencies::{BasicCurrencyAdapter};
+use orml_traits::parameter_type_with_key;
+
+pub type BlockNumber = u64;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TestRuntime;
+
+mod dex {
+  pub use super::super::*;
+}
+
+impl_outer_event! {
+  pub enum TestEvent for TestRuntime {
+    frame_system<T>,
+    dex<T>,
+    orml_tokens<T>,
+    orml_currencies<T>,
+    pallet_balances<T>,
+  }
+}
+
+impl_outer_origin! {
+  pub enum Origin for TestRuntime {}
+}
+
+parameter_types! {
+  pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for TestRuntime {
+  type Origin = Origin;
+  type Index = u64;
+  type BlockNumber = BlockNumber;
+  type Call = ();
+  type Hash = H256;
+  type Hashing = ::sp_runtime::traits::BlakeTwo256;
+  type AccountId = AccountId;
+  type Lookup = IdentityLookup<Self::AccountId>;
+  type Header = Header;
+  type Event = TestEvent;
+  type BlockHashCount = BlockHashCount;
+  type BlockWeights = ();
+  type BlockLength = ();
+  type Version = ();
+  type PalletInfo = ();
+  type AccountData = pallet_balances::AccountData<Balance>;
+  type OnNewAccount = ();
+  type OnKilledAccount = ();
+  type DbWeight = ();
+  type BaseCallFilter = ();
+  type SystemWeightInfo = ();
+  type SS58Prefix = ();
+}
+
+pub type System = frame_system::Module<TestRuntime>;
+
+parameter_types! {
+  pub const ExistentialDeposit: u128 = 500;
+  pub const MaxLocks: u32 = 50;
+}
+
+impl pallet_balances::Config for TestRuntime {
+  /// The type for recording an account's balance.
+  type Balance = Balance;
+  /// The ubiquitous event type.
+  type Event = TestEvent;
+  type DustRemoval = ();
+  type ExistentialDeposit = ExistentialDeposit;
+  type AccountStore = System;
+  type MaxLocks = MaxLocks;
+  type WeightInfo = ();
+}
+
+pub type Balances = pallet_balances::Module<TestRuntime>;
+
+parameter_type_with_key! {
+  pub ExistentialDeposits: |_currency_id: CurrencyId| -> Balance {
+    0
+  };
+}
+
+impl orml_tokens::Config for TestRuntime {
+  type Event = TestEvent;
+  type Balance = Balance;
+  type Amount = Amount;
+  type CurrencyId = CurrencyId;
+  type WeightInfo = ();
+  type ExistentialDeposits = ExistentialDeposits;
+  type OnDust = ();
+}
+
+pub type Tokens = orml_tokens::Module<TestRuntime>;
+
+parameter_types! {
+  pub const GetNativeCurrencyId: CurrencyId = CurrencyId::CLV;
+}
+
+impl orml_currencies::Config for TestRuntime {
+  type Event = TestEvent;
+  type MultiCurrency = Tokens;
+  type NativeCurrency = BasicCurrencyAdapter<TestRuntime, Balances, Amount, BlockNumber>;
+  type GetNativeCurrencyId = GetNativeCurrencyId;
+  type WeightInfo = ();
+}
+
+pub type Currencies = orml_currencies::Module<TestRuntime>;
+
+thread_local! {
+  static INCENTIVE_SHARES: RefCell<BTreeMap<(AccountId, CurrencyId, CurrencyId), Share>> = RefCell::new(BTreeMap::new());
+  static STABLE_INCENTIVE_SHARES: RefCell<BTreeMap<(AccountId, StablePoolIndex), Share>> = RefCell::new(BTreeMap::new());
+  static INCENTIVE_POOLS_CLOSED: RefCell<bool> = RefCell::new(false);
+}
+
+/// closes all the incentive pools, the shares added later are not reported
+/// and only the reported shares are removed like the closed reward pools
+pub fn close_incentive_pools() {
+  INCENTIVE_POOLS_CLOSED.with(|v| *v.borrow_mut() = true);
+}
+
+fn incentive_pools_closed() -> bool {
+  INCENTIVE_POOLS_CLOSED.with(|v| *v.borrow())
+}
+
+fn add_incentive_share(shares: &mut Share, amount: Share) -> Result<Share, DispatchError> {
+  if !incentive_pools_closed() {
+    *shares += amount;
+  }
+  Ok(*shares)
+}
+
+fn remove_incentive_share(shares: &mut Share, amount: Share) -> Result<Share, DispatchError> {
+  let amount = if incentive_pools_closed() { amount.min(*shares) } else { amount };
+  *shares = shares.checked_sub(amount).ok_or(DispatchError::Other("insufficient incentive shares"))?;
+  Ok(*shares)
+}
+
+/// the shares reported to the incentives
+pub fn incentive_shares(who: &AccountId, left: CurrencyId, right: CurrencyId) -> Share {
+  INCENTIVE_SHARES.with(|v| v.borrow().get(&(who.clone(), left, right)).cloned().unwrap_or_default())
+}
+
+/// the shares of the stable pool reported to the incentives
+pub fn stable_incentive_shares(who: &AccountId, pool_index: StablePoolIndex) -> Share {
+  STABLE_INCENTIVE_SHARES.with(|v| v.borrow().get(&(who.clone(), pool_index)).cloned().unwrap_or_default())
+}
+
+/// records the shares of the liquidity providers, the rewards are out of the scope of the dex
+pub struct MockIncentiveOps;
+impl IncentiveOps<AccountId, CurrencyId, Share, Balance, BlockNumber> for MockIncentiveOps {
+  type PoolId = ();
+
+  fn add_share(who: &AccountId, left: &CurrencyId, right: &CurrencyId, amount: &Share) -> Result<Share, DispatchError> {
+    INCENTIVE_SHARES.with(|v| {
+      let mut shares = v.borrow_mut();
+      add_incentive_share(shares.entry((who.clone(), *left, *right)).or_default(), *amount)
+    })
+  }
+
+  fn remove_share(who: &AccountId, left: &CurrencyId, right: &CurrencyId, amount: &Share) -> Result<Share, DispatchError> {
+    INCENTIVE_SHARES.with(|v| {
+      let mut shares = v.borrow_mut();
+      remove_incentive_share(shares.entry((who.clone(), *left, *right)).or_default(), *amount)
+    })
+  }
+
+  fn add_stable_share(who: &AccountId, pool_index: StablePoolIndex, amount: &Share) -> Result<Share, DispatchError> {
+    STABLE_INCENTIVE_SHARES.with(|v| {
+      let mut shares = v.borrow_mut();
+      add_incentive_share(shares.entry((who.clone(), pool_index)).or_default(), *amount)
+    })
+  }
+
+  fn remove_stable_share(who: &AccountId, pool_index: StablePoolIndex, amount: &Share) -> Result<Share, DispatchError> {
+    STABLE_INCENTIVE_SHARES.with(|v| {
+      let mut shares = v.borrow_mut();
+      remove_incentive_share(shares.entry((who.clone(), pool_index)).or_default(), *amount)
+    })
+  }
+
+  fn stake(_: &AccountId, _: &CurrencyId, _: &Balance) -> Result<Share, DispatchError> {
+    Ok(Zero::zero())
+  }
+
+  fn unstake(_: &AccountId, _: &CurrencyId, _: &Balance) -> Result<Share, DispatchError> {
+    Ok(Zero::zero())
+  }
+
+  fn withdraw_unbonded(_: &AccountId, _: &CurrencyId, _: &CurrencyId) -> Result<Share, DispatchError> {
+    Ok(Zero::zero())
+  }
+
+  fn get_account_shares(who: &AccountId, left: &CurrencyId, right: &CurrencyId) -> Share {
+    incentive_shares(who, *left, *right)
+  }
+
+  fn get_accumlated_rewards(_: &AccountId, _: &CurrencyId, _: &CurrencyId) -> Vec<(CurrencyId, Balance)> {
+    vec![]
+  }
+
+  fn get_account_info(who: &AccountId, left: &CurrencyId, right: &CurrencyId) -> IncentivePoolAccountInfo<CurrencyId, Share, Balance> {
+    IncentivePoolAccountInfo {
+      shares: incentive_shares(who, *left, *right),
+      accumlated_rewards: vec![],
+    }
+  }
+
+  fn claim_rewards(_: &AccountId, _: &CurrencyId, _: &CurrencyId) -> Result<Vec<(CurrencyId, Balance)>, DispatchError> {
+    Ok(vec![])
+  }
+
+  fn get_all_incentive_pools() -> Vec<((), Share, Vec<(CurrencyId, Balance)>)> {
+    vec![]
+  }
+
+  fn get_pool_reward_rates() -> Vec<((), Vec<(CurrencyId, Balance)>)> {
+    vec![]
+  }
+
+  fn get_pending_unbondings(_: &AccountId) -> Vec<((), Share, Vec<(CurrencyId, Balance)>, BlockNumber)> {
+    vec![]
+  }
+}
+
+parameter_types! {
+  pub const DexModuleId: ModuleId = ModuleId(*b"clv/dexm");
+  pub const MaxSwapHops: u32 = 3;
+  pub const MinAmplificationRampBlocks: BlockNumber = 10;
+  pub const MaxStablePoolsPerPair: u32 = 2;
+}
+
+impl Trait for TestRuntime {
+  type Event = TestEvent;
+  type Currency = Currencies;
+  type ModuleId = DexModuleId;
+  type IncentiveOps = MockIncentiveOps;
+  type MaxSwapHops = MaxSwapHops;
+  type MinAmplificationRampBlocks = MinAmplificationRampBlocks;
+  type MaxStablePoolsPerPair = MaxStablePoolsPerPair;
+  type UpdateOrigin = EnsureRoot<AccountId>;
+  type WeightInfo = ();
+}
+
+pub type DexModule = Module<TestRuntime>;
+
+pub const ALICE: [u8; 32] = [0u8; 32];
+pub const BOB: [u8; 32] = [1u8; 32];
+pub const CLV: CurrencyId = CurrencyId::CLV;
+pub const CUSDT: CurrencyId = CurrencyId::CUSDT;
+pub const DOT: CurrencyId = CurrencyId::DOT;
+pub const CETH: CurrencyId = CurrencyId::CETH;
+
+pub struct ExtBuilder {
+  endowed_accounts: Vec<(AccountId, CurrencyId, Balance)>,
+  trading_pairs: Vec<(CurrencyId, CurrencyId)>,
+  exchange_fee: Rate,
+}
+
+impl Default for ExtBuilder {
+  fn default() -> Self {
+    let alice = AccountId::from(ALICE);
+    let bob = AccountId::from(BOB);
+
+    Self {
+      endowed_accounts: vec![
+        (alice.clone(), CLV, 1_000_000 * DOLLARS),
+        (bob.clone(), CLV, 1_000_000 * DOLLARS),
+        (alice.clone(), CUSDT, 1_000_000 * DOLLARS),
+        (bob.clone(), CUSDT, 1_000_000 * DOLLARS),
+        (alice.clone(), DOT, 1_000_000 * DOLLARS),
+        (bob.clone(), DOT, 1_000_000 * DOLLARS),
+        (alice.clone(), CETH, 1_000_000 * DOLLARS),
+        (bob.clone(), CETH, 1_000_000 * DOLLARS),
+      ],
+      trading_pairs: vec![(CUSDT, DOT), (CLV, CUSDT)],
+      exchange_fee: Rate::saturating_from_rational(3, 1000),
+    }
+  }
+}
+
+impl ExtBuilder {
+  pub fn exchange_fee(mut self, exchange_fee: Rate) -> Self {
+    self.exchange_fee = exchange_fee;
+    self
+  }
+
+  pub fn build(self) -> sp_io::TestExternalities {
+    INCENTIVE_SHARES.with(|v| v.borrow_mut().clear());
+    STABLE_INCENTIVE_SHARES.with(|v| v.borrow_mut().clear());
+
+    let mut t = frame_system::GenesisConfig::default()
+      .build_storage::<TestRuntime>()
+      .unwrap();
+
+    pallet_balances::GenesisConfig::<TestRuntime> {
+      balances: self
+        .endowed_accounts
+        .clone()
+        .into_iter()
+        .filter(|(_, currency_id, _)| *currency_id == CLV)
+        .map(|(account_id, _, initial_balance)| (account_id, initial_balance))
+        .collect::<Vec<_>>(),
+    }
+    .assimilate_storage(&mut t)
+      .unwrap();
+
+    orml_tokens::GenesisConfig::<TestRuntime> {
+      endowed_accounts: self
+        .endowed_accounts
+        .into_iter()
+        .filter(|(_, currency_id, _)| *currency_id != CLV)
+        .collect::<Vec<_>>(),
+    }
+    .assimilate_storage(&mut t).unwrap();
+
+    GenesisConfig {
+      exchange_fee: self.exchange_fee,
+      trading_pairs: self.trading_pairs,
+    }
+    .assimilate_storage::<TestRuntime>(&mut t).unwrap();
+
+    let mut ext: sp_io::TestExternalities = t.into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+  }
+}
@@ -0,0 +1,154 @@
+//! StableSwap invariant of the stable pools
+//!
+//! A * n^n * sum(x_i) + D = A * D * n^n + D^(n+1) / (n^n * prod(x_i))
+//!
+//! The invariant is solved by the newton's method in the same way as the curve pools, with `Ann = A * n`.
+//! The pool behaves like a constant sum pool around the balanced point and like a constant product pool
+//! when it's imbalanced, the amplification A decides how flat the curve is.
+//! All the rounding is in favor of the pool.
+
+use sp_core::U256;
+use sp_runtime::{FixedPointNumber, traits::{One, Saturating, Zero}};
+use sp_std::{convert::TryFrom, prelude::*};
+
+use primitives::{Balance, Rate, Share};
+
+const MAX_ITERATIONS: u32 = 255;
+
+fn converged(a: U256, b: U256) -> bool {
+  if a > b { a - b <= U256::one() } else { b - a <= U256::one() }
+}
+
+/// the invariant D of the balances, None if any balance is zero or it doesn't converge
+pub fn get_d(balances: &[Balance], amplification: u128) -> Option<Balance> {
+  let n = U256::from(balances.len());
+  let sum = balances.iter().try_fold(U256::zero(), |sum, balance| sum.checked_add(U256::from(*balance)))?;
+  if sum.is_zero() {
+    return Some(Zero::zero());
+  }
+  let ann = U256::from(amplification).checked_mul(n)?;
+
+  let mut d = sum;
+  for _ in 0..MAX_ITERATIONS {
+    // d_p = d^(n+1) / (n^n * prod(x_i))
+    let mut d_p = d;
+    for balance in balances {
+      d_p = d_p.checked_mul(d)?.checked_div(U256::from(*balance).checked_mul(n)?)?;
+    }
+    let d_prev = d;
+    // d = (ann * sum + d_p * n) * d / ((ann - 1) * d + (n + 1) * d_p)
+    let numerator = ann.checked_mul(sum)?.checked_add(d_p.checked_mul(n)?)?.checked_mul(d)?;
+    let denominator = ann.checked_sub(U256::one())?.checked_mul(d)?
+      .checked_add(n.checked_add(U256::one())?.checked_mul(d_p)?)?;
+    d = numerator.checked_div(denominator)?;
+    if converged(d, d_prev) {
+      return Balance::try_from(d).ok();
+    }
+  }
+  None
+}
+
+/// the balance of the currency `j` which keeps the invariant D after the balance of the currency `i` is `x`
+pub fn get_y(i: usize, j: usize, x: Balance, balances: &[Balance], amplification: u128, d: Balance) -> Option<Balance> {
+  if i == j || i >= balances.len() || j >= balances.len() {
+    return None;
+  }
+  let n = U256::from(balances.len());
+  let ann = U256::from(amplification).checked_mul(n)?;
+  let d = U256::from(d);
+
+  // c = d^(n+1) / (n^n * prod(x_k) * ann * n), b = sum(x_k) + d / ann, for all k != j
+  let mut c = d;
+  let mut sum = U256::zero();
+  for (k, balance) in balances.iter().enumerate() {
+    let x_k = if k == i {
+      U256::from(x)
+    } else if k != j {
+      U256::from(*balance)
+    } else {
+      continue;
+    };
+    sum = sum.checked_add(x_k)?;
+    c = c.checked_mul(d)?.checked_div(x_k.checked_mul(n)?)?;
+  }
+  c = c.checked_mul(d)?.checked_div(ann.checked_mul(n)?)?;
+  let b = sum.checked_add(d.checked_div(ann)?)?;
+
+  let mut y = d;
+  for _ in 0..MAX_ITERATIONS {
+    let y_prev = y;
+    // y = (y^2 + c) / (2 * y + b - d)
+    y = y.checked_mul(y)?.checked_add(c)?
+      .checked_div(y.checked_mul(U256::from(2))?.checked_add(b)?.checked_sub(d)?)?;
+    if converged(y, y_prev) {
+      return Balance::try_from(y).ok();
+    }
+  }
+  None
+}
+
+/// the target amount of the currency `j` after the fee for the exact supply amount of the currency `i`
+pub fn get_target_amount(balances: &[Balance], amplification: u128, fee: Rate,
+                         i: usize, j: usize, supply_amount: Balance) -> Option<Balance> {
+  let d = get_d(balances, amplification)?;
+  let x = balances.get(i)?.checked_add(supply_amount)?;
+  let y = get_y(i, j, x, balances, amplification, d)?;
+  // 1 is taken for the rounding of y
+  let target_amount = balances.get(j)?.checked_sub(y)?.checked_sub(One::one())?;
+  Some(Rate::one().saturating_sub(fee).saturating_mul_int(target_amount))
+}
+
+/// the supply amount of the currency `i` needed for the exact target amount of the currency `j` after the fee
+pub fn get_supply_amount(balances: &[Balance], amplification: u128, fee: Rate,
+                         i: usize, j: usize, target_amount: Balance) -> Option<Balance> {
+  let d = get_d(balances, amplification)?;
+  // the target amount before the fee, rounded up
+  let accuracy = U256::from(Rate::accuracy());
+  let rate_after_fee = accuracy.checked_sub(U256::from(fee.into_inner()))?;
+  let target_before_fee = U256::from(target_amount).checked_mul(accuracy)?
+    .checked_add(rate_after_fee)?.checked_sub(U256::one())?
+    .checked_div(rate_after_fee)?;
+  let target_before_fee = Balance::try_from(target_before_fee).ok()?;
+  let y = balances.get(j)?.checked_sub(target_before_fee)?.checked_sub(One::one())?;
+  let x = get_y(j, i, y, balances, amplification, d)?;
+  x.checked_sub(*balances.get(i)?)?.checked_add(One::one())
+}
+
+/// the shares of adding the amounts to the pool and the balances after it,
+/// the imbalanced part of the amounts is charged `fee * n / (4 * (n - 1))` like a swap
+pub fn calc_add_liquidity(balances: &[Balance], amplification: u128, fee: Rate,
+                          total_shares: Share, amounts: &[Balance]) -> Option<(Share, Vec<Balance>)> {
+  let n = balances.len();
+  if n < 2 || amounts.len() != n {
+    return None;
+  }
+  let new_balances = balances.iter().zip(amounts.iter())
+    .map(|(balance, amount)| balance.checked_add(*amount))
+    .collect::<Option<Vec<Balance>>>()?;
+  let d1 = get_d(&new_balances, amplification)?;
+  if total_shares.is_zero() {
+    // the first shares are the invariant
+    return Some((d1, new_balances));
+  }
+
+  let d0 = get_d(balances, amplification)?;
+  if d1 <= d0 {
+    return None;
+  }
+  let imbalance_fee = fee.saturating_mul(Rate::saturating_from_rational(n as u128, 4 * (n as u128 - 1)));
+  let mut adjusted_balances = Vec::with_capacity(n);
+  for (balance, new_balance) in balances.iter().zip(new_balances.iter()) {
+    let ideal_balance = U256::from(d1).checked_mul(U256::from(*balance))?.checked_div(U256::from(d0))?;
+    let ideal_balance = Balance::try_from(ideal_balance).ok()?;
+    let difference = if *new_balance > ideal_balance {
+      new_balance - ideal_balance
+    } else {
+      ideal_balance - new_balance
+    };
+    adjusted_balances.push(new_balance.checked_sub(imbalance_fee.saturating_mul_int(difference))?);
+  }
+  let d2 = get_d(&adjusted_balances, amplification)?;
+
+  let shares = U256::from(total_shares).checked_mul(U256::from(d2.checked_sub(d0)?))?.checked_div(U256::from(d0))?;
+  Some((Share::try_from(shares).ok()?, new_balances))
+}
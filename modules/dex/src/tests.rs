@@ -0,0 +1,603 @@
+#![cfg(test)]
+
+use super::*;
+use frame_support::{assert_noop, assert_ok, storage::StorageDoubleMap};
+use mock::{
+  ALICE, BOB, CETH, CLV, CUSDT, DOT, Currencies, DexModule, Origin, System, TestEvent, TestRuntime,
+  close_incentive_pools, incentive_shares, stable_incentive_shares,
+  ExtBuilder,
+};
+
+pub use primitives::{AccountId, currency::*};
+
+fn pair() -> TradingPair {
+  TradingPair::from_currencies(CUSDT, DOT).unwrap()
+}
+
+fn add_initial_liquidity(who: &AccountId) {
+  assert_ok!(DexModule::add_liquidity(Origin::signed(who.clone()), CUSDT, DOT, 100 * DOLLARS, 400 * DOLLARS));
+}
+
+fn stable_fee() -> Rate {
+  Rate::saturating_from_rational(4, 10000)
+}
+
+/// creates the stable pool 0 of CUSDT, DOT and CETH with 100 DOLLARS of each
+fn create_stable_pool(who: &AccountId) {
+  assert_ok!(DexModule::create_stable_pool(Origin::root(), vec![CUSDT, DOT, CETH], 100, stable_fee()));
+  assert_ok!(DexModule::add_stable_liquidity(Origin::signed(who.clone()), 0,
+                                             vec![100 * DOLLARS, 100 * DOLLARS, 100 * DOLLARS], 0));
+}
+
+#[test]
+fn test_trading_pair() {
+  assert_eq!(TradingPair::from_currencies(DOT, CUSDT), TradingPair::from_currencies(CUSDT, DOT));
+  assert_eq!(TradingPair::from_currencies(DOT, CUSDT).map(|p| (p.first(), p.second())), Some((CUSDT, DOT)));
+  assert_eq!(TradingPair::from_currencies(DOT, DOT), None);
+}
+
+#[test]
+fn test_list_trading_pair() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    assert!(!DexModule::trading_pairs(TradingPair::from_currencies(CETH, DOT).unwrap()));
+
+    assert_noop!(DexModule::add_liquidity(Origin::signed(alice.clone()), CETH, DOT, DOLLARS, DOLLARS),
+                 Error::<TestRuntime>::TradingPairNotListed);
+    assert_noop!(DexModule::list_trading_pair(Origin::signed(alice.clone()), CETH, DOT),
+                 sp_runtime::DispatchError::BadOrigin);
+    assert_noop!(DexModule::list_trading_pair(Origin::root(), DOT, DOT),
+                 Error::<TestRuntime>::InvalidTradingPair);
+    assert_noop!(DexModule::list_trading_pair(Origin::root(), DOT, CUSDT),
+                 Error::<TestRuntime>::TradingPairAlreadyListed);
+
+    assert_ok!(DexModule::list_trading_pair(Origin::root(), DOT, CETH));
+    assert!(DexModule::trading_pairs(TradingPair::from_currencies(CETH, DOT).unwrap()));
+    let event = TestEvent::dex(RawEvent::TradingPairListed(DOT, CETH));
+    assert!(System::events().iter().any(|record| record.event == event));
+
+    assert_ok!(DexModule::add_liquidity(Origin::signed(alice.clone()), CETH, DOT, DOLLARS, DOLLARS));
+  });
+}
+
+#[test]
+fn test_add_remove_liquidity() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let bob = AccountId::from(BOB);
+    let module_account = DexModule::account_id();
+
+    assert_noop!(DexModule::add_liquidity(Origin::signed(alice.clone()), CUSDT, DOT, 0, DOLLARS),
+                 Error::<TestRuntime>::InvalidAmount);
+
+    // first liquidity gets the geometric mean of the amounts
+    add_initial_liquidity(&alice);
+    assert_eq!(DexModule::liquidity_pool(pair()), (100 * DOLLARS, 400 * DOLLARS));
+    assert_eq!(DexModule::total_shares(pair()), 200 * DOLLARS);
+    assert_eq!(DexModule::shares(pair(), &alice), 200 * DOLLARS);
+    assert_eq!(incentive_shares(&alice, CUSDT, DOT), 200 * DOLLARS);
+    assert_eq!(Currencies::free_balance(CUSDT, &module_account), 100 * DOLLARS);
+    assert_eq!(Currencies::free_balance(DOT, &module_account), 400 * DOLLARS);
+    assert_eq!(Currencies::free_balance(CUSDT, &alice), 999_900 * DOLLARS);
+    let event = TestEvent::dex(RawEvent::AddLiquidity(alice.clone(), CUSDT, 100 * DOLLARS, DOT, 400 * DOLLARS, 200 * DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == event));
+
+    // the amounts are added in the ratio of the pool, the currencies could be in any order
+    assert_ok!(DexModule::add_liquidity(Origin::signed(bob.clone()), DOT, CUSDT, 40 * DOLLARS, 20 * DOLLARS));
+    assert_eq!(DexModule::liquidity_pool(pair()), (110 * DOLLARS, 440 * DOLLARS));
+    assert_eq!(DexModule::total_shares(pair()), 220 * DOLLARS);
+    assert_eq!(DexModule::shares(pair(), &bob), 20 * DOLLARS);
+    assert_eq!(incentive_shares(&bob, CUSDT, DOT), 20 * DOLLARS);
+    assert_eq!(Currencies::free_balance(CUSDT, &bob), 999_990 * DOLLARS);
+    assert_eq!(Currencies::free_balance(DOT, &bob), 999_960 * DOLLARS);
+
+    // too small to get any shares
+    assert_noop!(DexModule::add_liquidity(Origin::signed(bob.clone()), CUSDT, DOT, 1, 1),
+                 Error::<TestRuntime>::InvalidLiquidityIncrement);
+
+    assert_noop!(DexModule::remove_liquidity(Origin::signed(bob.clone()), CUSDT, DOT, 21 * DOLLARS),
+                 Error::<TestRuntime>::InsufficientShares);
+    assert_noop!(DexModule::remove_liquidity(Origin::signed(bob.clone()), CUSDT, DOT, 0),
+                 Error::<TestRuntime>::InvalidAmount);
+
+    assert_ok!(DexModule::remove_liquidity(Origin::signed(bob.clone()), DOT, CUSDT, 10 * DOLLARS));
+    assert_eq!(DexModule::liquidity_pool(pair()), (105 * DOLLARS, 420 * DOLLARS));
+    assert_eq!(DexModule::total_shares(pair()), 210 * DOLLARS);
+    assert_eq!(DexModule::shares(pair(), &bob), 10 * DOLLARS);
+    assert_eq!(incentive_shares(&bob, CUSDT, DOT), 10 * DOLLARS);
+    assert_eq!(Currencies::free_balance(CUSDT, &bob), 999_995 * DOLLARS);
+    assert_eq!(Currencies::free_balance(DOT, &bob), 999_980 * DOLLARS);
+    let event = TestEvent::dex(RawEvent::RemoveLiquidity(bob.clone(), CUSDT, 5 * DOLLARS, DOT, 20 * DOLLARS, 10 * DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == event));
+
+    assert_ok!(DexModule::remove_liquidity(Origin::signed(bob.clone()), CUSDT, DOT, 10 * DOLLARS));
+    assert!(!<Shares<TestRuntime>>::contains_key(pair(), &bob));
+    assert_eq!(incentive_shares(&bob, CUSDT, DOT), 0);
+    assert_eq!(Currencies::free_balance(CUSDT, &bob), 1_000_000 * DOLLARS);
+    assert_eq!(Currencies::free_balance(DOT, &bob), 1_000_000 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_closed_incentive_pools() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let bob = AccountId::from(BOB);
+    add_initial_liquidity(&alice);
+    create_stable_pool(&alice);
+    close_incentive_pools();
+
+    // the liquidity is still added and removed when the incentive pools are closed,
+    // only the shares reported before are removed from the incentives
+    assert_ok!(DexModule::add_liquidity(Origin::signed(alice.clone()), CUSDT, DOT, 10 * DOLLARS, 40 * DOLLARS));
+    assert_eq!(DexModule::shares(pair(), &alice), 220 * DOLLARS);
+    assert_eq!(incentive_shares(&alice, CUSDT, DOT), 200 * DOLLARS);
+    assert_ok!(DexModule::remove_liquidity(Origin::signed(alice.clone()), CUSDT, DOT, 210 * DOLLARS));
+    assert_eq!(incentive_shares(&alice, CUSDT, DOT), 0);
+
+    assert_ok!(DexModule::add_stable_liquidity(Origin::signed(bob.clone()), 0,
+                                               vec![10 * DOLLARS, 10 * DOLLARS, 10 * DOLLARS], 0));
+    let shares = DexModule::stable_shares(0, &bob);
+    assert!(!shares.is_zero());
+    assert_eq!(stable_incentive_shares(&bob, 0), 0);
+    assert_ok!(DexModule::remove_stable_liquidity(Origin::signed(bob.clone()), 0, shares, vec![0, 0, 0]));
+    assert!(!<StableShares<TestRuntime>>::contains_key(0, &bob));
+  });
+}
+
+#[test]
+fn test_swap_with_exact_supply() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let bob = AccountId::from(BOB);
+
+    assert_noop!(DexModule::swap_with_exact_supply(Origin::signed(bob.clone()), CUSDT, DOT, 10 * DOLLARS, 0),
+                 Error::<TestRuntime>::InsufficientLiquidity);
+
+    add_initial_liquidity(&alice);
+    let target_amount = 36_264_435_755_205_965_263;
+    assert_eq!(DexModule::get_target_amount(100 * DOLLARS, 400 * DOLLARS, 10 * DOLLARS), target_amount);
+
+    assert_noop!(DexModule::swap_with_exact_supply(Origin::signed(bob.clone()), CUSDT, DOT, 10 * DOLLARS, target_amount + 1),
+                 Error::<TestRuntime>::SlippageExceeded);
+    assert_noop!(DexModule::swap_with_exact_supply(Origin::signed(bob.clone()), CUSDT, CUSDT, 10 * DOLLARS, 0),
+                 Error::<TestRuntime>::InvalidTradingPair);
+
+    assert_ok!(DexModule::swap_with_exact_supply(Origin::signed(bob.clone()), CUSDT, DOT, 10 * DOLLARS, target_amount));
+    assert_eq!(DexModule::liquidity_pool(pair()), (110 * DOLLARS, 400 * DOLLARS - target_amount));
+    assert_eq!(Currencies::free_balance(CUSDT, &bob), 999_990 * DOLLARS);
+    assert_eq!(Currencies::free_balance(DOT, &bob), 1_000_000 * DOLLARS + target_amount);
+    let event = TestEvent::dex(RawEvent::Swap(bob.clone(), CUSDT, 10 * DOLLARS, DOT, target_amount));
+    assert!(System::events().iter().any(|record| record.event == event));
+
+    // the shares are not changed by the swaps
+    assert_eq!(DexModule::total_shares(pair()), 200 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_swap_with_exact_target() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let bob = AccountId::from(BOB);
+
+    add_initial_liquidity(&alice);
+    let supply_amount = 11_144_544_745_347_152_569;
+    assert_eq!(DexModule::get_supply_amount(100 * DOLLARS, 400 * DOLLARS, 40 * DOLLARS), supply_amount);
+
+    assert_noop!(DexModule::swap_with_exact_target(Origin::signed(bob.clone()), CUSDT, DOT, 40 * DOLLARS, supply_amount - 1),
+                 Error::<TestRuntime>::SlippageExceeded);
+    assert_noop!(DexModule::swap_with_exact_target(Origin::signed(bob.clone()), CUSDT, DOT, 400 * DOLLARS, 1_000_000 * DOLLARS),
+                 Error::<TestRuntime>::InsufficientLiquidity);
+
+    assert_ok!(DexModule::swap_with_exact_target(Origin::signed(bob.clone()), CUSDT, DOT, 40 * DOLLARS, supply_amount));
+    assert_eq!(DexModule::liquidity_pool(pair()), (100 * DOLLARS + supply_amount, 360 * DOLLARS));
+    assert_eq!(Currencies::free_balance(CUSDT, &bob), 1_000_000 * DOLLARS - supply_amount);
+    assert_eq!(Currencies::free_balance(DOT, &bob), 1_000_040 * DOLLARS);
+
+    // the supply amount is rounded up, so swapping it back gets at least the target amount
+    assert!(DexModule::get_target_amount(100 * DOLLARS, 400 * DOLLARS, supply_amount) >= 40 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_set_exchange_fee() {
+  ExtBuilder::default().exchange_fee(Rate::zero()).build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    add_initial_liquidity(&alice);
+    // no fee: 10 * 400 / (100 + 10)
+    assert_eq!(DexModule::get_target_amount(100 * DOLLARS, 400 * DOLLARS, 10 * DOLLARS), 36_363_636_363_636_363_636);
+
+    assert_noop!(DexModule::set_exchange_fee(Origin::signed(alice.clone()), Rate::saturating_from_rational(1, 100)),
+                 sp_runtime::DispatchError::BadOrigin);
+    assert_noop!(DexModule::set_exchange_fee(Origin::root(), Rate::one()),
+                 Error::<TestRuntime>::InvalidExchangeFee);
+
+    assert_ok!(DexModule::set_exchange_fee(Origin::root(), Rate::saturating_from_rational(3, 1000)));
+    assert_eq!(DexModule::exchange_fee(), Rate::saturating_from_rational(3, 1000));
+    assert_eq!(DexModule::get_target_amount(100 * DOLLARS, 400 * DOLLARS, 10 * DOLLARS), 36_264_435_755_205_965_263);
+    let event = TestEvent::dex(RawEvent::ExchangeFeeUpdated(Rate::saturating_from_rational(3, 1000)));
+    assert!(System::events().iter().any(|record| record.event == event));
+
+    // native currency pair
+    assert_ok!(DexModule::add_liquidity(Origin::signed(alice.clone()), CLV, CUSDT, 1000 * DOLLARS, 100 * DOLLARS));
+    assert_eq!(DexModule::liquidity_pool(TradingPair::from_currencies(CLV, CUSDT).unwrap()),
+               (1000 * DOLLARS, 100 * DOLLARS));
+  });
+}
+
+#[test]
+fn test_exchange_amount_overflow() {
+  ExtBuilder::default().build().execute_with(|| {
+    let max = Balance::max_value();
+    assert_eq!(DexModule::get_target_amount(max, max, max), 0);
+    assert_eq!(DexModule::get_supply_amount(max, max, max - 1), 0);
+  });
+}
+
+fn add_path_liquidity(who: &AccountId) {
+  add_initial_liquidity(who);
+  assert_ok!(DexModule::add_liquidity(Origin::signed(who.clone()), CLV, CUSDT, 1000 * DOLLARS, 100 * DOLLARS));
+}
+
+#[test]
+fn test_find_paths() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    // no liquidity
+    assert!(DexModule::find_paths(CLV, DOT).is_empty());
+    assert_eq!(DexModule::target_amount_available(CLV, DOT, 10 * DOLLARS), (0, vec![]));
+
+    add_path_liquidity(&alice);
+    let mut pairs = DexModule::get_trading_pairs();
+    pairs.sort();
+    assert_eq!(pairs, vec![(CLV, CUSDT), (CUSDT, DOT)]);
+    assert_eq!(DexModule::find_paths(CLV, DOT), vec![vec![CLV, CUSDT, DOT]]);
+    assert_eq!(DexModule::find_paths(DOT, CLV), vec![vec![DOT, CUSDT, CLV]]);
+    assert!(DexModule::find_paths(CLV, CLV).is_empty());
+    assert!(DexModule::find_paths(CLV, CETH).is_empty());
+
+    assert_eq!(DexModule::target_amount_available(CLV, DOT, 10 * DOLLARS),
+               (3_898_418_143_897_370_401, vec![CLV, CUSDT, DOT]));
+    assert_eq!(DexModule::supply_amount_needed(CLV, DOT, 40 * DOLLARS),
+               (125_800_705_767_119_494_697, vec![CLV, CUSDT, DOT]));
+    assert_eq!(DexModule::supply_amount_needed(CLV, DOT, 400 * DOLLARS), (0, vec![]));
+
+    // the direct pair gets more than the path
+    assert_ok!(DexModule::list_trading_pair(Origin::root(), CLV, DOT));
+    assert_ok!(DexModule::add_liquidity(Origin::signed(alice.clone()), CLV, DOT, 1000 * DOLLARS, 3000 * DOLLARS));
+    let mut paths = DexModule::find_paths(CLV, DOT);
+    paths.sort();
+    assert_eq!(paths, vec![vec![CLV, CUSDT, DOT], vec![CLV, DOT]]);
+    assert_eq!(DexModule::target_amount_available(CLV, DOT, 10 * DOLLARS),
+               (29_614_741_031_911_838_965, vec![CLV, DOT]));
+    assert_eq!(DexModule::supply_amount_needed(CLV, DOT, 40 * DOLLARS),
+               (13_554_176_041_638_428_800, vec![CLV, DOT]));
+  });
+}
+
+#[test]
+fn test_swap_with_exact_supply_by_path() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let bob = AccountId::from(BOB);
+    add_path_liquidity(&alice);
+
+    assert_noop!(DexModule::swap_with_exact_supply_by_path(Origin::signed(bob.clone()), vec![CLV], 10 * DOLLARS, 0),
+                 Error::<TestRuntime>::InvalidSwapPath);
+    assert_noop!(DexModule::swap_with_exact_supply_by_path(Origin::signed(bob.clone()), vec![CLV, CUSDT, CLV], 10 * DOLLARS, 0),
+                 Error::<TestRuntime>::InvalidSwapPath);
+    // more than MaxSwapHops hops
+    assert_noop!(DexModule::swap_with_exact_supply_by_path(Origin::signed(bob.clone()),
+                                                           vec![CLV, CUSDT, DOT, CUSDT, CLV], 10 * DOLLARS, 0),
+                 Error::<TestRuntime>::InvalidSwapPath);
+    assert_noop!(DexModule::swap_with_exact_supply_by_path(Origin::signed(bob.clone()), vec![CLV, CLV], 10 * DOLLARS, 0),
+                 Error::<TestRuntime>::InvalidTradingPair);
+    assert_noop!(DexModule::swap_with_exact_supply_by_path(Origin::signed(bob.clone()), vec![CLV, DOT], 10 * DOLLARS, 0),
+                 Error::<TestRuntime>::InsufficientLiquidity);
+
+    let (middle_amount, target_amount) = (987_158_034_397_061_298, 3_898_418_143_897_370_401);
+    assert_eq!(DexModule::get_target_amounts(&[CLV, CUSDT, DOT], 10 * DOLLARS),
+               Some(vec![10 * DOLLARS, middle_amount, target_amount]));
+    assert_noop!(DexModule::swap_with_exact_supply_by_path(Origin::signed(bob.clone()), vec![CLV, CUSDT, DOT],
+                                                           10 * DOLLARS, target_amount + 1),
+                 Error::<TestRuntime>::SlippageExceeded);
+
+    assert_ok!(DexModule::swap_with_exact_supply_by_path(Origin::signed(bob.clone()), vec![CLV, CUSDT, DOT],
+                                                         10 * DOLLARS, target_amount));
+    assert_eq!(DexModule::liquidity_pool(TradingPair::from_currencies(CLV, CUSDT).unwrap()),
+               (1010 * DOLLARS, 100 * DOLLARS - middle_amount));
+    assert_eq!(DexModule::liquidity_pool(pair()), (100 * DOLLARS + middle_amount, 400 * DOLLARS - target_amount));
+    assert_eq!(Currencies::free_balance(CLV, &bob), 999_990 * DOLLARS);
+    assert_eq!(Currencies::free_balance(CUSDT, &bob), 1_000_000 * DOLLARS);
+    assert_eq!(Currencies::free_balance(DOT, &bob), 1_000_000 * DOLLARS + target_amount);
+
+    // one event for each hop
+    let first_hop = TestEvent::dex(RawEvent::Swap(bob.clone(), CLV, 10 * DOLLARS, CUSDT, middle_amount));
+    let second_hop = TestEvent::dex(RawEvent::Swap(bob.clone(), CUSDT, middle_amount, DOT, target_amount));
+    assert!(System::events().iter().any(|record| record.event == first_hop));
+    assert!(System::events().iter().any(|record| record.event == second_hop));
+  });
+}
+
+#[test]
+fn test_swap_with_exact_target_by_path() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let bob = AccountId::from(BOB);
+    add_path_liquidity(&alice);
+
+    let (supply_amount, middle_amount) = (125_800_705_767_119_494_697, 11_144_544_745_347_152_569);
+    assert_eq!(DexModule::get_supply_amounts(&[CLV, CUSDT, DOT], 40 * DOLLARS),
+               Some(vec![supply_amount, middle_amount, 40 * DOLLARS]));
+    assert_noop!(DexModule::swap_with_exact_target_by_path(Origin::signed(bob.clone()), vec![CLV, CUSDT, DOT],
+                                                           40 * DOLLARS, supply_amount - 1),
+                 Error::<TestRuntime>::SlippageExceeded);
+    assert_noop!(DexModule::swap_with_exact_target_by_path(Origin::signed(bob.clone()), vec![CLV, CUSDT, DOT],
+                                                           400 * DOLLARS, 1_000_000 * DOLLARS),
+                 Error::<TestRuntime>::InsufficientLiquidity);
+
+    assert_ok!(DexModule::swap_with_exact_target_by_path(Origin::signed(bob.clone()), vec![CLV, CUSDT, DOT],
+                                                         40 * DOLLARS, supply_amount));
+    assert_eq!(DexModule::liquidity_pool(TradingPair::from_currencies(CLV, CUSDT).unwrap()),
+               (1000 * DOLLARS + supply_amount, 100 * DOLLARS - middle_amount));
+    assert_eq!(DexModule::liquidity_pool(pair()), (100 * DOLLARS + middle_amount, 360 * DOLLARS));
+    assert_eq!(Currencies::free_balance(CLV, &bob), 1_000_000 * DOLLARS - supply_amount);
+    assert_eq!(Currencies::free_balance(CUSDT, &bob), 1_000_000 * DOLLARS);
+    assert_eq!(Currencies::free_balance(DOT, &bob), 1_000_040 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_liquidity_queries() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let bob = AccountId::from(BOB);
+    assert_eq!(DexModule::to_add_liquidity(CUSDT, DOT, 100 * DOLLARS, 400 * DOLLARS), (200 * DOLLARS, 200 * DOLLARS));
+    assert_eq!(DexModule::to_add_liquidity(DOT, DOT, 100 * DOLLARS, 400 * DOLLARS), (0, 0));
+
+    add_initial_liquidity(&alice);
+    assert_eq!(DexModule::to_add_liquidity(DOT, CUSDT, 40 * DOLLARS, 20 * DOLLARS), (20 * DOLLARS, 220 * DOLLARS));
+    assert_eq!(DexModule::to_add_liquidity(CUSDT, DOT, 1, 1), (0, 0));
+
+    assert_eq!(DexModule::get_pools_liquidity(Some(&alice)),
+               vec![(CUSDT, DOT, 100 * DOLLARS, 400 * DOLLARS, 200 * DOLLARS, 200 * DOLLARS)]);
+    assert_eq!(DexModule::get_pools_liquidity(Some(&bob)),
+               vec![(CUSDT, DOT, 100 * DOLLARS, 400 * DOLLARS, 200 * DOLLARS, 0)]);
+    assert_eq!(DexModule::get_pools_liquidity(None),
+               vec![(CUSDT, DOT, 100 * DOLLARS, 400 * DOLLARS, 200 * DOLLARS, 0)]);
+  });
+}
+
+#[test]
+fn test_stable_swap_invariant() {
+  // the invariant of the balanced pool is the sum of the balances
+  assert_eq!(stable_swap::get_d(&[100 * DOLLARS, 100 * DOLLARS, 100 * DOLLARS], 100), Some(300 * DOLLARS));
+  assert_eq!(stable_swap::get_d(&[50 * DOLLARS, 150 * DOLLARS], 100), Some(199_671_582_154_425_912_882));
+  assert_eq!(stable_swap::get_d(&[0, 0], 100), Some(0));
+  assert_eq!(stable_swap::get_d(&[0, 100 * DOLLARS], 100), None);
+
+  // the swap is close to 1:1 around the balanced point, and the rounding is in favor of the pool
+  let balances = [100 * DOLLARS, 100 * DOLLARS, 100 * DOLLARS];
+  assert_eq!(stable_swap::get_target_amount(&balances, 100, stable_fee(), 1, 0, 10 * DOLLARS),
+             Some(9_986_015_082_041_261_129));
+  let supply_amount = stable_swap::get_supply_amount(&balances, 100, stable_fee(), 1, 0, 5 * DOLLARS).unwrap();
+  assert!(stable_swap::get_target_amount(&balances, 100, stable_fee(), 1, 0, supply_amount).unwrap() >= 5 * DOLLARS);
+  assert_eq!(stable_swap::get_target_amount(&balances, 100, stable_fee(), 1, 1, 10 * DOLLARS), None);
+}
+
+#[test]
+fn test_create_stable_pool() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    assert_noop!(DexModule::create_stable_pool(Origin::signed(alice.clone()), vec![CUSDT, DOT], 100, stable_fee()),
+                 sp_runtime::DispatchError::BadOrigin);
+    assert_noop!(DexModule::create_stable_pool(Origin::root(), vec![CUSDT], 100, stable_fee()),
+                 Error::<TestRuntime>::InvalidStablePoolCurrencies);
+    assert_noop!(DexModule::create_stable_pool(Origin::root(), vec![CUSDT, DOT, CETH, CLV, CUSDT], 100, stable_fee()),
+                 Error::<TestRuntime>::InvalidStablePoolCurrencies);
+    assert_noop!(DexModule::create_stable_pool(Origin::root(), vec![CUSDT, DOT, CUSDT], 100, stable_fee()),
+                 Error::<TestRuntime>::InvalidStablePoolCurrencies);
+    assert_noop!(DexModule::create_stable_pool(Origin::root(), vec![CUSDT, DOT], 0, stable_fee()),
+                 Error::<TestRuntime>::InvalidAmplification);
+    assert_noop!(DexModule::create_stable_pool(Origin::root(), vec![CUSDT, DOT], MAX_AMPLIFICATION + 1, stable_fee()),
+                 Error::<TestRuntime>::InvalidAmplification);
+    assert_noop!(DexModule::create_stable_pool(Origin::root(), vec![CUSDT, DOT], 100, Rate::one()),
+                 Error::<TestRuntime>::InvalidExchangeFee);
+
+    assert_ok!(DexModule::create_stable_pool(Origin::root(), vec![CUSDT, DOT, CETH, CLV], 100, stable_fee()));
+    assert_ok!(DexModule::create_stable_pool(Origin::root(), vec![DOT, CETH], 200, stable_fee()));
+    assert_eq!(DexModule::next_stable_pool_index(), 2);
+    assert_eq!(DexModule::stable_pools(1), Some(StablePool {
+      currencies: vec![DOT, CETH],
+      balances: vec![0, 0],
+      total_shares: 0,
+      fee: stable_fee(),
+      initial_amplification: 200,
+      future_amplification: 200,
+      initial_block: 1,
+      future_block: 1,
+    }));
+    let event = TestEvent::dex(RawEvent::StablePoolCreated(1, vec![DOT, CETH], 200, stable_fee()));
+    assert!(System::events().iter().any(|record| record.event == event));
+
+    // the empty pools don't have any path
+    assert_eq!(DexModule::find_paths(DOT, CETH), Vec::<Vec<CurrencyId>>::new());
+
+    // the stable pools are indexed by each pair of their currencies, a pair is in at most 2 pools
+    let pair = TradingPair::from_currencies(DOT, CETH).unwrap();
+    assert_eq!(DexModule::pair_stable_pools(pair), vec![0, 1]);
+    assert_eq!(DexModule::pair_stable_pools(TradingPair::from_currencies(CUSDT, CLV).unwrap()), vec![0]);
+    assert_noop!(DexModule::create_stable_pool(Origin::root(), vec![CUSDT, CETH, DOT], 100, stable_fee()),
+                 Error::<TestRuntime>::TooManyStablePools);
+    assert_ok!(DexModule::create_stable_pool(Origin::root(), vec![CUSDT, CLV], 100, stable_fee()));
+    assert_eq!(DexModule::pair_stable_pools(TradingPair::from_currencies(CUSDT, CLV).unwrap()), vec![0, 2]);
+  });
+}
+
+#[test]
+fn test_amplification_ramp() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    assert_noop!(DexModule::ramp_amplification(Origin::root(), 0, 200, 101),
+                 Error::<TestRuntime>::StablePoolNotFound);
+    create_stable_pool(&alice);
+
+    assert_noop!(DexModule::ramp_amplification(Origin::signed(alice.clone()), 0, 200, 101),
+                 sp_runtime::DispatchError::BadOrigin);
+    assert_noop!(DexModule::ramp_amplification(Origin::root(), 0, 0, 101),
+                 Error::<TestRuntime>::InvalidAmplification);
+    // at least `MinAmplificationRampBlocks` blocks
+    assert_noop!(DexModule::ramp_amplification(Origin::root(), 0, 200, 10),
+                 Error::<TestRuntime>::InvalidAmplificationRamp);
+    // at most 10 times of the current amplification
+    assert_noop!(DexModule::ramp_amplification(Origin::root(), 0, 1001, 101),
+                 Error::<TestRuntime>::InvalidAmplificationRamp);
+    assert_noop!(DexModule::ramp_amplification(Origin::root(), 0, 9, 101),
+                 Error::<TestRuntime>::InvalidAmplificationRamp);
+
+    assert_ok!(DexModule::ramp_amplification(Origin::root(), 0, 200, 101));
+    let event = TestEvent::dex(RawEvent::AmplificationRamped(0, 100, 200, 101));
+    assert!(System::events().iter().any(|record| record.event == event));
+    let pool = DexModule::stable_pools(0).unwrap();
+    assert_eq!(pool.amplification(1), 100);
+    assert_eq!(pool.amplification(51), 150);
+    assert_eq!(pool.amplification(101), 200);
+    assert_eq!(pool.amplification(1000), 200);
+
+    // the higher amplification gives the better price around the balanced point
+    let target_amount = DexModule::get_target_amounts(&[DOT, CUSDT], 10 * DOLLARS).unwrap()[1];
+    System::set_block_number(51);
+    assert!(DexModule::get_target_amounts(&[DOT, CUSDT], 10 * DOLLARS).unwrap()[1] > target_amount);
+
+    // ramp down from the current amplification
+    assert_ok!(DexModule::ramp_amplification(Origin::root(), 0, 50, 61));
+    let pool = DexModule::stable_pools(0).unwrap();
+    assert_eq!((pool.initial_amplification, pool.initial_block), (150, 51));
+    assert_eq!(pool.amplification(56), 100);
+
+    System::set_block_number(56);
+    assert_ok!(DexModule::stop_amplification_ramp(Origin::root(), 0));
+    let event = TestEvent::dex(RawEvent::AmplificationRampStopped(0, 100));
+    assert!(System::events().iter().any(|record| record.event == event));
+    assert_eq!(DexModule::stable_pools(0).unwrap().amplification(61), 100);
+  });
+}
+
+#[test]
+fn test_add_remove_stable_liquidity() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let bob = AccountId::from(BOB);
+    let module_account = DexModule::account_id();
+    assert_noop!(DexModule::add_stable_liquidity(Origin::signed(alice.clone()), 0, vec![DOLLARS, DOLLARS], 0),
+                 Error::<TestRuntime>::StablePoolNotFound);
+    assert_ok!(DexModule::create_stable_pool(Origin::root(), vec![CUSDT, DOT, CETH], 100, stable_fee()));
+
+    assert_noop!(DexModule::add_stable_liquidity(Origin::signed(alice.clone()), 0, vec![DOLLARS, DOLLARS], 0),
+                 Error::<TestRuntime>::InvalidStableAmounts);
+    assert_noop!(DexModule::add_stable_liquidity(Origin::signed(alice.clone()), 0, vec![0, 0, 0], 0),
+                 Error::<TestRuntime>::InvalidAmount);
+    // the first liquidity should have all the currencies
+    assert_noop!(DexModule::add_stable_liquidity(Origin::signed(alice.clone()), 0, vec![DOLLARS, DOLLARS, 0], 0),
+                 Error::<TestRuntime>::InvalidStableAmounts);
+
+    // the first shares are the invariant
+    assert_ok!(DexModule::add_stable_liquidity(Origin::signed(alice.clone()), 0,
+                                               vec![100 * DOLLARS, 100 * DOLLARS, 100 * DOLLARS], 0));
+    let pool = DexModule::stable_pools(0).unwrap();
+    assert_eq!(pool.balances, vec![100 * DOLLARS, 100 * DOLLARS, 100 * DOLLARS]);
+    assert_eq!(pool.total_shares, 300 * DOLLARS);
+    assert_eq!(DexModule::stable_shares(0, &alice), 300 * DOLLARS);
+    assert_eq!(stable_incentive_shares(&alice, 0), 300 * DOLLARS);
+    assert_eq!(Currencies::free_balance(CETH, &module_account), 100 * DOLLARS);
+    assert_eq!(Currencies::free_balance(CETH, &alice), 999_900 * DOLLARS);
+    let event = TestEvent::dex(RawEvent::AddStableLiquidity(alice.clone(), 0,
+                                                            vec![100 * DOLLARS, 100 * DOLLARS, 100 * DOLLARS], 300 * DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == event));
+
+    // the imbalanced liquidity is charged the fee
+    let shares = 9_994_865_816_219_612_333;
+    assert_noop!(DexModule::add_stable_liquidity(Origin::signed(bob.clone()), 0, vec![10 * DOLLARS, 0, 0], shares + 1),
+                 Error::<TestRuntime>::SlippageExceeded);
+    assert_ok!(DexModule::add_stable_liquidity(Origin::signed(bob.clone()), 0, vec![10 * DOLLARS, 0, 0], shares));
+    let pool = DexModule::stable_pools(0).unwrap();
+    assert_eq!(pool.balances, vec![110 * DOLLARS, 100 * DOLLARS, 100 * DOLLARS]);
+    assert_eq!(pool.total_shares, 300 * DOLLARS + shares);
+    assert_eq!(DexModule::stable_shares(0, &bob), shares);
+    assert_eq!(stable_incentive_shares(&bob, 0), shares);
+    assert_eq!(Currencies::free_balance(CUSDT, &bob), 999_990 * DOLLARS);
+    assert_eq!(Currencies::free_balance(DOT, &bob), 1_000_000 * DOLLARS);
+
+    // the liquidity is removed in the ratio of the pool
+    assert_noop!(DexModule::remove_stable_liquidity(Origin::signed(bob.clone()), 0, shares + 1, vec![0, 0, 0]),
+                 Error::<TestRuntime>::InsufficientShares);
+    assert_noop!(DexModule::remove_stable_liquidity(Origin::signed(bob.clone()), 0, 0, vec![0, 0, 0]),
+                 Error::<TestRuntime>::InvalidAmount);
+    assert_noop!(DexModule::remove_stable_liquidity(Origin::signed(bob.clone()), 0, shares, vec![0, 0]),
+                 Error::<TestRuntime>::InvalidStableAmounts);
+    let amounts = vec![3_546_624_028_399_093_859, 3_224_203_662_180_994_417, 3_224_203_662_180_994_417];
+    assert_noop!(DexModule::remove_stable_liquidity(Origin::signed(bob.clone()), 0, shares,
+                                                    vec![amounts[0] + 1, 0, 0]),
+                 Error::<TestRuntime>::SlippageExceeded);
+    assert_ok!(DexModule::remove_stable_liquidity(Origin::signed(bob.clone()), 0, shares, amounts.clone()));
+    let pool = DexModule::stable_pools(0).unwrap();
+    assert_eq!(pool.balances, vec![110 * DOLLARS - amounts[0], 100 * DOLLARS - amounts[1], 100 * DOLLARS - amounts[2]]);
+    assert_eq!(pool.total_shares, 300 * DOLLARS);
+    assert!(!<StableShares<TestRuntime>>::contains_key(0, &bob));
+    assert_eq!(stable_incentive_shares(&bob, 0), 0);
+    assert_eq!(Currencies::free_balance(CUSDT, &bob), 999_990 * DOLLARS + amounts[0]);
+    assert_eq!(Currencies::free_balance(DOT, &bob), 1_000_000 * DOLLARS + amounts[1]);
+    let event = TestEvent::dex(RawEvent::RemoveStableLiquidity(bob.clone(), 0, amounts, shares));
+    assert!(System::events().iter().any(|record| record.event == event));
+  });
+}
+
+#[test]
+fn test_stable_swap_routing() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let bob = AccountId::from(BOB);
+    add_initial_liquidity(&alice);
+    create_stable_pool(&alice);
+
+    // the currencies of the stable pool are connected, the stable pool is used at most once in a path
+    assert_eq!(DexModule::find_paths(CETH, DOT), vec![vec![CETH, CUSDT, DOT], vec![CETH, DOT]]);
+    assert_eq!(DexModule::get_target_amounts(&[CETH, CUSDT, DOT], 10 * DOLLARS).map(|amounts| amounts.len()), Some(3));
+    assert_eq!(DexModule::get_target_amounts(&[CETH, DOT, CUSDT], 10 * DOLLARS).map(|amounts| amounts.len()), Some(3));
+    assert_eq!(DexModule::get_target_route(&[DOT, CETH, CUSDT], 10 * DOLLARS), None);
+
+    // each hop goes through the pool with the better price
+    let stable_amount = 9_986_015_082_041_261_129;
+    assert_eq!(DexModule::get_target_route(&[DOT, CUSDT], 10 * DOLLARS),
+               Some((vec![10 * DOLLARS, stable_amount], vec![SwapPool::Stable(0)])));
+    assert_eq!(DexModule::get_target_route(&[CUSDT, DOT], 10 * DOLLARS),
+               Some((vec![10 * DOLLARS, 36_264_435_755_205_965_263], vec![SwapPool::Pair(pair())])));
+    assert_eq!(DexModule::target_amount_available(DOT, CUSDT, 10 * DOLLARS), (stable_amount, vec![DOT, CUSDT]));
+
+    assert_ok!(DexModule::swap_with_exact_supply(Origin::signed(bob.clone()), DOT, CUSDT, 10 * DOLLARS, stable_amount));
+    let event = TestEvent::dex(RawEvent::StableSwap(bob.clone(), 0, DOT, 10 * DOLLARS, CUSDT, stable_amount));
+    assert!(System::events().iter().any(|record| record.event == event));
+    assert_eq!(DexModule::stable_pools(0).unwrap().balances,
+               vec![100 * DOLLARS - stable_amount, 110 * DOLLARS, 100 * DOLLARS]);
+    assert_eq!(DexModule::liquidity_pool(pair()), (100 * DOLLARS, 400 * DOLLARS));
+    assert_eq!(Currencies::free_balance(DOT, &bob), 999_990 * DOLLARS);
+    assert_eq!(Currencies::free_balance(CUSDT, &bob), 1_000_000 * DOLLARS + stable_amount);
+
+    // exact target through the stable pool
+    let supply_amount = 5_014_799_476_451_836_998;
+    assert_eq!(DexModule::supply_amount_needed(DOT, CUSDT, 5 * DOLLARS), (supply_amount, vec![DOT, CUSDT]));
+    assert_noop!(DexModule::swap_with_exact_target(Origin::signed(bob.clone()), DOT, CUSDT, 5 * DOLLARS, supply_amount - 1),
+                 Error::<TestRuntime>::SlippageExceeded);
+    assert_ok!(DexModule::swap_with_exact_target(Origin::signed(bob.clone()), DOT, CUSDT, 5 * DOLLARS, supply_amount));
+    assert_eq!(DexModule::stable_pools(0).unwrap().balances,
+               vec![95 * DOLLARS - stable_amount, 110 * DOLLARS + supply_amount, 100 * DOLLARS]);
+    assert_eq!(Currencies::free_balance(DOT, &bob), 999_990 * DOLLARS - supply_amount);
+    assert_eq!(Currencies::free_balance(CUSDT, &bob), 1_000_005 * DOLLARS + stable_amount);
+
+    // the module account holds the balances of both the pair and the stable pool
+    let module_account = DexModule::account_id();
+    assert_eq!(Currencies::free_balance(CUSDT, &module_account), 195 * DOLLARS - stable_amount);
+    assert_eq!(Currencies::free_balance(DOT, &module_account), 510 * DOLLARS + supply_amount);
+  });
+}
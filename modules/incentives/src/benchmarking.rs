@@ -0,0 +1,109 @@
+//! Benchmarks of the incentives module
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::benchmarks;
+use frame_support::traits::Get;
+use sp_runtime::FixedPointNumber;
+
+use crate::Trait as Config;
+
+const MAX_SCHEDULES: u32 = 50;
+
+// the pairs without CLV, so the benchmarked CLV/CUSDT pool is not one of them
+const ALLOCATED_PAIRS: [(CurrencyId, CurrencyId); 3] = [
+  (CurrencyId::CUSDT, CurrencyId::DOT),
+  (CurrencyId::CUSDT, CurrencyId::CETH),
+  (CurrencyId::DOT, CurrencyId::CETH),
+];
+
+fn allocate_pools(pools: u32) -> Result<(), &'static str> {
+  for (left, right) in ALLOCATED_PAIRS.iter().take(pools as usize) {
+    let pool_id = PoolId::Dex(PairKey::try_from(*left, *right).ok_or("invalid pair")?);
+    AllocationPoints::insert(pool_id, 100);
+  }
+  TotalAllocationPoints::put(100 * pools);
+  Ok(())
+}
+
+benchmarks! {
+  _ { }
+
+  set_lock_schedules {
+    let s in 1 .. MAX_SCHEDULES;
+    let pool_id = PoolId::Dex(PairKey::try_from(CurrencyId::CLV, CurrencyId::CUSDT).ok_or("invalid pair")?);
+    let schedules: Vec<(T::BlockNumber, Ratio)> = (1..=s)
+      .map(|i| (T::BlockNumber::from(i), Ratio::saturating_from_rational(100 + i, 100)))
+      .collect();
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool_id, schedules)
+  verify {
+    assert_eq!(Module::<T>::lock_schedules(pool_id).len(), s as usize);
+  }
+
+  set_emission_schedule {
+    let s in 1 .. T::MaxEmissionSteps::get();
+    let pool_id = PoolId::Dex(PairKey::try_from(CurrencyId::CLV, CurrencyId::CUSDT).ok_or("invalid pair")?);
+    let steps: Vec<(T::BlockNumber, Balance)> = (0..s)
+      .map(|i| (T::BlockNumber::from(i * 100), 1_000_000_000_000 / Balance::from(i + 1)))
+      .collect();
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool_id, CurrencyId::CLV, Some(EmissionSchedule::Steps(steps)))
+  verify {
+    assert!(Module::<T>::dex_emission_schedules(pool_id, CurrencyId::CLV).is_some());
+  }
+
+  add_dex_reward {
+    let pool_id = PoolId::Dex(PairKey::try_from(CurrencyId::CLV, CurrencyId::CUSDT).ok_or("invalid pair")?);
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool_id, CurrencyId::CLV, 1_000_000_000_000)
+  verify {
+    assert_eq!(Module::<T>::dex_incentive_rewards(pool_id, CurrencyId::CLV), 1_000_000_000_000);
+  }
+
+  update_dex_reward {
+    let pool_id = PoolId::Dex(PairKey::try_from(CurrencyId::CLV, CurrencyId::CUSDT).ok_or("invalid pair")?);
+    DexIncentiveRewards::insert(pool_id, CurrencyId::CLV, 1_000_000_000_000);
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool_id, CurrencyId::CLV, 2_000_000_000_000)
+  verify {
+    assert_eq!(Module::<T>::dex_incentive_rewards(pool_id, CurrencyId::CLV), 2_000_000_000_000);
+  }
+
+  remove_dex_reward {
+    let pool_id = PoolId::Dex(PairKey::try_from(CurrencyId::CLV, CurrencyId::CUSDT).ok_or("invalid pair")?);
+    DexIncentiveRewards::insert(pool_id, CurrencyId::CLV, 1_000_000_000_000);
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool_id, CurrencyId::CLV)
+  verify {
+    assert!(!DexIncentiveRewards::contains_key(pool_id, CurrencyId::CLV));
+  }
+
+  set_dex_reward_per_second {
+    let pool_id = PoolId::Dex(PairKey::try_from(CurrencyId::CLV, CurrencyId::CUSDT).ok_or("invalid pair")?);
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool_id, CurrencyId::CLV, 1_000_000_000)
+  verify {
+    assert_eq!(Module::<T>::dex_incentive_rewards_per_second(pool_id, CurrencyId::CLV), 1_000_000_000);
+  }
+
+  set_global_emission_rate {
+    let p in 1 .. ALLOCATED_PAIRS.len() as u32;
+    allocate_pools(p)?;
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, CurrencyId::CLV, 1_000_000_000_000)
+  verify {
+    assert_eq!(Module::<T>::global_emission_rates(CurrencyId::CLV), 1_000_000_000_000);
+  }
+
+  set_allocation_points {
+    let p in 1 .. ALLOCATED_PAIRS.len() as u32 + 1;
+    allocate_pools(p - 1)?;
+    let pool_id = PoolId::Dex(PairKey::try_from(CurrencyId::CLV, CurrencyId::CUSDT).ok_or("invalid pair")?);
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool_id, 100)
+  verify {
+    assert_eq!(Module::<T>::allocation_points(pool_id), 100);
+  }
+}
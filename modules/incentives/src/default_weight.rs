@@ -0,0 +1,57 @@
+//! Default weights of the incentives extrinsics, estimated from the storage access of each call.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(clippy::unnecessary_cast)]
+
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+
+impl crate::WeightInfo for () {
+	fn set_lock_schedules(s: u32, ) -> Weight {
+		(10_000_000 as Weight)
+			.saturating_add((200_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn set_emission_schedule(s: u32, ) -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add((100_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+	}
+	fn add_dex_reward() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(5 as Weight))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+	}
+	fn update_dex_reward() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(5 as Weight))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+	}
+	fn remove_dex_reward() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(5 as Weight))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+	}
+	fn set_dex_reward_per_second() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(5 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+	fn set_global_emission_rate(p: u32, ) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((60_000_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((4 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((3 as Weight).saturating_mul(p as Weight)))
+	}
+	fn set_allocation_points(p: u32, ) -> Weight {
+		(80_000_000 as Weight)
+			.saturating_add((60_000_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().reads((4 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+			.saturating_add(DbWeight::get().writes((3 as Weight).saturating_mul(p as Weight)))
+	}
+}
@@ -2,27 +2,67 @@
 //!
 //! ##Overview
 //! Implements clover incentives based on reward pool
+//!
+//! The module is the reward handler of the dex pools, the rewards are emitted per block by default.
+//! A reward currency of a pool could have an emission schedule in addition to the flat reward per block,
+//! e.g. a linear decay, periodic halving or a step table, which is evaluated for each block of the update.
+//! Besides the dex pools, a single currency could be staked to its single asset pool, the staked balance
+//! is reserved. The queries with a currency pair refer to the single asset pool of the currency
+//! if both currencies of the pair are the same. The rewards and the allocation points are set by the pool id,
+//! so the stable swap pools of the dex are incentivized the same way as the pairs.
+//! The global emission rates are shared by the pools by their allocation points, the pools are settled
+//! before the rates or the points change so the accrued rewards are kept at the old share.
+//! `TimestampRewards` is the handler variant which emits the rewards per second with the moments
+//! recorded for each pool, so the emission follows the wall clock regardless of the block production,
+//! it's used together with the module by a tuple of the reward handlers.
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
 use frame_support::{
-  decl_module, decl_error, decl_storage, debug,
+  decl_module, decl_error, decl_event, decl_storage, debug, ensure,
+  IterableStorageDoubleMap, IterableStorageMap,
+  traits::{EnsureOrigin, Get, UnixTime},
+  weights::Weight,
 };
 use sp_runtime::{
   DispatchError,
   RuntimeDebug,
+  helpers_128bit::multiply_by_rational,
   traits::{
+    AtLeast32BitUnsigned,
+    Convert,
+    One,
     SaturatedConversion,
     Zero,
   }
 };
 use sp_std::prelude::*;
-use sp_std::vec;
-use primitives::{Balance, CurrencyId, Share, };
+use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData, vec};
+use primitives::{Balance, CurrencyId, Ratio, Share, };
 use clover_traits::{RewardPoolOps, IncentiveOps, IncentivePoolAccountInfo, };
-use reward_pool::traits::RewardHandler;
+use reward_pool::traits::{LockScheduleProvider, RewardHandler};
+
+mod default_weight;
+#[cfg(feature = "runtime-benchmarks")]
+pub mod benchmarking;
+mod mock;
+mod tests;
+
+pub trait WeightInfo {
+  fn set_lock_schedules(s: u32) -> Weight;
+  fn set_emission_schedule(s: u32) -> Weight;
+  fn add_dex_reward() -> Weight;
+  fn update_dex_reward() -> Weight;
+  fn remove_dex_reward() -> Weight;
+  fn set_dex_reward_per_second() -> Weight;
+  fn set_global_emission_rate(p: u32) -> Weight;
+  fn set_allocation_points(p: u32) -> Weight;
+}
 
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct PairKey {
   left: CurrencyId,
   right: CurrencyId,
@@ -40,48 +80,422 @@ impl PairKey {
   }
 }
 
+/// Emission schedule of a reward currency, the reward per block changes with the block number
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum EmissionSchedule<BlockNumber> {
+  /// the reward per block decays linearly from `initial` at `start` to `last` at `start + duration`,
+  /// and `last` is emitted per block afterwards
+  LinearDecay {
+    start: BlockNumber,
+    duration: BlockNumber,
+    initial: Balance,
+    last: Balance,
+  },
+  /// the reward per block starts from `initial` at `start` and is halved every `period` blocks
+  Halving {
+    start: BlockNumber,
+    period: BlockNumber,
+    initial: Balance,
+  },
+  /// the reward per block of a step is emitted from the block of the step until the next step,
+  /// the blocks of the steps are increasing
+  Steps(Vec<(BlockNumber, Balance)>),
+}
+
+impl<BlockNumber: AtLeast32BitUnsigned + Copy> EmissionSchedule<BlockNumber> {
+  /// number of the steps, zero for the other schedules
+  pub fn steps(&self) -> u32 {
+    match self {
+      EmissionSchedule::Steps(steps) => steps.len() as u32,
+      _ => 0,
+    }
+  }
+
+  fn is_valid(&self, max_steps: u32) -> bool {
+    match self {
+      EmissionSchedule::LinearDecay { duration, initial, last, .. } => !duration.is_zero() && last <= initial,
+      EmissionSchedule::Halving { period, .. } => !period.is_zero(),
+      EmissionSchedule::Steps(steps) => !steps.is_empty() &&
+        steps.len() <= max_steps as usize &&
+        steps.windows(2).all(|pair| pair[0].0 < pair[1].0),
+    }
+  }
+
+  /// total rewards of the blocks in [from, to)
+  pub fn rewards_between(&self, from: BlockNumber, to: BlockNumber) -> Balance {
+    let block = |b: &BlockNumber| -> u128 { (*b).saturated_into() };
+    let (from, to) = (block(&from), block(&to));
+    if from >= to {
+      return Zero::zero();
+    }
+
+    match self {
+      EmissionSchedule::LinearDecay { start, duration, initial, last } => {
+        let (start, duration) = (block(start), block(duration));
+        let end = start.saturating_add(duration);
+        let mut rewards: Balance = Zero::zero();
+
+        // the reward of the k-th block since start is initial - (initial - last) * k / duration
+        let (decay_from, decay_to) = (from.max(start), to.min(end));
+        if decay_from < decay_to {
+          let (k0, k1) = (decay_from - start, decay_to - start);
+          let blocks = k1 - k0;
+          let sum_k = (k0 + k1 - 1).saturating_mul(blocks) / 2;
+          let decline = multiply_by_rational(initial.saturating_sub(*last), sum_k, duration)
+            .unwrap_or_else(|_| Balance::max_value());
+          rewards = initial.saturating_mul(blocks).saturating_sub(decline);
+        }
+
+        let flat_from = from.max(end);
+        if flat_from < to {
+          rewards = rewards.saturating_add(last.saturating_mul(to - flat_from));
+        }
+        rewards
+      },
+      EmissionSchedule::Halving { start, period, initial } => {
+        let (start, period) = (block(start), block(period));
+        let mut rewards: Balance = Zero::zero();
+        let mut cur = from.max(start);
+        while cur < to {
+          let halvings = (cur - start) / period;
+          if halvings >= 128 || (*initial >> halvings).is_zero() {
+            break;
+          }
+          let next = start.saturating_add(halvings.saturating_add(1).saturating_mul(period)).min(to);
+          rewards = rewards.saturating_add((*initial >> halvings).saturating_mul(next - cur));
+          cur = next;
+        }
+        rewards
+      },
+      EmissionSchedule::Steps(steps) => {
+        steps.iter().enumerate().fold(Zero::zero(), |rewards: Balance, (i, (step_block, reward))| {
+          let step_from = from.max(block(step_block));
+          let step_to = steps.get(i + 1).map_or(to, |(next_block, _)| to.min(block(next_block)));
+          if step_from < step_to {
+            rewards.saturating_add(reward.saturating_mul(step_to - step_from))
+          } else {
+            rewards
+          }
+        })
+      },
+    }
+  }
+}
+
 /// PoolId for various rewards pools
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum PoolId {
   /// Rewards for dex module
   Dex(PairKey),
+  /// Rewards for staking a single currency, the staked balance is reserved
+  Single(CurrencyId),
+  /// Rewards for the stable swap pool of the dex, identified by the pool index
+  Stable(u32),
+}
+
+impl PoolId {
+  /// the pair of a dex pool should be sorted and of different currencies like the pairs of the calls
+  fn is_valid(&self) -> bool {
+    match self {
+      PoolId::Dex(k) => k.left < k.right,
+      PoolId::Single(_) | PoolId::Stable(_) => true,
+    }
+  }
+}
+
+/// The staking currency of the pools, only the single asset pools are stakeable
+pub struct StakingCurrency;
+impl Convert<PoolId, Option<CurrencyId>> for StakingCurrency {
+  fn convert(pool_id: PoolId) -> Option<CurrencyId> {
+    match pool_id {
+      PoolId::Dex(_) | PoolId::Stable(_) => None,
+      PoolId::Single(currency_id) => Some(currency_id),
+    }
+  }
 }
 
 pub trait Trait: frame_system::Config{
-  type RewardPool:  RewardPoolOps<Self::AccountId, PoolId, Share, Balance>;
+  type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+
+  type RewardPool:  RewardPoolOps<Self::AccountId, PoolId, CurrencyId, Share, Balance, Self::BlockNumber>;
+
+  /// The wall clock time, used by the `TimestampRewards` handler
+  type UnixTime: UnixTime;
+
+  /// The expected block time in milliseconds,
+  /// the `TimestampRewards` handler uses it for the update without a recorded moment
+  type ExpectedBlockTime: Get<u64>;
+
+  /// The max number of the steps of an emission schedule
+  type MaxEmissionSteps: Get<u32>;
+
+  /// The max number of the pools with allocation points
+  type MaxAllocatedPools: Get<u32>;
+
+  /// The origin which may update the incentive pool configurations
+  type UpdateOrigin: EnsureOrigin<Self::Origin>;
+
+  /// Weight information for the extrinsics in this module.
+  type WeightInfo: WeightInfo;
 }
 
 decl_storage! {
   trait Store for Module<T: Trait> as Incentives {
-    // mapping from pool id to its incentive reward per block
-    pub DexIncentiveRewards get(fn dex_incentive_rewards): map hasher(twox_64_concat) PoolId => Balance;
+    // mapping from pool id and reward currency to the reward per block,
+    // a pool could be incentivized by several reward currencies at the same time
+    pub DexIncentiveRewards get(fn dex_incentive_rewards): double_map hasher(twox_64_concat) PoolId, hasher(twox_64_concat) CurrencyId => Balance;
+
+    // mapping from pool id and reward currency to the emission schedule,
+    // which is emitted in addition to the flat reward per block
+    pub DexEmissionSchedules get(fn dex_emission_schedules): double_map hasher(twox_64_concat) PoolId, hasher(twox_64_concat) CurrencyId => Option<EmissionSchedule<T::BlockNumber>>;
+
+    // mapping from reward currency to the global reward per block,
+    // which is shared by the pools by their allocation points
+    pub GlobalEmissionRates get(fn global_emission_rates): map hasher(twox_64_concat) CurrencyId => Balance;
+
+    // mapping from pool id to the allocation points of the pool
+    pub AllocationPoints get(fn allocation_points): map hasher(twox_64_concat) PoolId => u32;
+
+    // sum of the allocation points of all the pools
+    pub TotalAllocationPoints get(fn total_allocation_points): u32;
+
+    // mapping from pool id and reward currency to the reward per second, used by the `TimestampRewards` handler
+    pub DexIncentiveRewardsPerSecond get(fn dex_incentive_rewards_per_second): double_map hasher(twox_64_concat) PoolId, hasher(twox_64_concat) CurrencyId => Balance;
+
+    // the block and the moment in milliseconds of the last reward update of the pools,
+    // recorded by the `TimestampRewards` handler
+    pub PoolRewardMoments get(fn pool_reward_moments): map hasher(twox_64_concat) PoolId => Option<(T::BlockNumber, u64)>;
+
+    // mapping from pool id to the lock periods and the reward multipliers of the locked shares
+    pub LockSchedules get(fn lock_schedules): map hasher(twox_64_concat) PoolId => Vec<(T::BlockNumber, Ratio)>;
   }
 
   add_extra_genesis {
-    config(dex_rewards): Vec<(CurrencyId, CurrencyId, Balance)>;
+    config(dex_rewards): Vec<(CurrencyId, CurrencyId, CurrencyId, Balance)>;
+    config(dex_rewards_per_second): Vec<(CurrencyId, CurrencyId, CurrencyId, Balance)>;
 
     build(|config: &GenesisConfig| {
       debug::info!("got incentives config: {:?}", config.dex_rewards);
-      for (left, right, reward_per_block) in &config.dex_rewards {
-        let pair_key = PairKey::try_from(*left, *right).unwrap();
-        assert!(!reward_per_block.is_zero());
-        DexIncentiveRewards::insert(PoolId::Dex(pair_key), reward_per_block);
+      // the same currencies refer to the single asset pool like the calls, the zero rewards are ignored
+      for (left, right, reward_currency, reward_per_block) in &config.dex_rewards {
+        match Module::<T>::get_pool_id(left, right) {
+          Ok(pool_id) if !reward_per_block.is_zero() =>
+            DexIncentiveRewards::insert(pool_id, *reward_currency, *reward_per_block),
+          _ => debug::error!("ignore the dex rewards of {:?}/{:?}: {:?}", left, right, reward_per_block),
+        }
+      }
+      for (left, right, reward_currency, reward_per_second) in &config.dex_rewards_per_second {
+        match Module::<T>::get_pool_id(left, right) {
+          Ok(pool_id) if !reward_per_second.is_zero() =>
+            DexIncentiveRewardsPerSecond::insert(pool_id, *reward_currency, *reward_per_second),
+          _ => debug::error!("ignore the dex rewards per second of {:?}/{:?}: {:?}", left, right, reward_per_second),
+        }
       }
     })
   }
 }
 
+decl_event!(
+  pub enum Event<T> where
+    <T as frame_system::Config>::BlockNumber,
+  {
+    /// Lock schedules of the pool updated. \[pool_id, schedules\]
+    LockSchedulesUpdated(PoolId, Vec<(BlockNumber, Ratio)>),
+    /// Emission schedule of the reward currency of the pool updated. \[pool_id, reward_currency, schedule\]
+    EmissionScheduleUpdated(PoolId, CurrencyId, Option<EmissionSchedule<BlockNumber>>),
+    /// Reward per block of the reward currency of the pool added. \[pool_id, reward_currency, reward_per_block\]
+    DexRewardAdded(PoolId, CurrencyId, Balance),
+    /// Reward per block of the reward currency of the pool updated. \[pool_id, reward_currency, old_reward_per_block, new_reward_per_block\]
+    DexRewardUpdated(PoolId, CurrencyId, Balance, Balance),
+    /// Reward per block of the reward currency of the pool removed. \[pool_id, reward_currency, old_reward_per_block\]
+    DexRewardRemoved(PoolId, CurrencyId, Balance),
+    /// Reward per second of the reward currency of the pool updated, zero if it's removed. \[pool_id, reward_currency, reward_per_second\]
+    DexRewardPerSecondUpdated(PoolId, CurrencyId, Balance),
+    /// Global reward per block of the reward currency updated. \[reward_currency, reward_per_block\]
+    GlobalEmissionRateUpdated(CurrencyId, Balance),
+    /// Allocation points of the pool updated. \[pool_id, points, total_points\]
+    AllocationPointsUpdated(PoolId, u32, u32),
+  }
+);
+
 decl_error! {
   /// Error for incentive module.
   pub enum Error for Module<T: Trait> {
     /// invalid currency pair
     InvalidCurrencyPair,
+    /// lock period should be greater than zero and not duplicated
+    InvalidLockPeriod,
+    /// lock multiplier should not be less than one
+    InvalidLockMultiplier,
+    /// emission schedule has no steps, too many steps, unordered steps or a zero period
+    InvalidEmissionSchedule,
+    /// reward per block should be greater than zero
+    InvalidRewardRate,
+    /// reward currency of the pool has a reward per block already
+    DexRewardAlreadyExists,
+    /// reward currency of the pool has no reward per block
+    DexRewardNotFound,
+    /// too many pools with allocation points
+    TooManyAllocatedPools,
   }
 }
 
 decl_module! {
   pub struct Module<T: Trait> for enum Call where origin: T::Origin {
     type Error = Error<T>;
+    fn deposit_event() = default;
+
+    /// Set the lock periods and the reward multipliers of the pool,
+    /// the existing locks are not affected.
+    #[weight = T::WeightInfo::set_lock_schedules(schedules.len() as u32)]
+    pub fn set_lock_schedules(origin, pool_id: PoolId, schedules: Vec<(T::BlockNumber, Ratio)>) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      ensure!(pool_id.is_valid(), Error::<T>::InvalidCurrencyPair);
+
+      for (i, (lock_period, multiplier)) in schedules.iter().enumerate() {
+        ensure!(!lock_period.is_zero(), Error::<T>::InvalidLockPeriod);
+        ensure!(!schedules[..i].iter().any(|(p, _)| p == lock_period), Error::<T>::InvalidLockPeriod);
+        ensure!(*multiplier >= Ratio::one(), Error::<T>::InvalidLockMultiplier);
+      }
+
+      if schedules.is_empty() {
+        LockSchedules::<T>::remove(pool_id);
+      } else {
+        LockSchedules::<T>::insert(pool_id, schedules.clone());
+      }
+      Self::deposit_event(RawEvent::LockSchedulesUpdated(pool_id, schedules));
+    }
+
+    /// Set the emission schedule of the reward currency of the pool,
+    /// the schedule is removed if it's `None`.
+    #[weight = T::WeightInfo::set_emission_schedule(schedule.as_ref().map_or(0, |schedule| schedule.steps()))]
+    pub fn set_emission_schedule(origin,
+                                 pool_id: PoolId,
+                                 reward_currency: CurrencyId,
+                                 schedule: Option<EmissionSchedule<T::BlockNumber>>) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      ensure!(pool_id.is_valid(), Error::<T>::InvalidCurrencyPair);
+      if let Some(s) = &schedule {
+        ensure!(s.is_valid(T::MaxEmissionSteps::get()), Error::<T>::InvalidEmissionSchedule);
+      }
+
+      T::RewardPool::update_pool_rewards(&pool_id)?;
+      match &schedule {
+        Some(s) => DexEmissionSchedules::<T>::insert(pool_id, reward_currency, s),
+        None => DexEmissionSchedules::<T>::remove(pool_id, reward_currency),
+      }
+      Self::deposit_event(RawEvent::EmissionScheduleUpdated(pool_id, reward_currency, schedule));
+    }
+
+    /// Add the reward per block of the reward currency to the pool,
+    /// the rewards accrued before are settled first.
+    #[weight = T::WeightInfo::add_dex_reward()]
+    pub fn add_dex_reward(origin,
+                          pool_id: PoolId,
+                          reward_currency: CurrencyId,
+                          #[compact] reward_per_block: Balance) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      ensure!(pool_id.is_valid(), Error::<T>::InvalidCurrencyPair);
+      ensure!(!reward_per_block.is_zero(), Error::<T>::InvalidRewardRate);
+      ensure!(!DexIncentiveRewards::contains_key(pool_id, reward_currency), Error::<T>::DexRewardAlreadyExists);
+
+      T::RewardPool::update_pool_rewards(&pool_id)?;
+      DexIncentiveRewards::insert(pool_id, reward_currency, reward_per_block);
+      Self::deposit_event(RawEvent::DexRewardAdded(pool_id, reward_currency, reward_per_block));
+    }
+
+    /// Update the reward per block of the reward currency of the pool,
+    /// the rewards accrued at the old rate are settled first.
+    #[weight = T::WeightInfo::update_dex_reward()]
+    pub fn update_dex_reward(origin,
+                             pool_id: PoolId,
+                             reward_currency: CurrencyId,
+                             #[compact] reward_per_block: Balance) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      ensure!(pool_id.is_valid(), Error::<T>::InvalidCurrencyPair);
+      ensure!(!reward_per_block.is_zero(), Error::<T>::InvalidRewardRate);
+      ensure!(DexIncentiveRewards::contains_key(pool_id, reward_currency), Error::<T>::DexRewardNotFound);
+
+      T::RewardPool::update_pool_rewards(&pool_id)?;
+      let old_reward_per_block = DexIncentiveRewards::mutate(pool_id, reward_currency, |reward| {
+        sp_std::mem::replace(reward, reward_per_block)
+      });
+      Self::deposit_event(RawEvent::DexRewardUpdated(pool_id, reward_currency, old_reward_per_block, reward_per_block));
+    }
+
+    /// Remove the reward per block of the reward currency of the pool,
+    /// the rewards accrued at the old rate are settled first.
+    #[weight = T::WeightInfo::remove_dex_reward()]
+    pub fn remove_dex_reward(origin, pool_id: PoolId, reward_currency: CurrencyId) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      ensure!(pool_id.is_valid(), Error::<T>::InvalidCurrencyPair);
+      ensure!(DexIncentiveRewards::contains_key(pool_id, reward_currency), Error::<T>::DexRewardNotFound);
+
+      T::RewardPool::update_pool_rewards(&pool_id)?;
+      let old_reward_per_block = DexIncentiveRewards::take(pool_id, reward_currency);
+      Self::deposit_event(RawEvent::DexRewardRemoved(pool_id, reward_currency, old_reward_per_block));
+    }
+
+    /// Set the reward per second of the reward currency of the pool, which is emitted by the
+    /// `TimestampRewards` handler, it's removed if the reward is zero.
+    /// the rewards accrued at the old rate are settled first and the moment of the settlement is recorded.
+    #[weight = T::WeightInfo::set_dex_reward_per_second()]
+    pub fn set_dex_reward_per_second(origin,
+                                     pool_id: PoolId,
+                                     reward_currency: CurrencyId,
+                                     #[compact] reward_per_second: Balance) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      ensure!(pool_id.is_valid(), Error::<T>::InvalidCurrencyPair);
+
+      T::RewardPool::update_pool_rewards(&pool_id)?;
+      let now = <frame_system::Module<T>>::block_number();
+      PoolRewardMoments::<T>::insert(pool_id, (now, T::UnixTime::now().as_millis().saturated_into::<u64>()));
+      if reward_per_second.is_zero() {
+        DexIncentiveRewardsPerSecond::remove(pool_id, reward_currency);
+      } else {
+        DexIncentiveRewardsPerSecond::insert(pool_id, reward_currency, reward_per_second);
+      }
+      Self::deposit_event(RawEvent::DexRewardPerSecondUpdated(pool_id, reward_currency, reward_per_second));
+    }
+
+    /// Set the global reward per block of the reward currency, it's removed if the reward is zero.
+    /// the pools with allocation points are settled first.
+    #[weight = T::WeightInfo::set_global_emission_rate(T::MaxAllocatedPools::get())]
+    pub fn set_global_emission_rate(origin, reward_currency: CurrencyId, #[compact] reward_per_block: Balance) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+
+      Self::update_allocated_pools()?;
+      if reward_per_block.is_zero() {
+        GlobalEmissionRates::remove(reward_currency);
+      } else {
+        GlobalEmissionRates::insert(reward_currency, reward_per_block);
+      }
+      Self::deposit_event(RawEvent::GlobalEmissionRateUpdated(reward_currency, reward_per_block));
+    }
+
+    /// Set the allocation points of the pool, it's removed if the points are zero,
+    /// the pools with allocation points are settled first as the share of each pool changes.
+    #[weight = T::WeightInfo::set_allocation_points(T::MaxAllocatedPools::get())]
+    pub fn set_allocation_points(origin, pool_id: PoolId, points: u32) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      ensure!(pool_id.is_valid(), Error::<T>::InvalidCurrencyPair);
+      let allocated_pools = AllocationPoints::iter().count() as u32;
+      ensure!(
+        points.is_zero() || AllocationPoints::contains_key(pool_id) || allocated_pools < T::MaxAllocatedPools::get(),
+        Error::<T>::TooManyAllocatedPools,
+      );
+
+      Self::update_allocated_pools()?;
+      T::RewardPool::update_pool_rewards(&pool_id)?;
+      let old_points = AllocationPoints::take(pool_id);
+      if !points.is_zero() {
+        AllocationPoints::insert(pool_id, points);
+      }
+      let total_points = Self::total_allocation_points().saturating_sub(old_points).saturating_add(points);
+      TotalAllocationPoints::put(total_points);
+      Self::deposit_event(RawEvent::AllocationPointsUpdated(pool_id, points, total_points));
+    }
   }
 }
 
@@ -132,32 +546,173 @@ impl <T: Trait> Module<T> {
       .ok_or(Error::<T>::InvalidCurrencyPair)?;
     Ok(PoolId::Dex(pair_key))
   }
+
+  /// the dex pool of the pair, or the single asset pool if both currencies are the same
+  fn get_pool_id(left: &CurrencyId, right: &CurrencyId) -> Result<PoolId, DispatchError> {
+    if left == right {
+      Ok(PoolId::Single(*left))
+    } else {
+      Self::get_dex_id(left, right)
+    }
+  }
+
+  /// the shares are not added to a closed pool so the liquidity could still be added,
+  /// returns the shares of the account in the pool
+  fn add_pool_share(who: &T::AccountId, pool_id: PoolId, amount: Share) -> Result<Share, DispatchError> {
+    if T::RewardPool::is_pool_closed(&pool_id) {
+      return Ok(T::RewardPool::get_account_shares(who, &pool_id));
+    }
+    T::RewardPool::add_share(who, pool_id, amount)
+  }
+
+  /// only the shares which were added before the pool is closed are removed from a closed pool,
+  /// returns the shares of the account in the pool
+  fn remove_pool_share(who: &T::AccountId, pool_id: PoolId, amount: Share) -> Result<Share, DispatchError> {
+    let mut amount = amount;
+    if T::RewardPool::is_pool_closed(&pool_id) {
+      let shares = T::RewardPool::get_account_shares(who, &pool_id);
+      if shares.is_zero() {
+        return Ok(shares);
+      }
+      amount = amount.min(shares);
+    }
+    T::RewardPool::remove_share(who, pool_id, amount)
+  }
+
+  /// settle the rewards of the pools with allocation points
+  fn update_allocated_pools() -> Result<(), DispatchError> {
+    for (pool_id, _) in AllocationPoints::iter() {
+      T::RewardPool::update_pool_rewards(&pool_id)?;
+    }
+    Ok(())
+  }
+
+  /// the share of the global reward per block of the pool
+  fn allocated_reward(reward_per_block: Balance, points: u32) -> Balance {
+    multiply_by_rational(reward_per_block, points.into(), Self::total_allocation_points().into())
+      .unwrap_or_else(|_| Zero::zero())
+  }
+
+  /// rewards of the blocks in [from, to): the flat reward, the allocated reward and the emission schedules
+  fn rewards_between(pool_id: &PoolId, from: T::BlockNumber, to: T::BlockNumber) -> BTreeMap<CurrencyId, Balance> {
+    let blocks: Balance = (to - from).saturated_into();
+    let mut rewards: BTreeMap<CurrencyId, Balance> = DexIncentiveRewards::iter_prefix(pool_id)
+      .filter(|(_, reward_ratio)| !reward_ratio.is_zero())
+      .map(|(reward_currency, reward_ratio)| (reward_currency, reward_ratio.saturating_mul(blocks)))
+      .collect();
+    let mut add_reward = |reward_currency: CurrencyId, reward: Balance| {
+      if !reward.is_zero() {
+        let total = rewards.entry(reward_currency).or_default();
+        *total = total.saturating_add(reward);
+      }
+    };
+
+    let points = Self::allocation_points(pool_id);
+    if !points.is_zero() {
+      for (reward_currency, reward_per_block) in GlobalEmissionRates::iter() {
+        add_reward(reward_currency, Self::allocated_reward(reward_per_block, points).saturating_mul(blocks));
+      }
+    }
+
+    for (reward_currency, schedule) in DexEmissionSchedules::<T>::iter_prefix(pool_id) {
+      add_reward(reward_currency, schedule.rewards_between(from, to));
+    }
+    rewards
+  }
+
+  /// the reward per block of each reward currency of the pool at the current block
+  pub fn get_pool_reward_rates(pool_id: &PoolId) -> Vec<(CurrencyId, Balance)> {
+    let now = <frame_system::Module<T>>::block_number();
+    Self::rewards_between(pool_id, now, now + One::one()).into_iter().collect()
+  }
 }
 
-impl <T: Trait> RewardHandler<T::AccountId, T::BlockNumber, Balance, Share, PoolId> for Module<T>
+impl <T: Trait> RewardHandler<T::AccountId, T::BlockNumber, Balance, Share, PoolId, CurrencyId> for Module<T>
 where T::BlockNumber: SaturatedConversion, {
   fn caculate_reward(pool_id: &PoolId,
                      total_share: &Share,
                      last_update_block: T::BlockNumber,
-                     now: T::BlockNumber) -> Balance {
+                     now: T::BlockNumber) -> Vec<(CurrencyId, Balance)> {
     // no shares in the pool, should not pay the reward
-    if total_share.is_zero() {
-      return Balance::zero();
+    if total_share.is_zero() || last_update_block >= now {
+      return vec![];
     }
 
-    if !DexIncentiveRewards::contains_key(pool_id) || last_update_block >= now {
-      return Balance::zero();
+    Self::rewards_between(pool_id, last_update_block, now).into_iter().collect()
+  }
+}
+
+/// Reward handler which emits the rewards per second
+/// the elapsed time of an update is the difference of the moments of its blocks, the moment is recorded
+/// when the pool is updated to the current block, the moments of the other blocks are estimated
+/// by the expected block time, e.g. the first update of the pool or the pool was updated without rewards
+pub struct TimestampRewards<T>(PhantomData<T>);
+
+impl<T: Trait> TimestampRewards<T> {
+  /// the moment of the block in milliseconds, it's the wall clock for the current block
+  /// and the recorded moment for the block of the last update, the other blocks are estimated backwards
+  /// from the current block but not earlier than the recorded moment
+  fn moment_at(pool_id: &PoolId, block: T::BlockNumber) -> u64 {
+    let cur_block = <frame_system::Module<T>>::block_number();
+    let moment = T::UnixTime::now().as_millis().saturated_into::<u64>();
+    if block >= cur_block {
+      return moment;
     }
-    let reward_ratio = Self::dex_incentive_rewards(pool_id);
-    if reward_ratio.is_zero() {
-      return Balance::zero();
+
+    let recorded = Module::<T>::pool_reward_moments(pool_id);
+    match recorded {
+      Some((recorded_block, recorded_moment)) if recorded_block == block => recorded_moment,
+      _ => {
+        let blocks: u64 = (cur_block - block).saturated_into();
+        let estimated = moment.saturating_sub(blocks.saturating_mul(T::ExpectedBlockTime::get()));
+        recorded
+          .filter(|(recorded_block, _)| *recorded_block < block)
+          .map_or(estimated, |(_, recorded_moment)| estimated.max(recorded_moment))
+      },
     }
-    let blocks = now - last_update_block;
-    reward_ratio.saturating_mul(blocks.saturated_into())
   }
 }
 
-impl<T: Trait> IncentiveOps<T::AccountId, CurrencyId, Share, Balance> for Module<T> {
+impl<T: Trait> RewardHandler<T::AccountId, T::BlockNumber, Balance, Share, PoolId, CurrencyId> for TimestampRewards<T>
+where T::BlockNumber: SaturatedConversion, {
+  fn caculate_reward(pool_id: &PoolId,
+                     total_share: &Share,
+                     last_update_block: T::BlockNumber,
+                     now: T::BlockNumber) -> Vec<(CurrencyId, Balance)> {
+    // no shares in the pool, should not pay the reward
+    if total_share.is_zero() || last_update_block >= now {
+      return vec![];
+    }
+
+    let rewards_per_second: Vec<(CurrencyId, Balance)> = DexIncentiveRewardsPerSecond::iter_prefix(pool_id)
+      .filter(|(_, reward_per_second)| !reward_per_second.is_zero())
+      .collect();
+    if rewards_per_second.is_empty() {
+      return vec![];
+    }
+
+    let moment = Self::moment_at(pool_id, now);
+    let millis: Balance = moment.saturating_sub(Self::moment_at(pool_id, last_update_block)).saturated_into();
+    if now == <frame_system::Module<T>>::block_number() {
+      PoolRewardMoments::<T>::insert(pool_id, (now, moment));
+    }
+    rewards_per_second.into_iter()
+      .map(|(reward_currency, reward_per_second)| (reward_currency, reward_per_second.saturating_mul(millis) / 1_000))
+      .collect()
+  }
+}
+
+impl <T: Trait> LockScheduleProvider<PoolId, T::BlockNumber> for Module<T> {
+  fn lock_multiplier(pool_id: &PoolId, lock_period: &T::BlockNumber) -> Option<Ratio> {
+    Self::lock_schedules(pool_id)
+      .into_iter()
+      .find(|(p, _)| p == lock_period)
+      .map(|(_, multiplier)| multiplier)
+  }
+}
+
+impl<T: Trait> IncentiveOps<T::AccountId, CurrencyId, Share, Balance, T::BlockNumber> for Module<T> {
+  type PoolId = PoolId;
 
   fn add_share(who: &T::AccountId,
                currency_first: &CurrencyId,
@@ -165,7 +720,7 @@ impl<T: Trait> IncentiveOps<T::AccountId, CurrencyId, Share, Balance> for Module
                amount: &Share) -> Result<Share, DispatchError>{
     let pair_key = PairKey::try_from(*currency_first, *currency_second)
       .ok_or(Error::<T>::InvalidCurrencyPair)?;
-    T::RewardPool::add_share(who, PoolId::Dex(pair_key), *amount)
+    Self::add_pool_share(who, PoolId::Dex(pair_key), *amount)
   }
 
   fn remove_share(who: &T::AccountId,
@@ -174,48 +729,91 @@ impl<T: Trait> IncentiveOps<T::AccountId, CurrencyId, Share, Balance> for Module
                   amount: &Share) -> Result<Share, DispatchError> {
     let pair_key = PairKey::try_from(*currency_first, *currency_second)
       .ok_or(Error::<T>::InvalidCurrencyPair)?;
-    T::RewardPool::remove_share(who, PoolId::Dex(pair_key), *amount)
+    Self::remove_pool_share(who, PoolId::Dex(pair_key), *amount)
+  }
+
+  fn add_stable_share(who: &T::AccountId, pool_index: u32, amount: &Share) -> Result<Share, DispatchError> {
+    Self::add_pool_share(who, PoolId::Stable(pool_index), *amount)
+  }
+
+  fn remove_stable_share(who: &T::AccountId, pool_index: u32, amount: &Share) -> Result<Share, DispatchError> {
+    Self::remove_pool_share(who, PoolId::Stable(pool_index), *amount)
+  }
+
+  fn stake(who: &T::AccountId, currency_id: &CurrencyId, amount: &Balance) -> Result<Share, DispatchError> {
+    T::RewardPool::stake(who, PoolId::Single(*currency_id), *amount)
+  }
+
+  fn unstake(who: &T::AccountId, currency_id: &CurrencyId, amount: &Balance) -> Result<Share, DispatchError> {
+    T::RewardPool::unstake(who, PoolId::Single(*currency_id), *amount)
+  }
+
+  fn withdraw_unbonded(who: &T::AccountId, left: &CurrencyId, right: &CurrencyId) -> Result<Share, DispatchError> {
+    Self::get_pool_id(left, right)
+      .and_then(|pool_id| T::RewardPool::withdraw_unbonded(who, pool_id))
   }
 
   fn get_account_shares(who: &T::AccountId, left: &CurrencyId, right: &CurrencyId) -> Share {
-    if let Ok(id) = Self::get_dex_id(left, right) {
+    if let Ok(id) = Self::get_pool_id(left, right) {
       T::RewardPool::get_account_shares(who, &id)
     } else {
       Zero::zero()
     }
   }
 
-  fn get_accumlated_rewards(who: &T::AccountId, left: &CurrencyId, right: &CurrencyId) -> Share {
-    if let Ok(id) = Self::get_dex_id(left, right) {
+  fn get_accumlated_rewards(who: &T::AccountId, left: &CurrencyId, right: &CurrencyId) -> Vec<(CurrencyId, Balance)> {
+    if let Ok(id) = Self::get_pool_id(left, right) {
       T::RewardPool::get_accumlated_rewards(who, &id)
     } else {
-      Zero::zero()
+      vec![]
     }
   }
 
-  fn get_account_info(who: &T::AccountId, left: &CurrencyId, right: &CurrencyId) -> IncentivePoolAccountInfo<Share, Balance> {
-    if let Ok(pool_id) = Self::get_dex_id(left, right) {
+  fn get_account_info(who: &T::AccountId, left: &CurrencyId, right: &CurrencyId) -> IncentivePoolAccountInfo<CurrencyId, Share, Balance> {
+    if let Ok(pool_id) = Self::get_pool_id(left, right) {
       let shares = T::RewardPool::get_account_shares(who, &pool_id);
       let accumlated_rewards = T::RewardPool::get_accumlated_rewards(who, &pool_id);
       IncentivePoolAccountInfo { shares, accumlated_rewards, }
     } else {
-      IncentivePoolAccountInfo { shares: Zero::zero(), accumlated_rewards: Zero::zero(), }
+      IncentivePoolAccountInfo { shares: Zero::zero(), accumlated_rewards: vec![], }
     }
   }
 
-  fn claim_rewards(who: &T::AccountId, left: &CurrencyId, right: &CurrencyId) -> Result<Balance, DispatchError> {
-    Self::get_dex_id(left, right)
+  fn claim_rewards(who: &T::AccountId, left: &CurrencyId, right: &CurrencyId) -> Result<Vec<(CurrencyId, Balance)>, DispatchError> {
+    Self::get_pool_id(left, right)
       .and_then(|pool_id| T::RewardPool::claim_rewards(who, &pool_id))
   }
 
-  fn get_all_incentive_pools() -> vec::Vec<(CurrencyId, CurrencyId, Share, Balance)>{
+  fn get_all_incentive_pools() -> vec::Vec<(PoolId, Share, vec::Vec<(CurrencyId, Balance)>)>{
     T::RewardPool::get_all_pools()
-      .iter()
-      .filter(|(pool_id, _, _)| match pool_id {
-        PoolId::Dex(_) => true,
-      })
-      .map(|(pool_id, shares, balance)| match pool_id {
-        PoolId::Dex(k) => (k.left, k.right, shares.clone(), balance.clone()),
+  }
+
+  fn get_pool_reward_rates() -> Vec<(PoolId, Vec<(CurrencyId, Balance)>)> {
+    let mut pools: Vec<PoolId> = T::RewardPool::get_all_pools()
+      .into_iter()
+      .map(|(pool_id, _, _)| pool_id)
+      .collect();
+    let configured = AllocationPoints::iter().map(|(pool_id, _)| pool_id)
+      .chain(DexIncentiveRewards::iter().map(|(pool_id, _, _)| pool_id))
+      .chain(DexEmissionSchedules::<T>::iter().map(|(pool_id, _, _)| pool_id));
+    for pool_id in configured {
+      if !pools.contains(&pool_id) {
+        pools.push(pool_id);
+      }
+    }
+
+    pools.into_iter()
+      .map(|pool_id| (pool_id, Self::get_pool_reward_rates(&pool_id)))
+      .collect()
+  }
+
+  fn get_pending_unbondings(who: &T::AccountId) -> Vec<(PoolId, Share, Vec<(CurrencyId, Balance)>, T::BlockNumber)> {
+    T::RewardPool::get_all_pools()
+      .into_iter()
+      .flat_map(|(pool_id, _, _)| {
+        T::RewardPool::get_unbondings(who, &pool_id)
+          .into_iter()
+          .map(move |(shares, rewards, unbonded_block)| (pool_id, shares, rewards, unbonded_block))
       })
       .collect()
   }
@@ -0,0 +1,262 @@
+#![cfg(test)]
+use super::*;
+use frame_support::{
+  impl_outer_event, impl_outer_origin, parameter_types,
+  traits::{OnFinalize, OnInitialize},
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_std::cell::RefCell;
+use sp_runtime::{ModuleId, testing::Header, traits::IdentityLookup};
+
+pub use primitives::{AccountId, Amount, Balance, CurrencyId, currency::*, };
+
+use orml_currencies::{BasicCurrencyAdapter};
+use orml_traits::parameter_type_with_key;
+
+pub type BlockNumber = u64;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TestRuntime;
+
+mod incentives {
+  pub use super::super::*;
+}
+
+impl_outer_event! {
+  pub enum TestEvent for TestRuntime {
+    frame_system<T>,
+    incentives<T>,
+    reward_pool<T>,
+    orml_tokens<T>,
+    orml_currencies<T>,
+    pallet_balances<T>,
+  }
+}
+
+impl_outer_origin! {
+  pub enum Origin for TestRuntime {}
+}
+
+parameter_types! {
+  pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for TestRuntime {
+  type Origin = Origin;
+  type Index = u64;
+  type BlockNumber = BlockNumber;
+  type Call = ();
+  type Hash = H256;
+  type Hashing = ::sp_runtime::traits::BlakeTwo256;
+  type AccountId = AccountId;
+  type Lookup = IdentityLookup<Self::AccountId>;
+  type Header = Header;
+  type Event = TestEvent;
+  type BlockHashCount = BlockHashCount;
+  type BlockWeights = ();
+  type BlockLength = ();
+  type Version = ();
+  type PalletInfo = ();
+  type AccountData = pallet_balances::AccountData<Balance>;
+  type OnNewAccount = ();
+  type OnKilledAccount = ();
+  type DbWeight = ();
+  type BaseCallFilter = ();
+  type SystemWeightInfo = ();
+  type SS58Prefix = ();
+}
+
+pub type System = frame_system::Module<TestRuntime>;
+
+parameter_types! {
+  pub const ExistentialDeposit: u128 = 500;
+  pub const MaxLocks: u32 = 50;
+}
+
+impl pallet_balances::Config for TestRuntime {
+  type Balance = Balance;
+  type Event = TestEvent;
+  type DustRemoval = ();
+  type ExistentialDeposit = ExistentialDeposit;
+  type AccountStore = System;
+  type MaxLocks = MaxLocks;
+  type WeightInfo = ();
+}
+
+pub type Balances = pallet_balances::Module<TestRuntime>;
+
+parameter_type_with_key! {
+  pub ExistentialDeposits: |_currency_id: CurrencyId| -> Balance {
+    0
+  };
+}
+
+impl orml_tokens::Config for TestRuntime {
+  type Event = TestEvent;
+  type Balance = Balance;
+  type Amount = Amount;
+  type CurrencyId = CurrencyId;
+  type WeightInfo = ();
+  type ExistentialDeposits = ExistentialDeposits;
+  type OnDust = ();
+}
+
+pub type Tokens = orml_tokens::Module<TestRuntime>;
+
+parameter_types! {
+  pub const GetNativeCurrencyId: CurrencyId = CurrencyId::CLV;
+}
+
+impl orml_currencies::Config for TestRuntime {
+  type Event = TestEvent;
+  type MultiCurrency = Tokens;
+  type NativeCurrency = BasicCurrencyAdapter<TestRuntime, Balances, Amount, BlockNumber>;
+  type GetNativeCurrencyId = GetNativeCurrencyId;
+  type WeightInfo = ();
+}
+
+pub type Currencies = orml_currencies::Module<TestRuntime>;
+
+parameter_types! {
+  pub const RewardPoolModuleId: ModuleId = ModuleId(*b"clv/repm");
+  pub const MaxShareLocks: u32 = 2;
+  pub const MaxUnbondingChunks: u32 = 2;
+  pub const MaxClaimPools: u32 = 3;
+}
+
+impl reward_pool::Trait for TestRuntime {
+  type Event = TestEvent;
+  type Currency = Currencies;
+  type ModuleId = RewardPoolModuleId;
+  type GetNativeCurrencyId = GetNativeCurrencyId;
+  type PoolId = PoolId;
+  type Handler = (IncentivesModule, TimestampRewards<TestRuntime>);
+  type ExistentialReward = ExistentialDeposit;
+  type LockSchedule = IncentivesModule;
+  type MaxShareLocks = MaxShareLocks;
+  type MaxUnbondingChunks = MaxUnbondingChunks;
+  type MaxClaimPools = MaxClaimPools;
+  type UpdateOrigin = EnsureRoot<AccountId>;
+  type StakingCurrency = StakingCurrency;
+  type WeightInfo = ();
+}
+
+pub type RewardPoolModule = reward_pool::Module<TestRuntime>;
+
+thread_local! {
+  static NOW: RefCell<u64> = RefCell::new(0);
+}
+
+/// the wall clock of the tests, in milliseconds
+pub fn set_now(millis: u64) {
+  NOW.with(|v| *v.borrow_mut() = millis);
+}
+
+pub struct MockUnixTime;
+impl UnixTime for MockUnixTime {
+  fn now() -> core::time::Duration {
+    core::time::Duration::from_millis(NOW.with(|v| *v.borrow()))
+  }
+}
+
+parameter_types! {
+  pub const ExpectedBlockTime: u64 = 6_000;
+  pub const MaxEmissionSteps: u32 = 4;
+  pub const MaxAllocatedPools: u32 = 2;
+}
+
+impl Trait for TestRuntime {
+  type Event = TestEvent;
+  type RewardPool = RewardPoolModule;
+  type UnixTime = MockUnixTime;
+  type ExpectedBlockTime = ExpectedBlockTime;
+  type MaxEmissionSteps = MaxEmissionSteps;
+  type MaxAllocatedPools = MaxAllocatedPools;
+  type UpdateOrigin = EnsureRoot<AccountId>;
+  type WeightInfo = ();
+}
+
+pub type IncentivesModule = Module<TestRuntime>;
+
+pub const ALICE: [u8; 32] = [0u8; 32];
+pub const CLV: CurrencyId = CurrencyId::CLV;
+pub const CUSDT: CurrencyId = CurrencyId::CUSDT;
+pub const DOT: CurrencyId = CurrencyId::DOT;
+
+pub struct ExtBuilder {
+  endowed_accounts: Vec<(AccountId, CurrencyId, Balance)>,
+  dex_rewards: Vec<(CurrencyId, CurrencyId, CurrencyId, Balance)>,
+}
+
+impl Default for ExtBuilder {
+  fn default() -> Self {
+    let alice = AccountId::from(ALICE);
+
+    Self {
+      endowed_accounts: vec![
+        (alice.clone(), CLV, 1_000_000_000_000_000_000u128),
+        (alice.clone(), DOT, 1_000_000_000_000_000_000u128),
+      ],
+      dex_rewards: vec![],
+    }
+  }
+}
+
+impl ExtBuilder {
+  pub fn dex_rewards(mut self, dex_rewards: Vec<(CurrencyId, CurrencyId, CurrencyId, Balance)>) -> Self {
+    self.dex_rewards = dex_rewards;
+    self
+  }
+
+  pub fn build(self) -> sp_io::TestExternalities {
+    set_now(0);
+
+    let mut t = frame_system::GenesisConfig::default()
+      .build_storage::<TestRuntime>()
+      .unwrap();
+
+    pallet_balances::GenesisConfig::<TestRuntime> {
+      balances: self
+        .endowed_accounts
+        .clone()
+        .into_iter()
+        .filter(|(_, currency_id, _)| *currency_id == CLV)
+        .map(|(account_id, _, initial_balance)| (account_id, initial_balance))
+        .collect::<Vec<_>>(),
+    }
+    .assimilate_storage(&mut t)
+      .unwrap();
+
+    orml_tokens::GenesisConfig::<TestRuntime> {
+      endowed_accounts: self
+        .endowed_accounts
+        .into_iter()
+        .filter(|(_, currency_id, _)| *currency_id != CLV)
+        .collect::<Vec<_>>(),
+    }
+    .assimilate_storage(&mut t).unwrap();
+
+    GenesisConfig {
+      dex_rewards: self.dex_rewards,
+      dex_rewards_per_second: vec![],
+    }
+    .assimilate_storage::<TestRuntime>(&mut t).unwrap();
+
+    let mut ext: sp_io::TestExternalities = t.into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+  }
+}
+
+/// runs to the block `n`, the wall clock advances by the expected block time for each block
+pub fn run_to_block(n: u64) {
+  while System::block_number() < n {
+    RewardPoolModule::on_finalize(System::block_number());
+    System::on_finalize(System::block_number());
+    System::set_block_number(System::block_number() + 1);
+    NOW.with(|v| *v.borrow_mut() += ExpectedBlockTime::get());
+    System::on_initialize(System::block_number());
+    RewardPoolModule::on_initialize(System::block_number());
+  }
+}
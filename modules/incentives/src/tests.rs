@@ -0,0 +1,480 @@
+#![cfg(test)]
+
+use super::*;
+use frame_support::{assert_noop, assert_ok};
+use mock::{
+  ALICE, CLV, CUSDT, DOT, Balances, BlockNumber, IncentivesModule, Origin, RewardPoolModule, System, TestEvent, TestRuntime, Tokens,
+  run_to_block, set_now,
+  ExtBuilder,
+};
+use sp_runtime::traits::BadOrigin;
+
+pub use primitives::{ AccountId, currency::*, };
+
+use IncentivesModule as IM;
+
+fn dex_pool(left: CurrencyId, right: CurrencyId) -> PoolId {
+  PoolId::Dex(PairKey::try_from(left, right).unwrap())
+}
+
+fn pending_rewards(who: &AccountId, left: CurrencyId, right: CurrencyId) -> Balance {
+  IM::get_accumlated_rewards(who, &left, &right).into_iter()
+    .find(|(currency_id, _)| *currency_id == CLV)
+    .map(|(_, reward)| reward)
+    .unwrap_or_default()
+}
+
+#[test]
+fn test_timestamp_rewards() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = dex_pool(CLV, DOT);
+
+    assert_ok!(IM::set_dex_reward_per_second(Origin::root(), pool_id, CLV, DOLLARS));
+    assert_eq!(IM::pool_reward_moments(pool_id), Some((1, 0)));
+    assert_ok!(IM::add_share(&alice, &CLV, &DOT, &100));
+
+    // the block took 10 seconds
+    run_to_block(2);
+    set_now(10_000);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 10 * DOLLARS);
+    // the query doesn't record the moment
+    assert_eq!(IM::pool_reward_moments(pool_id), Some((1, 0)));
+
+    assert_eq!(IM::claim_rewards(&alice, &CLV, &DOT), Ok(vec![(CLV, 10 * DOLLARS)]));
+    assert_eq!(IM::pool_reward_moments(pool_id), Some((2, 10_000)));
+
+    run_to_block(4);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 12 * DOLLARS);
+    assert_eq!(IM::pool_reward_moments(pool_id), Some((2, 10_000)));
+  });
+}
+
+#[test]
+fn test_timestamp_rewards_without_moment() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = dex_pool(CLV, DOT);
+
+    DexIncentiveRewardsPerSecond::insert(pool_id, CLV, DOLLARS);
+    assert_ok!(IM::add_share(&alice, &CLV, &DOT, &100));
+    assert_eq!(IM::pool_reward_moments(pool_id), None);
+
+    // the elapsed time of the blocks without a recorded moment is estimated by the expected block time
+    run_to_block(3);
+    set_now(20_000);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 12 * DOLLARS);
+    assert_eq!(IM::claim_rewards(&alice, &CLV, &DOT), Ok(vec![(CLV, 12 * DOLLARS)]));
+    assert_eq!(IM::pool_reward_moments(pool_id), Some((3, 20_000)));
+  });
+}
+
+#[test]
+fn test_set_dex_reward_per_second() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = dex_pool(CLV, DOT);
+
+    assert_noop!(
+      IM::set_dex_reward_per_second(Origin::signed(alice.clone()), pool_id, CLV, DOLLARS),
+      BadOrigin,
+    );
+
+    assert_ok!(IM::set_dex_reward_per_second(Origin::root(), pool_id, CLV, DOLLARS));
+    assert_eq!(IM::dex_incentive_rewards_per_second(pool_id, CLV), DOLLARS);
+    let updated_event = TestEvent::incentives(RawEvent::DexRewardPerSecondUpdated(pool_id, CLV, DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == updated_event));
+    assert_ok!(IM::add_share(&alice, &CLV, &DOT, &100));
+
+    // the rewards are settled at the old rate
+    run_to_block(3);
+    assert_ok!(IM::set_dex_reward_per_second(Origin::root(), pool_id, CLV, 2 * DOLLARS));
+    assert_eq!(IM::pool_reward_moments(pool_id), Some((3, 12_000)));
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 12 * DOLLARS);
+
+    run_to_block(4);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 24 * DOLLARS);
+
+    assert_ok!(IM::set_dex_reward_per_second(Origin::root(), pool_id, CLV, 0));
+    assert!(!DexIncentiveRewardsPerSecond::contains_key(pool_id, CLV));
+    run_to_block(6);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 24 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_linear_decay_schedule() {
+  // the reward per block decays by 10 from 100 at block 10 to 0 at block 20
+  let schedule = EmissionSchedule::<u64>::LinearDecay { start: 10, duration: 10, initial: 100, last: 0 };
+  assert_eq!(schedule.rewards_between(0, 10), 0);
+  assert_eq!(schedule.rewards_between(10, 20), 550);
+  assert_eq!(schedule.rewards_between(10, 15), 400);
+  assert_eq!(schedule.rewards_between(15, 25), 150);
+  assert_eq!(schedule.rewards_between(20, 30), 0);
+  assert_eq!(schedule.rewards_between(15, 15), 0);
+  assert_eq!(schedule.rewards_between(15, 10), 0);
+
+  // the last reward is emitted per block after the schedule ends
+  let schedule = EmissionSchedule::<u64>::LinearDecay { start: 0, duration: 10, initial: 100, last: 10 };
+  assert_eq!(schedule.rewards_between(5, 15), 185 + 50);
+  assert_eq!(schedule.rewards_between(100, 110), 100);
+
+  // the decline is rounded down, so the reward of each block is rounded up
+  let schedule = EmissionSchedule::<u64>::LinearDecay { start: 0, duration: 3, initial: 10, last: 0 };
+  assert_eq!(schedule.rewards_between(0, 3), 20);
+  assert_eq!(schedule.rewards_between(0, 1), 10);
+  assert_eq!(schedule.rewards_between(1, 2), 7);
+  assert_eq!(schedule.rewards_between(2, 3), 4);
+}
+
+#[test]
+fn test_halving_schedule() {
+  let schedule = EmissionSchedule::<u64>::Halving { start: 10, period: 5, initial: 64 };
+  assert_eq!(schedule.rewards_between(0, 10), 0);
+  assert_eq!(schedule.rewards_between(10, 20), 5 * 64 + 5 * 32);
+  assert_eq!(schedule.rewards_between(12, 17), 3 * 64 + 2 * 32);
+  assert_eq!(schedule.rewards_between(5, 11), 64);
+
+  // the reward is 1 in the 7th period and nothing is emitted afterwards
+  assert_eq!(schedule.rewards_between(40, 100), 5);
+  assert_eq!(schedule.rewards_between(45, 1_000_000), 0);
+
+  // the halved reward is rounded down
+  let schedule = EmissionSchedule::<u64>::Halving { start: 10, period: 5, initial: 5 };
+  assert_eq!(schedule.rewards_between(10, 25), 5 * 5 + 5 * 2 + 5 * 1);
+}
+
+#[test]
+fn test_steps_schedule() {
+  let schedule = EmissionSchedule::<u64>::Steps(vec![(10, 100), (20, 50), (30, 0)]);
+  assert_eq!(schedule.rewards_between(0, 10), 0);
+  assert_eq!(schedule.rewards_between(5, 15), 500);
+  assert_eq!(schedule.rewards_between(15, 25), 5 * 100 + 5 * 50);
+  assert_eq!(schedule.rewards_between(25, 40), 250);
+  assert_eq!(schedule.rewards_between(40, 50), 0);
+  assert_eq!(schedule.steps(), 3);
+
+  // the last step is emitted afterwards
+  let schedule = EmissionSchedule::<u64>::Steps(vec![(10, 100), (20, 50)]);
+  assert_eq!(schedule.rewards_between(30, 40), 500);
+}
+
+#[test]
+fn test_emission_schedule_is_valid() {
+  let max_steps = 4;
+  assert!(EmissionSchedule::<u64>::LinearDecay { start: 0, duration: 10, initial: 100, last: 10 }.is_valid(max_steps));
+  assert!(EmissionSchedule::<u64>::LinearDecay { start: 0, duration: 10, initial: 100, last: 100 }.is_valid(max_steps));
+  assert!(!EmissionSchedule::<u64>::LinearDecay { start: 0, duration: 0, initial: 100, last: 10 }.is_valid(max_steps));
+  assert!(!EmissionSchedule::<u64>::LinearDecay { start: 0, duration: 10, initial: 10, last: 100 }.is_valid(max_steps));
+
+  assert!(EmissionSchedule::<u64>::Halving { start: 0, period: 10, initial: 100 }.is_valid(max_steps));
+  assert!(!EmissionSchedule::<u64>::Halving { start: 0, period: 0, initial: 100 }.is_valid(max_steps));
+
+  assert!(EmissionSchedule::<u64>::Steps(vec![(0, 100), (10, 50), (20, 0), (30, 10)]).is_valid(max_steps));
+  assert!(!EmissionSchedule::<u64>::Steps(vec![]).is_valid(max_steps));
+  assert!(!EmissionSchedule::<u64>::Steps(vec![(0, 100), (10, 50), (20, 0), (30, 10), (40, 5)]).is_valid(max_steps));
+  assert!(!EmissionSchedule::<u64>::Steps(vec![(0, 100), (20, 50), (10, 0)]).is_valid(max_steps));
+  assert!(!EmissionSchedule::<u64>::Steps(vec![(0, 100), (10, 50), (10, 0)]).is_valid(max_steps));
+}
+
+#[test]
+fn test_set_emission_schedule() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = dex_pool(CLV, DOT);
+    let schedule = EmissionSchedule::Steps(vec![(0, DOLLARS), (3, 2 * DOLLARS)]);
+
+    assert_noop!(
+      IM::set_emission_schedule(Origin::signed(alice.clone()), pool_id, CLV, Some(schedule.clone())),
+      BadOrigin,
+    );
+
+    assert_ok!(IM::set_emission_schedule(Origin::root(), pool_id, CLV, Some(schedule.clone())));
+    assert_eq!(IM::dex_emission_schedules(pool_id, CLV), Some(schedule.clone()));
+    let updated_event = TestEvent::incentives(RawEvent::EmissionScheduleUpdated(pool_id, CLV, Some(schedule)));
+    assert!(System::events().iter().any(|record| record.event == updated_event));
+    assert_ok!(IM::add_share(&alice, &CLV, &DOT, &100));
+
+    run_to_block(5);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 6 * DOLLARS);
+    assert_eq!(IM::get_pool_reward_rates(&pool_id), vec![(CLV, 2 * DOLLARS)]);
+
+    // the rewards of the schedule are settled before it's removed
+    assert_ok!(IM::set_emission_schedule(Origin::root(), pool_id, CLV, None));
+    assert_eq!(IM::dex_emission_schedules(pool_id, CLV), None);
+    run_to_block(7);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 6 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_set_invalid_emission_schedule() {
+  ExtBuilder::default().dex_rewards(vec![(CLV, DOT, CLV, DOLLARS)]).build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = dex_pool(CLV, DOT);
+    assert_ok!(IM::add_share(&alice, &CLV, &DOT, &100));
+
+    // the pool is not settled if the schedule is invalid
+    run_to_block(3);
+    assert_noop!(
+      IM::set_emission_schedule(Origin::root(), pool_id, CLV, Some(EmissionSchedule::Steps(vec![]))),
+      Error::<TestRuntime>::InvalidEmissionSchedule,
+    );
+    assert_noop!(
+      IM::set_emission_schedule(Origin::root(), pool_id, CLV, Some(EmissionSchedule::Halving { start: 0, period: 0, initial: DOLLARS })),
+      Error::<TestRuntime>::InvalidEmissionSchedule,
+    );
+  });
+}
+
+#[test]
+fn test_genesis_dex_rewards() {
+  ExtBuilder::default()
+    .dex_rewards(vec![(DOT, CLV, CLV, DOLLARS), (DOT, DOT, CLV, 2 * DOLLARS), (CLV, DOT, DOT, 0)])
+    .build()
+    .execute_with(|| {
+      assert_eq!(IM::dex_incentive_rewards(dex_pool(CLV, DOT), CLV), DOLLARS);
+      assert_eq!(IM::dex_incentive_rewards(PoolId::Single(DOT), CLV), 2 * DOLLARS);
+      assert!(!DexIncentiveRewards::contains_key(dex_pool(CLV, DOT), DOT));
+    });
+}
+
+#[test]
+fn test_add_dex_reward() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = dex_pool(CLV, DOT);
+
+    assert_noop!(IM::add_dex_reward(Origin::signed(alice.clone()), pool_id, CLV, DOLLARS), BadOrigin);
+    assert_noop!(IM::add_dex_reward(Origin::root(), pool_id, CLV, 0), Error::<TestRuntime>::InvalidRewardRate);
+
+    assert_ok!(IM::add_share(&alice, &CLV, &DOT, &100));
+    run_to_block(3);
+    assert_ok!(IM::add_dex_reward(Origin::root(), pool_id, CLV, DOLLARS));
+    assert_eq!(IM::dex_incentive_rewards(pool_id, CLV), DOLLARS);
+    let added_event = TestEvent::incentives(RawEvent::DexRewardAdded(pool_id, CLV, DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == added_event));
+    assert_noop!(
+      IM::add_dex_reward(Origin::root(), pool_id, CLV, 2 * DOLLARS),
+      Error::<TestRuntime>::DexRewardAlreadyExists,
+    );
+
+    // the blocks before the reward is added are settled without the reward
+    run_to_block(5);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 2 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_update_dex_reward() {
+  ExtBuilder::default().dex_rewards(vec![(CLV, DOT, CLV, DOLLARS)]).build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = dex_pool(CLV, DOT);
+
+    assert_noop!(
+      IM::update_dex_reward(Origin::root(), pool_id, DOT, DOLLARS),
+      Error::<TestRuntime>::DexRewardNotFound,
+    );
+    assert_noop!(IM::update_dex_reward(Origin::signed(alice.clone()), pool_id, CLV, 3 * DOLLARS), BadOrigin);
+    assert_noop!(IM::update_dex_reward(Origin::root(), pool_id, CLV, 0), Error::<TestRuntime>::InvalidRewardRate);
+
+    // the rewards are settled at the old rate
+    assert_ok!(IM::add_share(&alice, &CLV, &DOT, &100));
+    run_to_block(3);
+    assert_ok!(IM::update_dex_reward(Origin::root(), pool_id, CLV, 3 * DOLLARS));
+    assert_eq!(IM::dex_incentive_rewards(pool_id, CLV), 3 * DOLLARS);
+    let updated_event = TestEvent::incentives(RawEvent::DexRewardUpdated(pool_id, CLV, DOLLARS, 3 * DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == updated_event));
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 2 * DOLLARS);
+
+    run_to_block(4);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 5 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_remove_dex_reward() {
+  ExtBuilder::default().dex_rewards(vec![(CLV, DOT, CLV, DOLLARS)]).build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = dex_pool(CLV, DOT);
+
+    assert_noop!(IM::remove_dex_reward(Origin::signed(alice.clone()), pool_id, CLV), BadOrigin);
+    assert_noop!(IM::remove_dex_reward(Origin::root(), pool_id, DOT), Error::<TestRuntime>::DexRewardNotFound);
+
+    // the rewards are settled at the old rate
+    assert_ok!(IM::add_share(&alice, &CLV, &DOT, &100));
+    run_to_block(3);
+    assert_ok!(IM::remove_dex_reward(Origin::root(), pool_id, CLV));
+    assert!(!DexIncentiveRewards::contains_key(pool_id, CLV));
+    let removed_event = TestEvent::incentives(RawEvent::DexRewardRemoved(pool_id, CLV, DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == removed_event));
+    assert_noop!(IM::remove_dex_reward(Origin::root(), pool_id, CLV), Error::<TestRuntime>::DexRewardNotFound);
+
+    run_to_block(5);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 2 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_allocation_points() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let dot_pool = dex_pool(CLV, DOT);
+    let usdt_pool = dex_pool(CLV, CUSDT);
+
+    assert_noop!(IM::set_allocation_points(Origin::signed(alice.clone()), dot_pool, 100), BadOrigin);
+    assert_ok!(IM::set_allocation_points(Origin::root(), dot_pool, 100));
+    assert_ok!(IM::set_allocation_points(Origin::root(), usdt_pool, 300));
+    assert_eq!(IM::allocation_points(usdt_pool), 300);
+    assert_eq!(IM::total_allocation_points(), 400);
+    let updated_event = TestEvent::incentives(RawEvent::AllocationPointsUpdated(usdt_pool, 300, 400));
+    assert!(System::events().iter().any(|record| record.event == updated_event));
+
+    // the max number of the allocated pools is 2
+    assert_noop!(
+      IM::set_allocation_points(Origin::root(), dex_pool(DOT, CUSDT), 100),
+      Error::<TestRuntime>::TooManyAllocatedPools,
+    );
+    assert_ok!(IM::set_allocation_points(Origin::root(), dex_pool(DOT, CUSDT), 0));
+
+    assert_noop!(IM::set_global_emission_rate(Origin::signed(alice.clone()), CLV, 4 * DOLLARS), BadOrigin);
+    assert_ok!(IM::set_global_emission_rate(Origin::root(), CLV, 4 * DOLLARS));
+    let updated_event = TestEvent::incentives(RawEvent::GlobalEmissionRateUpdated(CLV, 4 * DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == updated_event));
+    assert_ok!(IM::add_share(&alice, &CLV, &DOT, &100));
+    assert_ok!(IM::add_share(&alice, &CLV, &CUSDT, &100));
+
+    // the global rate is shared by the points
+    run_to_block(3);
+    assert_eq!(IM::get_pool_reward_rates(&dot_pool), vec![(CLV, DOLLARS)]);
+    assert_eq!(IM::get_pool_reward_rates(&usdt_pool), vec![(CLV, 3 * DOLLARS)]);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 2 * DOLLARS);
+    assert_eq!(pending_rewards(&alice, CLV, CUSDT), 6 * DOLLARS);
+
+    // all the allocated pools are settled at the old shares before the points change
+    assert_ok!(IM::set_allocation_points(Origin::root(), dot_pool, 300));
+    assert_eq!(IM::total_allocation_points(), 600);
+    run_to_block(5);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 6 * DOLLARS);
+    assert_eq!(pending_rewards(&alice, CLV, CUSDT), 10 * DOLLARS);
+
+    // and before the global rate changes
+    assert_ok!(IM::set_global_emission_rate(Origin::root(), CLV, 0));
+    assert!(!GlobalEmissionRates::contains_key(CLV));
+    run_to_block(7);
+    assert_eq!(pending_rewards(&alice, CLV, DOT), 6 * DOLLARS);
+    assert_eq!(pending_rewards(&alice, CLV, CUSDT), 10 * DOLLARS);
+
+    // the pool without points doesn't count in the limit
+    assert_ok!(IM::set_allocation_points(Origin::root(), usdt_pool, 0));
+    assert!(!AllocationPoints::contains_key(usdt_pool));
+    assert_eq!(IM::total_allocation_points(), 300);
+    assert_ok!(IM::set_allocation_points(Origin::root(), dex_pool(DOT, CUSDT), 100));
+    assert_eq!(IM::total_allocation_points(), 400);
+  });
+}
+
+#[test]
+fn test_single_pool_stake() {
+  ExtBuilder::default().dex_rewards(vec![(DOT, DOT, CLV, DOLLARS)]).build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = PoolId::Single(DOT);
+
+    assert_noop!(IM::stake(&alice, &DOT, &0), reward_pool::Error::<TestRuntime>::InvalidAmount);
+    // the shares of the single asset pool are only added by staking
+    assert_noop!(IM::add_share(&alice, &DOT, &DOT, &100), Error::<TestRuntime>::InvalidCurrencyPair);
+
+    assert_eq!(IM::stake(&alice, &DOT, &100), Ok(100));
+    assert_eq!(Tokens::accounts(&alice, DOT).reserved, 100);
+    assert_eq!(IM::get_account_shares(&alice, &DOT, &DOT), 100);
+    assert_eq!(RewardPoolModule::get_pool_info(&pool_id).total_shares, 100);
+
+    run_to_block(3);
+    assert_eq!(pending_rewards(&alice, DOT, DOT), 2 * DOLLARS);
+    assert_eq!(IM::get_all_incentive_pools(), vec![(pool_id, 100, vec![(CLV, 2 * DOLLARS)])]);
+    assert_eq!(IM::get_pool_reward_rates(&pool_id), vec![(CLV, DOLLARS)]);
+
+    // the rewards are paid and the unstaked balance is released
+    assert_noop!(IM::unstake(&alice, &DOT, &101), reward_pool::Error::<TestRuntime>::InsufficientShares);
+    assert_eq!(IM::unstake(&alice, &DOT, &50), Ok(50));
+    assert_eq!(Tokens::accounts(&alice, DOT).reserved, 50);
+    assert_eq!(Balances::free_balance(&alice), 3 * DOLLARS);
+    assert_eq!(pending_rewards(&alice, DOT, DOT), 0);
+
+    run_to_block(5);
+    assert_eq!(IM::claim_rewards(&alice, &DOT, &DOT), Ok(vec![(CLV, 2 * DOLLARS)]));
+    assert_eq!(Balances::free_balance(&alice), 5 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_single_pool_unbonding() {
+  ExtBuilder::default().dex_rewards(vec![(DOT, DOT, CLV, DOLLARS)]).build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = PoolId::Single(DOT);
+    assert_ok!(RewardPoolModule::set_unbonding_period(Origin::root(), pool_id, 5));
+    assert_ok!(IM::stake(&alice, &DOT, &100));
+
+    // the staked balance is kept reserved until the shares are unbonded
+    run_to_block(3);
+    assert_eq!(IM::unstake(&alice, &DOT, &100), Ok(0));
+    assert_eq!(Tokens::accounts(&alice, DOT).reserved, 100);
+    assert_eq!(IM::get_pending_unbondings(&alice), vec![(pool_id, 100, vec![(CLV, 2 * DOLLARS)], 8)]);
+    assert_eq!(IM::withdraw_unbonded(&alice, &DOT, &DOT), Ok(0));
+
+    run_to_block(8);
+    assert_eq!(IM::withdraw_unbonded(&alice, &DOT, &DOT), Ok(100));
+    assert_eq!(Tokens::accounts(&alice, DOT).reserved, 0);
+    assert_eq!(Balances::free_balance(&alice), 3 * DOLLARS);
+    assert_eq!(IM::get_pending_unbondings(&alice), vec![]);
+  });
+}
+
+#[test]
+fn test_stable_pool_rewards() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = PoolId::Stable(0);
+
+    // the pair of a dex pool should be sorted
+    let unsorted_pool = PoolId::Dex(PairKey { left: DOT, right: CLV });
+    assert_noop!(IM::add_dex_reward(Origin::root(), unsorted_pool, CLV, DOLLARS), Error::<TestRuntime>::InvalidCurrencyPair);
+    assert_noop!(IM::set_allocation_points(Origin::root(), unsorted_pool, 100), Error::<TestRuntime>::InvalidCurrencyPair);
+
+    assert_ok!(IM::add_dex_reward(Origin::root(), pool_id, CLV, DOLLARS));
+    assert_ok!(IM::set_allocation_points(Origin::root(), pool_id, 100));
+    assert_ok!(IM::set_global_emission_rate(Origin::root(), CLV, 2 * DOLLARS));
+    assert_ok!(IM::add_stable_share(&alice, 0, &100));
+
+    // the stable pools are reported by the pool id
+    run_to_block(3);
+    assert_eq!(IM::get_all_incentive_pools(), vec![(pool_id, 100, vec![(CLV, 6 * DOLLARS)])]);
+    assert_eq!(
+      <IM as IncentiveOps<AccountId, CurrencyId, Share, Balance, BlockNumber>>::get_pool_reward_rates(),
+      vec![(pool_id, vec![(CLV, 3 * DOLLARS)])],
+    );
+  });
+}
+
+#[test]
+fn test_closed_pool_shares() {
+  ExtBuilder::default().build().execute_with(|| {
+    let alice = AccountId::from(ALICE);
+    let pool_id = dex_pool(CLV, DOT);
+
+    assert_ok!(IM::add_share(&alice, &CLV, &DOT, &100));
+    assert_ok!(RewardPoolModule::close_pool(Origin::root(), pool_id));
+
+    // the liquidity added after the pool is closed is not reported
+    assert_eq!(IM::add_share(&alice, &CLV, &DOT, &100), Ok(100));
+    assert_eq!(IM::add_stable_share(&alice, 0, &100), Ok(100));
+    assert_ok!(RewardPoolModule::close_pool(Origin::root(), PoolId::Stable(0)));
+    assert_eq!(IM::add_stable_share(&alice, 0, &100), Ok(100));
+
+    // only the reported shares are removed
+    assert_eq!(IM::remove_share(&alice, &CLV, &DOT, &150), Ok(0));
+    assert_eq!(IM::remove_share(&alice, &CLV, &DOT, &50), Ok(0));
+    assert_eq!(IM::remove_stable_share(&alice, 0, &200), Ok(0));
+    assert_eq!(<RewardPoolModule as RewardPoolOps<_, _, _, _, _, _>>::get_account_shares(&alice, &pool_id), 0);
+  });
+}
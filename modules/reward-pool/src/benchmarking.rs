@@ -0,0 +1,358 @@
+//! Benchmarks of the reward pool module
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+use primitives::currency::DOLLARS;
+
+const SEED: u32 = 0;
+const MAX_ACCOUNTS: u32 = 1000;
+
+/// Pools and lock periods used by the benchmarks
+pub trait Config: Trait {
+  /// returns a pool by the index, pools of different indexes should be different
+  fn pool(index: u32) -> Self::PoolId;
+  /// returns a pool which has a staking currency
+  fn staking_pool() -> Self::PoolId;
+  /// returns a lock period which is supported by the staking pool,
+  /// the lock schedule of the pool should be set up by it if it's kept in the storage
+  fn lock_period() -> Self::BlockNumber;
+}
+
+type RewardPool<T> = Module<T>;
+
+fn fund<T: Config>(who: &T::AccountId, currency_id: CurrencyId, amount: Balance) -> DispatchResult {
+  T::Currency::update_balance(currency_id, who, amount.unique_saturated_into())
+}
+
+/// add shares of `accounts` accounts to the pool, so the pool has a large number of accounts
+fn seed_pool<T: Config>(pool: T::PoolId, accounts: u32) -> DispatchResult {
+  for i in 0..accounts {
+    let who: T::AccountId = account("staker", i, SEED);
+    <RewardPool<T> as RewardPoolOps<_, _, _, _, _, _>>::add_share(&who, pool, DOLLARS)?;
+  }
+  Ok(())
+}
+
+/// returns a caller who staked `amount` in the staking pool
+fn staked_caller<T: Config>(amount: Balance) -> Result<T::AccountId, &'static str> {
+  let pool = T::staking_pool();
+  let currency_id = T::StakingCurrency::convert(pool).ok_or("not a staking pool")?;
+  let caller: T::AccountId = whitelisted_caller();
+  fund::<T>(&caller, currency_id, amount)?;
+  RewardPool::<T>::stake(RawOrigin::Signed(caller.clone()).into(), pool, amount)?;
+  Ok(caller)
+}
+
+fn set_block_number<T: Config>(n: u32) {
+  frame_system::Module::<T>::set_block_number(n.into());
+}
+
+fn vesting_config<T: Config>() -> VestingConfig<T::BlockNumber> {
+  VestingConfig {
+    vesting_ratio: Ratio::saturating_from_rational(1, 2),
+    vesting_period: 100u32.into(),
+    forfeit_penalty: Ratio::saturating_from_rational(1, 2),
+  }
+}
+
+benchmarks! {
+  _ { }
+
+  stake {
+    let a in 1 .. MAX_ACCOUNTS;
+    let pool = T::staking_pool();
+    let currency_id = T::StakingCurrency::convert(pool).ok_or("not a staking pool")?;
+    seed_pool::<T>(pool, a)?;
+    let caller: T::AccountId = whitelisted_caller();
+    fund::<T>(&caller, currency_id, 10 * DOLLARS)?;
+    set_block_number::<T>(10);
+  }: _(RawOrigin::Signed(caller.clone()), pool, DOLLARS)
+  verify {
+    assert!(!<RewardPool<T> as RewardPoolOps<_, _, _, _, _, _>>::get_account_shares(&caller, &pool).is_zero());
+  }
+
+  unstake {
+    let a in 1 .. MAX_ACCOUNTS;
+    let pool = T::staking_pool();
+    seed_pool::<T>(pool, a)?;
+    let caller = staked_caller::<T>(DOLLARS)?;
+    set_block_number::<T>(10);
+  }: _(RawOrigin::Signed(caller), pool, DOLLARS)
+
+  claim {
+    let a in 1 .. MAX_ACCOUNTS;
+    let pool = T::staking_pool();
+    seed_pool::<T>(pool, a)?;
+    let caller = staked_caller::<T>(DOLLARS)?;
+    set_block_number::<T>(10);
+  }: _(RawOrigin::Signed(caller), pool)
+
+  claim_all {
+    let p in 1 .. T::MaxClaimPools::get();
+    let caller: T::AccountId = whitelisted_caller();
+    let pools: Vec<T::PoolId> = (0..p).map(|i| T::pool(i)).collect();
+    for pool in pools.iter() {
+      <RewardPool<T> as RewardPoolOps<_, _, _, _, _, _>>::add_share(&caller, *pool, DOLLARS)?;
+    }
+    set_block_number::<T>(10);
+  }: _(RawOrigin::Signed(caller), pools)
+
+  lock {
+    let pool = T::staking_pool();
+    let caller = staked_caller::<T>(DOLLARS)?;
+  }: _(RawOrigin::Signed(caller.clone()), pool, DOLLARS, T::lock_period())
+  verify {
+    assert_eq!(RewardPool::<T>::pool_account_locks(&pool, &caller).len(), 1);
+  }
+
+  release_locks {
+    let pool = T::staking_pool();
+    let caller = staked_caller::<T>(DOLLARS)?;
+    RewardPool::<T>::lock(RawOrigin::Signed(caller.clone()).into(), pool, DOLLARS, T::lock_period())?;
+    let now = frame_system::Module::<T>::block_number();
+    frame_system::Module::<T>::set_block_number(now + T::lock_period());
+    let keeper: T::AccountId = account("keeper", 0, SEED);
+  }: _(RawOrigin::Signed(keeper), caller.clone(), pool)
+  verify {
+    assert!(RewardPool::<T>::pool_account_locks(&pool, &caller).is_empty());
+  }
+
+  withdraw_unbonded {
+    let c in 1 .. T::MaxUnbondingChunks::get();
+    let pool = T::staking_pool();
+    <UnbondingPeriods<T>>::insert(pool, T::BlockNumber::from(1u32));
+    let caller = staked_caller::<T>(DOLLARS.saturating_mul(c.into()))?;
+    set_block_number::<T>(10);
+    for _ in 0..c {
+      RewardPool::<T>::unstake(RawOrigin::Signed(caller.clone()).into(), pool, DOLLARS)?;
+    }
+    set_block_number::<T>(20);
+  }: _(RawOrigin::Signed(caller.clone()), pool)
+  verify {
+    assert!(RewardPool::<T>::unbondings(&pool, &caller).is_empty());
+  }
+
+  set_unbonding_period {
+    let pool = T::staking_pool();
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool, 10u32.into())
+  verify {
+    assert_eq!(RewardPool::<T>::unbonding_periods(&pool), 10u32.into());
+  }
+
+  vest {
+    let pool = T::staking_pool();
+    <VestingConfigs<T>>::insert(pool, vesting_config::<T>());
+    let caller = staked_caller::<T>(DOLLARS)?;
+    set_block_number::<T>(10);
+    <RewardPool<T> as RewardPoolOps<_, _, _, _, _, _>>::claim_rewards(&caller, &pool)?;
+    set_block_number::<T>(60);
+  }: _(RawOrigin::Signed(caller), pool)
+
+  forfeit_vesting {
+    let pool = T::staking_pool();
+    <VestingConfigs<T>>::insert(pool, vesting_config::<T>());
+    seed_pool::<T>(pool, 1)?;
+    let caller = staked_caller::<T>(DOLLARS)?;
+    set_block_number::<T>(10);
+    <RewardPool<T> as RewardPoolOps<_, _, _, _, _, _>>::claim_rewards(&caller, &pool)?;
+    set_block_number::<T>(20);
+  }: _(RawOrigin::Signed(caller.clone()), pool)
+  verify {
+    assert!(RewardPool::<T>::vesting_schedules(&pool, &caller).is_empty());
+  }
+
+  set_vesting_config {
+    let pool = T::staking_pool();
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool, Some(vesting_config::<T>()))
+  verify {
+    assert_eq!(RewardPool::<T>::vesting_configs(&pool), Some(vesting_config::<T>()));
+  }
+
+  create_pool {
+    let pool = T::pool(T::MaxClaimPools::get());
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool, 10u32.into(), Some(100u32.into()))
+  verify {
+    assert!(<PoolStates<T>>::contains_key(&pool));
+  }
+
+  pause_pool {
+    let pool = T::staking_pool();
+    seed_pool::<T>(pool, 1)?;
+    set_block_number::<T>(10);
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool)
+  verify {
+    assert_eq!(RewardPool::<T>::pool_states(&pool).status, PoolStatus::Paused);
+  }
+
+  resume_pool {
+    let pool = T::staking_pool();
+    seed_pool::<T>(pool, 1)?;
+    RewardPool::<T>::pause_pool(T::UpdateOrigin::successful_origin(), pool)?;
+    set_block_number::<T>(10);
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool)
+  verify {
+    assert_eq!(RewardPool::<T>::pool_states(&pool).status, PoolStatus::Active);
+  }
+
+  close_pool {
+    let pool = T::staking_pool();
+    seed_pool::<T>(pool, 1)?;
+    set_block_number::<T>(10);
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool)
+  verify {
+    assert_eq!(RewardPool::<T>::pool_states(&pool).status, PoolStatus::Closed);
+  }
+
+  emergency_withdraw {
+    let pool = T::staking_pool();
+    <EmergencyWithdrawEnabled<T>>::insert(pool, true);
+    let caller = staked_caller::<T>(DOLLARS)?;
+    RewardPool::<T>::lock(RawOrigin::Signed(caller.clone()).into(), pool, DOLLARS, T::lock_period())?;
+  }: _(RawOrigin::Signed(caller.clone()), pool)
+  verify {
+    assert!(<RewardPool<T> as RewardPoolOps<_, _, _, _, _, _>>::get_account_shares(&caller, &pool).is_zero());
+  }
+
+  set_emergency_withdraw {
+    let pool = T::staking_pool();
+    let origin = T::UpdateOrigin::successful_origin();
+  }: _<T::Origin>(origin, pool, true)
+  verify {
+    assert!(RewardPool::<T>::emergency_withdraw_enabled(&pool));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::mock::{ExtBuilder, TestRuntime};
+  use frame_support::assert_ok;
+
+  #[test]
+  fn test_stake() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_stake::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_unstake() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_unstake::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_claim() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_claim::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_claim_all() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_claim_all::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_lock() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_lock::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_release_locks() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_release_locks::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_withdraw_unbonded() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_withdraw_unbonded::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_set_unbonding_period() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_set_unbonding_period::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_vest() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_vest::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_forfeit_vesting() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_forfeit_vesting::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_set_vesting_config() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_set_vesting_config::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_create_pool() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_create_pool::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_pause_pool() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_pause_pool::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_resume_pool() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_resume_pool::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_close_pool() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_close_pool::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_emergency_withdraw() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_emergency_withdraw::<TestRuntime>());
+    });
+  }
+
+  #[test]
+  fn test_set_emergency_withdraw() {
+    ExtBuilder::default().build().execute_with(|| {
+      assert_ok!(test_benchmark_set_emergency_withdraw::<TestRuntime>());
+    });
+  }
+}
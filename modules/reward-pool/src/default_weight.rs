@@ -0,0 +1,97 @@
+//! Default weights of the reward pool extrinsics, estimated from the storage access of each call.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(clippy::unnecessary_cast)]
+
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+
+impl crate::WeightInfo for () {
+	fn stake() -> Weight {
+		(120_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+	}
+	fn unstake() -> Weight {
+		(150_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(7 as Weight))
+			.saturating_add(DbWeight::get().writes(6 as Weight))
+	}
+	fn claim() -> Weight {
+		(110_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(5 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+	}
+	fn lock() -> Weight {
+		(130_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+	}
+	fn release_locks() -> Weight {
+		(110_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().writes(6 as Weight))
+	}
+	fn withdraw_unbonded(c: u32, ) -> Weight {
+		(100_000_000 as Weight)
+			.saturating_add((30_000_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+			.saturating_add(DbWeight::get().writes((2 as Weight).saturating_mul(c as Weight)))
+	}
+	fn set_unbonding_period() -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn vest() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(3 as Weight))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+	}
+	fn forfeit_vesting() -> Weight {
+		(120_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().writes(5 as Weight))
+	}
+	fn set_vesting_config() -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn create_pool() -> Weight {
+		(25_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(2 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn pause_pool() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+	}
+	fn resume_pool() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+	}
+	fn close_pool() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(4 as Weight))
+			.saturating_add(DbWeight::get().writes(3 as Weight))
+	}
+	fn emergency_withdraw() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(5 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+	fn set_emergency_withdraw() -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	fn claim_all(p: u32, ) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((110_000_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((5 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(DbWeight::get().writes((5 as Weight).saturating_mul(p as Weight)))
+	}
+}
@@ -0,0 +1,265 @@
+//! Randomized tests of the reward pool math
+//!
+//! Random interleavings of add/remove/claim of many accounts are run against a reference model,
+//! which distributes the rewards of each block range pro rata to the shares and rounds down.
+//! The rewards of an account (paid + pending + dust) should only drift from the model by the rounding
+//! of the accumulated reward per share, any error returned by the module is a failure.
+//!
+//! The seeds and rounds could be overridden by `REWARD_POOL_FUZZ_SEED` (hex) and `REWARD_POOL_FUZZ_ROUNDS`,
+//! the seed of a failed case is in the failure message.
+
+#![cfg(test)]
+
+use super::*;
+use mock::{
+  Currencies, PoolId, RewardPoolModule, System,
+  run_to_block,
+  ExtBuilder,
+};
+use primitives::{AccountId, currency::*};
+
+use RewardPoolModule as RPM;
+
+const ACCOUNTS: u8 = 8;
+const DEFAULT_SEEDS: [u64; 4] = [0x2545_f491_4f6c_dd1d, 0x9e37_79b9_7f4a_7c15, 42, 7];
+const DEFAULT_ROUNDS: usize = 300;
+// the shares of an add are up to 10^MAX_SHARE_EXP, the boundary of the precision of the accumulator
+const MAX_SHARE_EXP: u32 = 22;
+
+/// xorshift64*, so the failures could be reproduced by the seed
+struct Rng(u64);
+
+impl Rng {
+  fn new(seed: u64) -> Self {
+    Rng(seed.max(1))
+  }
+
+  fn next(&mut self) -> u64 {
+    self.0 ^= self.0 >> 12;
+    self.0 ^= self.0 << 25;
+    self.0 ^= self.0 >> 27;
+    self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+  }
+
+  fn below(&mut self, n: u64) -> u64 {
+    self.next() % n
+  }
+
+  fn below_u128(&mut self, n: u128) -> u128 {
+    (((self.next() as u128) << 64) | self.next() as u128) % n
+  }
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+  Add(u8, Share),
+  Remove(u8, Share),
+  Claim(u8),
+  Advance(u64),
+}
+
+impl Op {
+  fn random(rng: &mut Rng, model: &Model) -> Op {
+    let index = rng.below(ACCOUNTS as u64) as u8;
+    match rng.below(8) {
+      0..=2 => {
+        let exp = rng.below(MAX_SHARE_EXP as u64 + 1) as u32;
+        Op::Add(index, rng.below_u128(10u128.pow(exp)) + 1)
+      },
+      3 | 4 => {
+        let shares = model.shares.get(&index).cloned().unwrap_or_default();
+        match rng.below(3) {
+          // remove all the shares to reset the account
+          0 => Op::Remove(index, shares),
+          _ if shares.is_zero() => Op::Remove(index, 0),
+          _ => Op::Remove(index, rng.below_u128(shares) + 1),
+        }
+      },
+      5 => Op::Claim(index),
+      _ => Op::Advance(rng.below(5) + 1),
+    }
+  }
+}
+
+/// the reference model, the rewards of each update are split pro rata and rounded down
+#[derive(Default)]
+struct Model {
+  shares: BTreeMap<u8, Share>,
+  earned: BTreeMap<u8, Balance>,
+  total_shares: Share,
+  // the max shares of an account, which bounds the rounding of the accumulator
+  max_shares: Share,
+  last_update_block: u64,
+  minted: Balance,
+  // the number of updates with rewards, each of them could cause some rounding
+  updates: u128,
+}
+
+impl Model {
+  fn update(&mut self, now: u64) {
+    if now <= self.last_update_block {
+      return;
+    }
+    let blocks = now - self.last_update_block;
+    self.last_update_block = now;
+    if self.total_shares.is_zero() {
+      return;
+    }
+
+    let reward = DOLLARS * blocks as u128;
+    self.minted += reward;
+    self.updates += 1;
+    for (index, shares) in self.shares.iter() {
+      let earned = U256::from(reward) * U256::from(*shares) / U256::from(self.total_shares);
+      *self.earned.entry(*index).or_default() += earned.as_u128();
+    }
+  }
+
+  fn add(&mut self, index: u8, amount: Share) {
+    let shares = self.shares.entry(index).or_default();
+    *shares += amount;
+    self.max_shares = self.max_shares.max(*shares);
+    self.total_shares += amount;
+  }
+
+  fn remove(&mut self, index: u8, amount: Share) {
+    *self.shares.entry(index).or_default() -= amount;
+    self.total_shares -= amount;
+  }
+}
+
+fn account(index: u8) -> AccountId {
+  // fresh accounts without any balance, so the balance is the paid rewards
+  AccountId::from([100 + index; 32])
+}
+
+/// paid + pending + dust rewards of the account
+fn actual_rewards(index: u8, pool_id: &PoolId) -> Balance {
+  let who = account(index);
+  let pending: Balance = RPM::get_accumlated_rewards(&who, pool_id).into_iter()
+    .filter(|(currency_id, _)| *currency_id == CurrencyId::CLV)
+    .map(|(_, reward)| reward)
+    .sum();
+  Currencies::total_balance(CurrencyId::CLV, &who) + pending
+}
+
+fn fail(seed: u64, e: &dyn core::fmt::Debug, history: &[(u64, Op)]) -> ! {
+  panic!("seed: {:#x}, error: {:?}, ops (block, op): {:?}", seed, e, history)
+}
+
+fn run_case(seed: u64, rounds: usize) {
+  let pool_id = PoolId::Swap(0);
+  let mut rng = Rng::new(seed);
+  let mut model = Model::default();
+  let mut history: Vec<(u64, Op)> = vec![];
+
+  ExtBuilder::default().build().execute_with(|| {
+    run_to_block(1);
+    model.last_update_block = 1;
+
+    for _ in 0..rounds {
+      let op = Op::random(&mut rng, &model);
+      let now = System::block_number();
+      history.push((now, op.clone()));
+
+      match op {
+        Op::Add(index, amount) => {
+          model.update(now);
+          if let Err(e) = RPM::add_share(&account(index), pool_id, amount) {
+            fail(seed, &e, &history);
+          }
+          model.add(index, amount);
+        },
+        Op::Remove(index, amount) => {
+          model.update(now);
+          let result = RPM::remove_share(&account(index), pool_id, amount);
+          if amount.is_zero() {
+            assert!(result.is_err(), "seed: {:#x}, removing 0 shares should fail", seed);
+          } else if let Err(e) = result {
+            fail(seed, &e, &history);
+          } else {
+            model.remove(index, amount);
+          }
+        },
+        Op::Claim(index) => {
+          model.update(now);
+          if let Err(e) = RPM::claim_rewards(&account(index), &pool_id) {
+            fail(seed, &e, &history);
+          }
+        },
+        Op::Advance(blocks) => run_to_block(now + blocks),
+      }
+
+      let violations = RPM::check_invariants();
+      if !violations.is_empty() {
+        fail(seed, &violations, &history);
+      }
+    }
+
+    // settle the rewards up to now without changing the storage
+    model.update(System::block_number());
+    // the accumulator loses less than shares / precision for each update,
+    // the model loses less than 1 for each update
+    let tolerance = model.updates * (model.max_shares / ACC_REWARD_PRECISION + 2);
+    let mut distributed: Balance = 0;
+    for index in 0..ACCOUNTS {
+      let expected = model.earned.get(&index).cloned().unwrap_or_default();
+      let actual = actual_rewards(index, &pool_id);
+      let drift = if actual > expected { actual - expected } else { expected - actual };
+      assert!(drift <= tolerance,
+        "seed: {:#x}, account: {}, expected: {}, actual: {}, drift: {} > {}, ops (block, op): {:?}",
+        seed, index, expected, actual, drift, tolerance, history);
+      distributed += actual;
+    }
+
+    // the pool never pays more than the rewards it has accrued
+    assert!(distributed <= model.minted,
+      "seed: {:#x}, distributed: {} > minted: {}", seed, distributed, model.minted);
+  });
+}
+
+#[test]
+fn fuzz_add_remove_claim() {
+  let rounds = std::env::var("REWARD_POOL_FUZZ_ROUNDS").ok()
+    .and_then(|rounds| rounds.parse().ok())
+    .unwrap_or(DEFAULT_ROUNDS);
+  let seeds = match std::env::var("REWARD_POOL_FUZZ_SEED").ok()
+    .and_then(|seed| u64::from_str_radix(seed.trim_start_matches("0x"), 16).ok()) {
+    Some(seed) => vec![seed],
+    None => DEFAULT_SEEDS.to_vec(),
+  };
+
+  for seed in seeds {
+    run_case(seed, rounds);
+  }
+}
+
+#[test]
+fn fuzz_calc_reward_by_shares() {
+  let precision = U256::from(ACC_REWARD_PRECISION);
+  let mut rng = Rng::new(DEFAULT_SEEDS[0]);
+
+  for _ in 0..10_000 {
+    let shares = rng.below_u128(10u128.pow(rng.below(MAX_SHARE_EXP as u64 + 1) as u32)) + 1;
+    let reward = rng.below_u128(1_000_000 * DOLLARS) + 1;
+    let total_shares = shares + rng.below_u128(10u128.pow(MAX_SHARE_EXP));
+    let acc_reward_per_share = RPM::calc_reward_per_share(reward, total_shares)
+      .unwrap_or_else(|e| panic!("reward: {}, total shares: {}, error: {:?}", reward, total_shares, e));
+    let (paid, remainder) = RPM::calc_reward_by_shares(&shares, &acc_reward_per_share, &U256::zero())
+      .unwrap_or_else(|e| panic!("shares: {}, acc: {}, error: {:?}", shares, acc_reward_per_share, e));
+
+    // paid + remainder is exactly the scaled rewards of the shares
+    assert_eq!(U256::from(paid) * precision + remainder, U256::from(shares) * acc_reward_per_share);
+    assert!(remainder < precision);
+
+    // never more than the exact share of the reward, and less than shares / precision + 1 below it
+    let exact = (U256::from(reward) * U256::from(shares) / U256::from(total_shares)).as_u128();
+    assert!(paid <= exact, "shares: {}, total: {}, reward: {}, paid: {} > {}", shares, total_shares, reward, paid, exact);
+    assert!(exact - paid <= shares / ACC_REWARD_PRECISION + 1,
+      "shares: {}, total: {}, reward: {}, drift: {}", shares, total_shares, reward, exact - paid);
+
+    // the reward debt keeps the remainder, nothing is paid twice
+    let reward_debt = RPM::calc_reward_debt(&shares, &acc_reward_per_share, remainder).unwrap();
+    assert_eq!(RPM::calc_reward_by_shares(&shares, &acc_reward_per_share, &reward_debt).unwrap(), (0, remainder));
+  }
+}
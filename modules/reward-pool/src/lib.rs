@@ -2,20 +2,64 @@
 //!
 //! ##Overview
 //! Reward pooling based on shares,
-//! Add shares to the pool, receive native currency reward
-//! Allow add shares, withdraw shares and coressponding native currency
+//! Add shares to the pool, receive rewards in one or more reward currencies
+//! Allow add shares, withdraw shares and coressponding reward currencies
+//!
+//! Rewards are distributed with a global accumulated reward per share for each reward currency,
+//! scaled by `ACC_REWARD_PRECISION`, and a reward debt for each account.
+//! The pending rewards of an account are `shares * acc_reward_per_share - reward_debt`,
+//! the rounding is always in favor of the pool so the paid rewards never exceed the accrued rewards,
+//! and the sub-unit remainder of an account is kept in its reward debt instead of being dropped.
+//! Settled rewards which are less than `ExistentialReward` are kept as the dust of the account,
+//! and they're sent with the rewards of the next claim or removal,
+//! the dust of an account without shares is sent by the next claim regardless of the existential reward.
+//!
+//! Shares could be locked for a period supported by the pool in exchange for a reward multiplier,
+//! the extra boost shares only accrue rewards and are removed once the lock is expired.
+//!
+//! A pool could have an unbonding period, the removed shares stop accruing rewards immediately,
+//! but the shares and their settled rewards are only released by `withdraw_unbonded` after the period.
+//!
+//! Pools could be created explicitly with a reward schedule, the rewards are only accrued between
+//! the start and end block while the pool is active. Paused pools freeze the accrual, closed pools
+//! stop it permanently, the accounts could still remove their shares and claim the rewards.
+//! Pools which are not created explicitly are active without a schedule.
+//!
+//! Emergency withdraw could be enabled for a staking pool by the governance, it returns all the shares
+//! of the account immediately without computing the rewards, the unclaimed rewards are forfeited
+//! and redistributed to the shares in the pool.
+//!
+//! A pool could route a fraction of the paid rewards into a linear vesting schedule of the account,
+//! the vesting rewards are kept in the pool account until they're vested, or paid at once by
+//! forfeiting a penalty which is redistributed to the shares in the pool.
+//!
+//! The rewards of several sources could be summed by a tuple of reward handlers,
+//! and `CappedRewards` limits the emission of a handler for each pool and reward currency in an era.
+//!
+//! `check_invariants` verifies the accounting of all the pools, it's exposed to the clients by the runtime api
+//! so the pools could be checked against the state after the runtime upgrades.
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode, FullCodec, HasCompact};
 use frame_support::{
-  decl_error, decl_event, decl_module, decl_storage, Parameter,
-  debug,
-  traits::{Get},
+  IterableStorageDoubleMap, IterableStorageMap,
+  decl_error, decl_event, decl_module, decl_storage, ensure, Parameter,
+  debug, transactional,
+  storage::{with_transaction, TransactionOutcome},
+  traits::{EnsureOrigin, Get},
+  weights::Weight,
 };
+use sp_core::U256;
+use frame_system::ensure_signed;
+use orml_traits::{MultiCurrency, MultiCurrencyExtended, MultiReservableCurrency};
+use orml_utilities::with_transaction_result;
 use sp_runtime::{
   traits::{
     AccountIdConversion,
+    Convert,
     Member,
+    One,
+    Saturating,
     UniqueSaturatedInto,
     Zero,
   },
@@ -25,19 +69,61 @@ use sp_runtime::{
 };
 
 use sp_std::{
-  cmp::{Eq, PartialEq},
+  cmp::{self, Eq, PartialEq},
+  collections::btree_map::BTreeMap,
+  convert::TryFrom,
+  marker::PhantomData,
+  prelude::*,
 };
 use sp_std::vec;
 
-use primitives::{Balance, CurrencyId, Price, Share, Ratio};
+use primitives::{Balance, CurrencyId, Ratio, Share};
 
 pub mod traits;
 
-use traits::RewardHandler;
+use traits::{EmissionCap, LockScheduleProvider, RewardHandler};
 use clover_traits::RewardPoolOps;
 
+mod default_weight;
+#[cfg(feature = "runtime-benchmarks")]
+pub mod benchmarking;
+mod migrations;
 mod mock;
 mod tests;
+mod fuzz_tests;
+
+/// The precision of the accumulated reward per share
+pub const ACC_REWARD_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+pub trait WeightInfo {
+  fn stake() -> Weight;
+  fn unstake() -> Weight;
+  fn claim() -> Weight;
+  fn claim_all(p: u32) -> Weight;
+  fn lock() -> Weight;
+  fn release_locks() -> Weight;
+  fn withdraw_unbonded(c: u32) -> Weight;
+  fn set_unbonding_period() -> Weight;
+  fn vest() -> Weight;
+  fn forfeit_vesting() -> Weight;
+  fn set_vesting_config() -> Weight;
+  fn create_pool() -> Weight;
+  fn pause_pool() -> Weight;
+  fn resume_pool() -> Weight;
+  fn close_pool() -> Weight;
+  fn emergency_withdraw() -> Weight;
+  fn set_emergency_withdraw() -> Weight;
+}
+
+/// The reward info of a reward currency in the pool
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, Default)]
+pub struct PoolRewardInfo<Balance: HasCompact> {
+  /// Accumulated rewards per share, scaled by `ACC_REWARD_PRECISION`
+  pub acc_reward_per_share: U256,
+  /// Total rewards amount which can be withdrawn
+  #[codec(compact)]
+  pub total_rewards_useable: Balance,
+}
 
 /// The Reward Pool Info.
 #[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, Default)]
@@ -45,14 +131,9 @@ pub struct PoolInfo<Share: HasCompact, Balance: HasCompact + From<Share>, Block:
   /// Total shares amount
   #[codec(compact)]
   pub total_shares: Share,
-  /// Total rewards amount
-  /// including some "virtual" amount added while adding shares
-  #[codec(compact)]
-  pub total_rewards: Balance,
-  /// Total rewards amount which can be withdrawn
-  /// this is equals to total_rewards - virtual_rewards_amount
-  #[codec(compact)]
-  pub total_rewards_useable: Balance,
+
+  /// rewards of each reward currency in the pool
+  pub rewards: BTreeMap<CurrencyId, PoolRewardInfo<Balance>>,
 
   /// last reward grant block number
   #[codec[compact]]
@@ -61,11 +142,121 @@ pub struct PoolInfo<Share: HasCompact, Balance: HasCompact + From<Share>, Block:
 
 /// The Reward Pool balance info for an account
 #[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, Default)]
-pub struct PoolAccountInfo <Share: HasCompact, Balance: HasCompact> {
+pub struct PoolAccountInfo <Share: HasCompact> {
+  /// shares of the account, including the boost shares of the locks
+  #[codec(compact)]
+  pub shares: Share,
+  /// reward debt of each reward currency, scaled by `ACC_REWARD_PRECISION`
+  /// the rewards which are accumulated before the shares are added, or are already paid
+  pub reward_debt: BTreeMap<CurrencyId, U256>,
+}
+
+/// Shares locked in the pool for a period in exchange for a reward multiplier
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, Default)]
+pub struct ShareLock<Share: HasCompact, Block: HasCompact> {
+  /// locked shares, which can't be removed before the unlock block
+  #[codec(compact)]
+  pub shares: Share,
+  /// extra shares granted by the multiplier, which only accrue rewards
+  #[codec(compact)]
+  pub boost_shares: Share,
+  /// the shares are unlocked at this block
+  #[codec(compact)]
+  pub unlock_block: Block,
+}
+
+/// Shares removed from the pool which are waiting for the unbonding period
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, Default)]
+pub struct UnbondingChunk<Share: HasCompact, Balance: HasCompact, Block: HasCompact> {
+  /// removed shares, which don't accrue rewards any more
   #[codec(compact)]
   pub shares: Share,
+  /// rewards settled while removing the shares, which are paid with the shares
+  pub rewards: Vec<(CurrencyId, Balance)>,
+  /// the shares could be withdrawn at this block
   #[codec(compact)]
-  pub borrowed_amount: Balance, // borrow balances
+  pub unbonded_block: Block,
+}
+
+/// Status of the pool
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum PoolStatus {
+  /// rewards are accrued in the schedule of the pool
+  Active,
+  /// rewards accrual is frozen, the pool could be resumed later
+  Paused,
+  /// rewards accrual is stopped permanently, shares could only be removed
+  Closed,
+}
+
+impl Default for PoolStatus {
+  fn default() -> Self {
+    PoolStatus::Active
+  }
+}
+
+/// Status and reward schedule of the pool
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, Default)]
+pub struct PoolState<Block: HasCompact> {
+  pub status: PoolStatus,
+  /// rewards are accrued from this block
+  #[codec(compact)]
+  pub start_block: Block,
+  /// rewards are accrued until this block, no end if it's not set
+  pub end_block: Option<Block>,
+}
+
+/// Vesting configuration of the pool rewards
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, Default)]
+pub struct VestingConfig<Block: HasCompact> {
+  /// fraction of the paid rewards which are vested, the rest are liquid immediately
+  pub vesting_ratio: Ratio,
+  /// the vesting rewards are released linearly in the period
+  #[codec(compact)]
+  pub vesting_period: Block,
+  /// fraction of the vesting rewards which are forfeited for an instant payout
+  pub forfeit_penalty: Ratio,
+}
+
+/// Linear vesting schedule of a reward currency
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, Default)]
+pub struct VestingSchedule<Balance: HasCompact, Block: HasCompact> {
+  /// rewards which are not released yet
+  #[codec(compact)]
+  pub locked: Balance,
+  /// the locked rewards are released linearly from this block
+  #[codec(compact)]
+  pub start_block: Block,
+  /// all the locked rewards are released at this block
+  #[codec(compact)]
+  pub end_block: Block,
+}
+
+/// Violation of the pool accounting invariants
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum InvariantViolation<AccountId> {
+  /// the sum of the account shares is not the total shares of the pool
+  TotalSharesMismatch,
+  /// the pool account balance is less than the rewards it should hold,
+  /// including the useable, vesting, unbonding and dust rewards
+  InsufficientRewardBalance(CurrencyId),
+  /// the reward debt of the account exceeds its shares * accumulated reward per share
+  RewardDebtExceeded(AccountId, CurrencyId),
+}
+
+/// Storage version of the module
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+  /// rewards are tracked with virtual rewards and borrowed amount
+  V1_0_0,
+  /// rewards are tracked with accumulated reward per share and reward debt
+  V2_0_0,
+}
+
+impl Default for Releases {
+  fn default() -> Self {
+    Releases::V1_0_0
+  }
 }
 
 pub trait Trait: frame_system::Config{
@@ -77,26 +268,97 @@ pub trait Trait: frame_system::Config{
   /// The reward  module id, keep all assets in DEX sub account.
   type ModuleId: Get<ModuleId>;
 
-  type Handler: RewardHandler<Self::AccountId, Self::BlockNumber, Balance, Share, Self::PoolId>;
+  type Handler: RewardHandler<Self::AccountId, Self::BlockNumber, Balance, Share, Self::PoolId, CurrencyId>;
 
   /// Currency for transfer currencies
-  type Currency: MultiCurrencyExtended<Self::AccountId, CurrencyId = CurrencyId, Balance = Balance>;
+  type Currency: MultiCurrencyExtended<Self::AccountId, CurrencyId = CurrencyId, Balance = Balance>
+    + MultiReservableCurrency<Self::AccountId>;
 
   type GetNativeCurrencyId: Get<CurrencyId>;
 
   /// minimum amount that reward could be sent to account
   type ExistentialReward: Get<Balance>;
+
+  /// Lock periods and reward multipliers of the pools
+  type LockSchedule: LockScheduleProvider<Self::PoolId, Self::BlockNumber>;
+
+  /// Maximum number of locks of an account in a pool
+  type MaxShareLocks: Get<u32>;
+
+  /// Maximum number of unbonding chunks of an account in a pool
+  type MaxUnbondingChunks: Get<u32>;
+
+  /// Maximum number of pools which could be claimed by `claim_all`
+  type MaxClaimPools: Get<u32>;
+
+  /// The origin which may update the pool configurations
+  type UpdateOrigin: EnsureOrigin<Self::Origin>;
+
+  /// The currency which is staked by `stake` and `unstake` for the pool,
+  /// pools without a staking currency only receive shares from other modules
+  type StakingCurrency: Convert<Self::PoolId, Option<CurrencyId>>;
+
+  /// Weight information for the extrinsics in this module.
+  type WeightInfo: WeightInfo;
 }
 
 decl_event!(
   pub enum Event<T> where
     <T as frame_system::Config>::AccountId,
+    <T as frame_system::Config>::BlockNumber,
     <T as Trait>::PoolId,
+    CurrencyId = CurrencyId,
     Share = Share,
     Balance = Balance,
   {
-    RewardUpdated(PoolId, Balance),
-    ShareRemoved(PoolId, AccountId, Share),
+    /// Rewards accrued to the pool. \[pool_id, currency_id, amount, acc_reward_per_share\]
+    RewardAccrued(PoolId, CurrencyId, Balance, U256),
+    /// Shares added to the pool, including the boost shares.
+    /// \[account_id, pool_id, amount, account_shares, total_shares\]
+    ShareAdded(AccountId, PoolId, Share, Share, Share),
+    /// Shares removed from the pool, including the boost shares.
+    /// \[account_id, pool_id, amount, account_shares, total_shares\]
+    ShareRemoved(AccountId, PoolId, Share, Share, Share),
+    /// Currency staked into the pool. \[account_id, pool_id, amount\]
+    Staked(AccountId, PoolId, Balance),
+    /// Currency unstaked from the pool. \[account_id, pool_id, amount\]
+    Unstaked(AccountId, PoolId, Balance),
+    /// Rewards settled to the account by claiming or removing shares. \[account_id, pool_id, currency_id, amount\]
+    RewardClaimed(AccountId, PoolId, CurrencyId, Balance),
+    /// Shares locked in the pool. \[account_id, pool_id, shares, boost_shares, unlock_block\]
+    SharesLocked(AccountId, PoolId, Share, Share, BlockNumber),
+    /// Locked shares released after the lock period. \[account_id, pool_id, shares\]
+    SharesUnlocked(AccountId, PoolId, Share),
+    /// Removed shares start unbonding. \[account_id, pool_id, shares, unbonded_block\]
+    SharesUnbonding(AccountId, PoolId, Share, BlockNumber),
+    /// Unbonded shares withdrawn. \[account_id, pool_id, shares\]
+    SharesWithdrawn(AccountId, PoolId, Share),
+    /// Unbonding period of the pool updated. \[pool_id, unbonding_period\]
+    UnbondingPeriodUpdated(PoolId, BlockNumber),
+    /// Rewards added to the vesting schedule. \[account_id, pool_id, currency_id, amount\]
+    RewardVesting(AccountId, PoolId, CurrencyId, Balance),
+    /// Vested rewards released. \[account_id, pool_id, currency_id, amount\]
+    RewardVested(AccountId, PoolId, CurrencyId, Balance),
+    /// Vesting rewards paid with a penalty. \[account_id, pool_id, currency_id, amount, penalty\]
+    VestingForfeited(AccountId, PoolId, CurrencyId, Balance, Balance),
+    /// Vesting config of the pool updated. \[pool_id\]
+    VestingConfigUpdated(PoolId),
+    /// Pool created. \[pool_id, start_block, end_block\]
+    PoolCreated(PoolId, BlockNumber, Option<BlockNumber>),
+    /// Pool paused. \[pool_id\]
+    PoolPaused(PoolId),
+    /// Pool resumed. \[pool_id\]
+    PoolResumed(PoolId),
+    /// Pool closed. \[pool_id\]
+    PoolClosed(PoolId),
+    /// Shares withdrawn without rewards, including the unbonding shares. \[account_id, pool_id, shares\]
+    EmergencyWithdrawn(AccountId, PoolId, Share),
+    /// Unclaimed rewards forfeited by emergency withdraw. \[account_id, pool_id, currency_id, amount\]
+    EmergencyRewardForfeited(AccountId, PoolId, CurrencyId, Balance),
+    /// Emergency withdraw of the pool enabled or disabled. \[pool_id, enabled\]
+    EmergencyWithdrawUpdated(PoolId, bool),
+    /// Settled rewards kept as dust, which are too small to send. \[account_id, pool_id, currency_id, dust\]
+    DustAccumulated(AccountId, PoolId, CurrencyId, Balance),
   }
 );
 
@@ -108,6 +370,34 @@ decl_error! {
     InsufficientShares,
     InvalidAmount,
     InvalidRewards,
+    /// the pool doesn't accept staking
+    PoolNotStakeable,
+    /// staked currency can't be released
+    InsufficientStakedBalance,
+    /// the lock period is not supported by the pool
+    InvalidLockPeriod,
+    /// the shares are locked and can't be removed before the unlock block
+    SharesStillLocked,
+    /// the account has too many locks in the pool
+    TooManyLocks,
+    /// the account has too many unbonding chunks in the pool
+    TooManyUnbondingChunks,
+    /// the vesting ratio or penalty is larger than one, or the vesting period is zero
+    InvalidVestingConfig,
+    /// the pool is created or used already
+    PoolAlreadyExists,
+    /// the end block of the pool is not after the start block
+    InvalidPoolSchedule,
+    /// the pool status doesn't allow the operation
+    InvalidPoolStatus,
+    /// the pool is closed, no shares could be added
+    PoolClosed,
+    /// emergency withdraw is not enabled for the pool
+    EmergencyWithdrawDisabled,
+    /// the account has no expired locks in the pool
+    NoExpiredLocks,
+    /// more pools than `MaxClaimPools` are passed to `claim_all`
+    TooManyPools,
   }
 }
 
@@ -116,8 +406,38 @@ decl_storage! {
     /// reward pool info.
     pub Pools get(fn get_pool): map hasher(twox_64_concat) T::PoolId => PoolInfo<Share, Balance, T::BlockNumber>;
 
-    /// Record share amount and virtual amount in the account
-    pub PoolAccountData get(fn pool_account_data): double_map hasher(twox_64_concat) T::PoolId, hasher(twox_64_concat) T::AccountId => PoolAccountInfo<Share, Balance>;
+    /// Record share amount and reward debt in the account
+    pub PoolAccountData get(fn pool_account_data): double_map hasher(twox_64_concat) T::PoolId, hasher(twox_64_concat) T::AccountId => PoolAccountInfo<Share>;
+
+    /// Locked shares of the account in the pool
+    pub PoolAccountLocks get(fn pool_account_locks): double_map hasher(twox_64_concat) T::PoolId, hasher(twox_64_concat) T::AccountId => Vec<ShareLock<Share, T::BlockNumber>>;
+
+    /// Unbonding period of the pool, the removed shares are withdrawn immediately if it's zero
+    pub UnbondingPeriods get(fn unbonding_periods): map hasher(twox_64_concat) T::PoolId => T::BlockNumber;
+
+    /// Unbonding shares of the account in the pool
+    pub Unbondings get(fn unbondings): double_map hasher(twox_64_concat) T::PoolId, hasher(twox_64_concat) T::AccountId => Vec<UnbondingChunk<Share, Balance, T::BlockNumber>>;
+
+    /// Status and reward schedule of the pool
+    pub PoolStates get(fn pool_states): map hasher(twox_64_concat) T::PoolId => PoolState<T::BlockNumber>;
+
+    /// Settled rewards of the account which are too small to send
+    pub PendingDust get(fn pending_dust): double_map hasher(twox_64_concat) T::PoolId, hasher(twox_64_concat) T::AccountId => BTreeMap<CurrencyId, Balance>;
+
+    /// Pools which allow withdrawing shares without rewards
+    pub EmergencyWithdrawEnabled get(fn emergency_withdraw_enabled): map hasher(twox_64_concat) T::PoolId => bool;
+
+    /// Vesting config of the pool, the rewards are liquid immediately if it's not set
+    pub VestingConfigs get(fn vesting_configs): map hasher(twox_64_concat) T::PoolId => Option<VestingConfig<T::BlockNumber>>;
+
+    /// Vesting schedules of the account rewards in the pool
+    pub VestingSchedules get(fn vesting_schedules): double_map hasher(twox_64_concat) T::PoolId, hasher(twox_64_concat) T::AccountId => BTreeMap<CurrencyId, VestingSchedule<Balance, T::BlockNumber>>;
+
+    /// The era and the emission of the pool and reward currency in the era, recorded by `CappedRewards`
+    pub EraEmissions get(fn era_emissions): double_map hasher(twox_64_concat) T::PoolId, hasher(twox_64_concat) CurrencyId => (T::BlockNumber, Balance);
+
+    /// Storage version of the module, new networks start with the latest version
+    pub StorageVersion get(fn storage_version) build(|_| Releases::V2_0_0): Releases;
   }
 }
 
@@ -128,6 +448,225 @@ decl_module! {
 
     const GetNativeCurrencyId: CurrencyId = T::GetNativeCurrencyId::get();
     const ExistentialReward: Balance = T::ExistentialReward::get();
+    const MaxShareLocks: u32 = T::MaxShareLocks::get();
+    const MaxUnbondingChunks: u32 = T::MaxUnbondingChunks::get();
+    const MaxClaimPools: u32 = T::MaxClaimPools::get();
+
+    fn on_runtime_upgrade() -> Weight {
+      if Self::storage_version() == Releases::V1_0_0 {
+        migrations::migrate_to_v2::<T>()
+      } else {
+        0
+      }
+    }
+
+    /// Stake currency into the reward pool, the staked amount is reserved
+    /// and the account receives the same amount of shares.
+    #[weight = T::WeightInfo::stake()]
+    pub fn stake(origin, pool_id: T::PoolId, #[compact] amount: Balance) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_stake(&who, pool_id, amount)?;
+        Ok(())
+      })?;
+    }
+
+    /// Unstake currency from the reward pool,
+    /// the rewards of the removed shares are sent to the account.
+    #[weight = T::WeightInfo::unstake()]
+    pub fn unstake(origin, pool_id: T::PoolId, #[compact] amount: Balance) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_unstake(&who, pool_id, amount)?;
+        Ok(())
+      })?;
+    }
+
+    /// Claim all accumlated rewards of the pool.
+    #[weight = T::WeightInfo::claim()]
+    pub fn claim(origin, pool_id: T::PoolId) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::claim_rewards(&who, &pool_id)?;
+        Ok(())
+      })?;
+    }
+
+    /// Lock shares in the pool for `lock_period` blocks,
+    /// the locked shares get more rewards by the multiplier of the lock period.
+    #[weight = T::WeightInfo::lock()]
+    pub fn lock(origin, pool_id: T::PoolId, #[compact] amount: Share, lock_period: T::BlockNumber) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::lock_share(&who, pool_id, amount, lock_period)?;
+        Ok(())
+      })?;
+    }
+
+    /// Release the expired locks of the account in the pool, so its boost shares stop accruing rewards.
+    /// Anyone could release the expired locks of any account, the rewards are settled to the account.
+    #[weight = T::WeightInfo::release_locks()]
+    pub fn release_locks(origin, who: T::AccountId, pool_id: T::PoolId) {
+      ensure_signed(origin)?;
+      let now = <frame_system::Module<T>>::block_number();
+      ensure!(
+        Self::pool_account_locks(&pool_id, &who).iter().any(|lock| lock.unlock_block <= now),
+        Error::<T>::NoExpiredLocks,
+      );
+      with_transaction_result(|| Self::release_expired_locks(&who, pool_id))?;
+    }
+
+    /// Withdraw the unbonded shares and their rewards,
+    /// the staked currency of the shares is released as well.
+    #[weight = T::WeightInfo::withdraw_unbonded(T::MaxUnbondingChunks::get())]
+    pub fn withdraw_unbonded(origin, pool_id: T::PoolId) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        <Self as RewardPoolOps<_, _, _, _, _, _>>::withdraw_unbonded(&who, pool_id)?;
+        Ok(())
+      })?;
+    }
+
+    /// Set the unbonding period of the pool, the existing unbonding chunks are not affected.
+    #[weight = T::WeightInfo::set_unbonding_period()]
+    pub fn set_unbonding_period(origin, pool_id: T::PoolId, unbonding_period: T::BlockNumber) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      if unbonding_period.is_zero() {
+        <UnbondingPeriods<T>>::remove(pool_id);
+      } else {
+        <UnbondingPeriods<T>>::insert(pool_id, unbonding_period);
+      }
+      Self::deposit_event(RawEvent::UnbondingPeriodUpdated(pool_id, unbonding_period));
+    }
+
+    /// Release the vested rewards of the pool.
+    #[weight = T::WeightInfo::vest()]
+    pub fn vest(origin, pool_id: T::PoolId) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_release_vested(&who, pool_id)?;
+        Ok(())
+      })?;
+    }
+
+    /// Pay all the vesting rewards of the pool immediately,
+    /// the penalty of the pool is deducted and redistributed to the shares in the pool.
+    #[weight = T::WeightInfo::forfeit_vesting()]
+    pub fn forfeit_vesting(origin, pool_id: T::PoolId) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        Self::do_forfeit_vesting(&who, pool_id)?;
+        Ok(())
+      })?;
+    }
+
+    /// Set the vesting config of the pool, remove it by `None`.
+    /// The existing vesting schedules are not affected.
+    #[weight = T::WeightInfo::set_vesting_config()]
+    pub fn set_vesting_config(origin, pool_id: T::PoolId, config: Option<VestingConfig<T::BlockNumber>>) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      match config {
+        Some(config) => {
+          ensure!(config.vesting_ratio <= Ratio::one() && config.forfeit_penalty <= Ratio::one(),
+                  Error::<T>::InvalidVestingConfig);
+          ensure!(!config.vesting_period.is_zero(), Error::<T>::InvalidVestingConfig);
+          <VestingConfigs<T>>::insert(pool_id, config);
+        },
+        None => <VestingConfigs<T>>::remove(pool_id),
+      }
+      Self::deposit_event(RawEvent::VestingConfigUpdated(pool_id));
+    }
+
+    /// Create a pool which accrues rewards from `start_block` until `end_block`.
+    #[weight = T::WeightInfo::create_pool()]
+    pub fn create_pool(origin, pool_id: T::PoolId, start_block: T::BlockNumber, end_block: Option<T::BlockNumber>) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      ensure!(!<PoolStates<T>>::contains_key(pool_id) && !<Pools<T>>::contains_key(pool_id),
+              Error::<T>::PoolAlreadyExists);
+      if let Some(end_block) = end_block {
+        ensure!(start_block < end_block, Error::<T>::InvalidPoolSchedule);
+      }
+
+      <PoolStates<T>>::insert(pool_id, PoolState { status: PoolStatus::Active, start_block, end_block, });
+      Self::deposit_event(RawEvent::PoolCreated(pool_id, start_block, end_block));
+    }
+
+    /// Pause the rewards accrual of the pool.
+    #[weight = T::WeightInfo::pause_pool()]
+    pub fn pause_pool(origin, pool_id: T::PoolId) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      with_transaction_result(|| {
+        Self::set_pool_status(pool_id, PoolStatus::Active, PoolStatus::Paused)
+      })?;
+      Self::deposit_event(RawEvent::PoolPaused(pool_id));
+    }
+
+    /// Resume the rewards accrual of the paused pool, no rewards are accrued for the paused period.
+    #[weight = T::WeightInfo::resume_pool()]
+    pub fn resume_pool(origin, pool_id: T::PoolId) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      with_transaction_result(|| {
+        Self::set_pool_status(pool_id, PoolStatus::Paused, PoolStatus::Active)
+      })?;
+      Self::deposit_event(RawEvent::PoolResumed(pool_id));
+    }
+
+    /// Close the pool, the accrued rewards could still be claimed and the shares could still be removed.
+    #[weight = T::WeightInfo::close_pool()]
+    pub fn close_pool(origin, pool_id: T::PoolId) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      with_transaction_result(|| {
+        let status = Self::pool_states(pool_id).status;
+        ensure!(status != PoolStatus::Closed, Error::<T>::InvalidPoolStatus);
+        Self::set_pool_status(pool_id, status, PoolStatus::Closed)
+      })?;
+      Self::deposit_event(RawEvent::PoolClosed(pool_id));
+    }
+
+    /// Withdraw all the staked shares of the pool without computing the rewards, the locks and the
+    /// unbonding period of the shares are ignored and the unclaimed rewards are forfeited.
+    /// Only allowed for the staking pools if it's enabled for the pool.
+    #[weight = T::WeightInfo::emergency_withdraw()]
+    pub fn emergency_withdraw(origin, pool_id: T::PoolId) {
+      let who = ensure_signed(origin)?;
+      with_transaction_result(|| {
+        let shares = <Self as RewardPoolOps<_, _, _, _, _, _>>::emergency_withdraw(&who, pool_id)?;
+        if let Some(currency_id) = T::StakingCurrency::convert(pool_id) {
+          let remaining = T::Currency::unreserve(currency_id, &who, shares);
+          ensure!(remaining.is_zero(), Error::<T>::InsufficientStakedBalance);
+        }
+        Ok(())
+      })?;
+    }
+
+    /// Enable or disable emergency withdraw of the pool.
+    #[weight = T::WeightInfo::set_emergency_withdraw()]
+    pub fn set_emergency_withdraw(origin, pool_id: T::PoolId, enabled: bool) {
+      T::UpdateOrigin::ensure_origin(origin)?;
+      if enabled {
+        <EmergencyWithdrawEnabled<T>>::insert(pool_id, true);
+      } else {
+        <EmergencyWithdrawEnabled<T>>::remove(pool_id);
+      }
+      Self::deposit_event(RawEvent::EmergencyWithdrawUpdated(pool_id, enabled));
+    }
+
+    /// Claim accumlated rewards of the pools, the pools without shares of the account are skipped,
+    /// the weight is charged for each pool and there should be no more than `MaxClaimPools` pools.
+    #[weight = T::WeightInfo::claim_all(pool_ids.len() as u32)]
+    pub fn claim_all(origin, pool_ids: Vec<T::PoolId>) {
+      let who = ensure_signed(origin)?;
+      ensure!(pool_ids.len() as u32 <= T::MaxClaimPools::get(), Error::<T>::TooManyPools);
+      with_transaction_result(|| {
+        for pool_id in pool_ids {
+          let has_shares = !Self::get_pool_account_info(&pool_id, &who).shares.is_zero();
+          if has_shares || <PendingDust<T>>::contains_key(&pool_id, &who) {
+            Self::claim_rewards(&who, &pool_id)?;
+          }
+        }
+        Ok(())
+      })?;
+    }
   }
 }
 
@@ -136,21 +675,98 @@ impl<T: Trait> Module<T> {
     T::ModuleId::get().into_sub_account(pool_id)
   }
 
+  fn do_stake(who: &T::AccountId, pool_id: T::PoolId, amount: Balance) -> Result<Share, DispatchError> {
+    ensure!(!amount.is_zero(), Error::<T>::InvalidAmount);
+    let currency_id = T::StakingCurrency::convert(pool_id).ok_or(Error::<T>::PoolNotStakeable)?;
+
+    T::Currency::reserve(currency_id, who, amount)?;
+    let shares = Self::add_share(who, pool_id, amount)?;
+
+    Self::deposit_event(RawEvent::Staked(who.clone(), pool_id, amount));
+    Ok(shares)
+  }
+
+  fn do_unstake(who: &T::AccountId, pool_id: T::PoolId, amount: Balance) -> Result<Share, DispatchError> {
+    ensure!(!amount.is_zero(), Error::<T>::InvalidAmount);
+    let currency_id = T::StakingCurrency::convert(pool_id).ok_or(Error::<T>::PoolNotStakeable)?;
+
+    let shares = Self::remove_share(who, pool_id, amount)?;
+    // the staked currency is released by `withdraw_unbonded` if the pool has an unbonding period
+    if Self::unbonding_periods(pool_id).is_zero() {
+      let remaining = T::Currency::unreserve(currency_id, who, amount);
+      ensure!(remaining.is_zero(), Error::<T>::InsufficientStakedBalance);
+    }
+
+    Self::deposit_event(RawEvent::Unstaked(who.clone(), pool_id, amount));
+    Ok(shares)
+  }
+
   pub fn get_pool_info(pool_id: &T::PoolId) -> PoolInfo<Share, Balance, T::BlockNumber> {
     Self::get_pool(pool_id)
   }
 
-  pub fn get_pool_account_info(pool_id: &T::PoolId, account: &T::AccountId) -> PoolAccountInfo<Share, Balance> {
+  pub fn get_pool_account_info(pool_id: &T::PoolId, account: &T::AccountId) -> PoolAccountInfo<Share> {
     Self::pool_account_data(pool_id, account)
   }
 
+  /// check the accounting invariants of all the pools, returns the violations.
+  /// it iterates all the accounts of the pools, should not be called in the extrinsics
+  pub fn check_invariants() -> Vec<(T::PoolId, InvariantViolation<T::AccountId>)> {
+    let mut violations = vec![];
+    for (pool, pool_info) in <Pools<T>>::iter() {
+      let mut total_shares: Share = Zero::zero();
+      for (who, account_info) in <PoolAccountData<T>>::iter_prefix(&pool) {
+        total_shares = total_shares.saturating_add(account_info.shares);
+        for (currency_id, reward_debt) in account_info.reward_debt.iter() {
+          let acc_reward_per_share = pool_info.rewards.get(currency_id)
+            .map(|reward_info| reward_info.acc_reward_per_share)
+            .unwrap_or_default();
+          if *reward_debt > U256::from(account_info.shares).saturating_mul(acc_reward_per_share) {
+            violations.push((pool, InvariantViolation::RewardDebtExceeded(who.clone(), *currency_id)));
+          }
+        }
+      }
+      if total_shares != pool_info.total_shares {
+        violations.push((pool, InvariantViolation::TotalSharesMismatch));
+      }
+
+      // the settled rewards which are not sent yet are kept in the pool account as well
+      let mut held: BTreeMap<CurrencyId, Balance> = pool_info.rewards.iter()
+        .map(|(currency_id, reward_info)| (*currency_id, reward_info.total_rewards_useable))
+        .collect();
+      let mut hold = |currency_id: CurrencyId, amount: Balance| {
+        let total = held.entry(currency_id).or_default();
+        *total = total.saturating_add(amount);
+      };
+      for (_, schedules) in <VestingSchedules<T>>::iter_prefix(&pool) {
+        schedules.into_iter().for_each(|(currency_id, schedule)| hold(currency_id, schedule.locked));
+      }
+      for (_, chunks) in <Unbondings<T>>::iter_prefix(&pool) {
+        chunks.into_iter().flat_map(|chunk| chunk.rewards).for_each(|(currency_id, reward)| hold(currency_id, reward));
+      }
+      for (_, dust) in <PendingDust<T>>::iter_prefix(&pool) {
+        dust.into_iter().for_each(|(currency_id, amount)| hold(currency_id, amount));
+      }
+
+      let sub_account = Self::sub_account_id(pool);
+      for (currency_id, amount) in held {
+        if T::Currency::free_balance(currency_id, &sub_account) < amount {
+          violations.push((pool, InvariantViolation::InsufficientRewardBalance(currency_id)));
+        }
+      }
+    }
+    violations
+  }
+
+  /// settle the pending rewards of the account and remove the shares from the pool,
+  /// returns the new pool info, the new account info and the rewards to send to the account
   fn get_rewards_by_account_shares(
     pool_info: PoolInfo<Share, Balance, T::BlockNumber>,
-    account_info: PoolAccountInfo<Share, Balance>,
+    account_info: PoolAccountInfo<Share>,
     amount: Share) -> Result<
       (PoolInfo<Share, Balance, T::BlockNumber>,
-       PoolAccountInfo<Share, Balance>,
-       Balance),
+       PoolAccountInfo<Share>,
+       Vec<(CurrencyId, Balance)>),
     DispatchError> {
 
     // amount > 0 and user has sufficient shares to remove
@@ -158,97 +774,90 @@ impl<T: Trait> Module<T> {
       return Err(Error::<T>::InsufficientShares.into());
     }
 
-    // total rewards should send to account per shares including some amount 'borrowed'
-    let reward_with_virtual = Self::calc_reward_by_shares(&pool_info, &amount)?;
-
-    let PoolInfo { total_shares, total_rewards, total_rewards_useable, ..} = pool_info;
-    let PoolAccountInfo { shares, borrowed_amount } = account_info;
-
-    // remove the balance from reward pool account
-    let account_balance_to_remove = Ratio::checked_from_rational(amount, shares)
-      .and_then(|n| n.checked_mul_int(borrowed_amount))
-      .ok_or(Error::<T>::RewardCaculationError)?;
-
-    let new_balance = borrowed_amount.checked_sub(account_balance_to_remove)
-      .ok_or(Error::<T>::RewardCaculationError)?;
-
-    let reward = reward_with_virtual.checked_sub(account_balance_to_remove)
-      .ok_or(Error::<T>::RewardCaculationError)?;
-
-    // should not happen, but it's nice to have a check
-    if reward > total_rewards_useable || new_balance > borrowed_amount {
-      debug::error!("got wrong reward for account: {:?}, pool info: {:?}, shares: {:?}", account_info, pool_info, amount);
-      return Err(Error::<T>::RewardCaculationError.into());
-    }
-    let total_shares = total_shares.checked_sub(amount)
+    let mut new_pool_info = pool_info.clone();
+    let mut new_account_info = account_info.clone();
+    new_pool_info.total_shares = pool_info.total_shares.checked_sub(amount)
       .ok_or(Error::<T>::InsufficientShares)?;
-    let (reward, total_rewards, total_rewards_useable) = if reward <= T::ExistentialReward::get() {
-      debug::warn!("reward {:?} is less than existential reward, don't send the reward", reward);
-      (0, total_rewards, total_rewards_useable)
-    } else {
-      let rewards = total_rewards.checked_sub(reward_with_virtual)
-        .ok_or(Error::<T>::RewardCaculationError)?;
-      let rewards_useable = total_rewards_useable.checked_sub(reward)
-        .ok_or(Error::<T>::RewardCaculationError)?;
-      (reward, rewards, rewards_useable)
-    };
-
-    let pool_info = PoolInfo::<Share, Balance,  T::BlockNumber> {
-      total_rewards, total_rewards_useable, total_shares,
-      ..pool_info
-    };
-    let shares = shares.checked_sub(amount)
+    new_account_info.shares = account_info.shares.checked_sub(amount)
       .ok_or(Error::<T>::RewardCaculationError)?;
 
-    let account_info = PoolAccountInfo::<Share, Balance> {
-      shares, borrowed_amount: new_balance,
-      ..account_info
-    };
-
+    let mut rewards = vec![];
+    for (currency_id, reward_info) in new_pool_info.rewards.iter_mut() {
+      let acc_reward_per_share = reward_info.acc_reward_per_share;
+      let reward_debt = account_info.reward_debt.get(currency_id).cloned().unwrap_or_default();
+      let (reward, remainder) = Self::calc_reward_by_shares(&account_info.shares, &acc_reward_per_share, &reward_debt)?;
 
-    Ok((pool_info, account_info, reward))
-  }
+      // should not happen, but it's nice to have a check
+      if reward > reward_info.total_rewards_useable {
+        debug::error!("got wrong reward of {:?} for account: {:?}, pool info: {:?}, shares: {:?}", currency_id, account_info, pool_info, amount);
+        return Err(Error::<T>::RewardCaculationError.into());
+      }
 
-  // returns (actual_reward, total_rewards_in_pool, total_rewards_useable_in_pool)
-  fn calc_reward_by_shares(pool_info: &PoolInfo<Share, Balance, T::BlockNumber>,
-                           amount: &Share) -> Result<Balance, DispatchError> {
-    let PoolInfo { total_shares, total_rewards, ..} = pool_info;
+      if !reward.is_zero() {
+        reward_info.total_rewards_useable = reward_info.total_rewards_useable.checked_sub(reward)
+          .ok_or(Error::<T>::RewardCaculationError)?;
+        rewards.push((*currency_id, reward));
+      }
 
-    if total_shares.is_zero() || amount.is_zero() {
-      return Ok(Zero::zero());
+      let reward_debt = Self::calc_reward_debt(&new_account_info.shares, &acc_reward_per_share, remainder)?;
+      if reward_debt.is_zero() {
+        new_account_info.reward_debt.remove(currency_id);
+      } else {
+        new_account_info.reward_debt.insert(*currency_id, reward_debt);
+      }
     }
 
-    // should not happen
-    if amount > total_shares {
-      return Err(Error::<T>::InsufficientShares.into());
-    }
+    Ok((new_pool_info, new_account_info, rewards))
+  }
 
-    // total rewards should send to account per shares including some amount 'borrowed'
-    let reward_with_virtual = Ratio::checked_from_rational::<Balance, _>(amount.clone().into(), total_shares.clone())
-      .and_then(|n| n.checked_mul_int(total_rewards.clone()))
+  /// returns the pending reward of the shares and the scaled remainder which is less than 1 unit
+  fn calc_reward_by_shares(shares: &Share,
+                           acc_reward_per_share: &U256,
+                           reward_debt: &U256) -> Result<(Balance, U256), DispatchError> {
+    let accumulated = U256::from(*shares).checked_mul(*acc_reward_per_share)
       .ok_or(Error::<T>::RewardCaculationError)?;
+    // the debt could be slightly greater than the accumulated rewards after the shares are removed
+    let pending = accumulated.saturating_sub(*reward_debt);
+    let precision = U256::from(ACC_REWARD_PRECISION);
 
+    let reward = Balance::try_from(pending / precision)
+      .map_err(|_| Error::<T>::RewardCaculationError)?;
 
-    // should not happen, but it's nice to have a check
-    if &reward_with_virtual > total_rewards {
-      debug::error!("got wrong reward for pool info: {:?}, shares: {:?}", pool_info, amount);
-      return Err(Error::<T>::RewardCaculationError.into());
-    }
+    Ok((reward, pending % precision))
+  }
 
-    Ok(reward_with_virtual.clone())
+  /// returns the reward debt of the shares, the remainder is the scaled reward which is not paid yet
+  fn calc_reward_debt(shares: &Share,
+                      acc_reward_per_share: &U256,
+                      remainder: U256) -> Result<U256, DispatchError> {
+    let accumulated = U256::from(*shares).checked_mul(*acc_reward_per_share)
+      .ok_or(Error::<T>::RewardCaculationError)?;
+    Ok(accumulated.saturating_sub(remainder))
+  }
+
+  /// returns the scaled reward per share, rounded down
+  fn calc_reward_per_share(reward: Balance, total_shares: Share) -> Result<U256, DispatchError> {
+    U256::from(reward).checked_mul(U256::from(ACC_REWARD_PRECISION))
+      .and_then(|r| r.checked_div(U256::from(total_shares)))
+      .ok_or(Error::<T>::RewardCaculationError.into())
   }
 
   /// update the pool reward and releated storage
   fn update_pool_reward(pool: &T::PoolId,)
                         -> Result<PoolInfo<Share, Balance, T::BlockNumber>, DispatchError> {
-    let (pool_info, balance_change) = Self::calc_pool_reward(pool)?;
+    let (pool_info, balance_changes) = Self::calc_pool_reward(pool)?;
 
-    if !balance_change.is_zero() {
-      let sub_account = Self::sub_account_id(pool.clone());
-      debug::info!("updating reward pool {:?}, account {:?} balance by: {:?}", pool, sub_account, balance_change);
+    let sub_account = Self::sub_account_id(pool.clone());
+    for (currency_id, balance_change) in balance_changes {
+      debug::info!("updating reward pool {:?}, account {:?} balance of {:?} by: {:?}", pool, sub_account, currency_id, balance_change);
 
       let amount = balance_change.unique_saturated_into();
-      T::Currency::update_balance(T::GetNativeCurrencyId::get(), &sub_account, amount)?;
+      T::Currency::update_balance(currency_id, &sub_account, amount)?;
+
+      let acc_reward_per_share = pool_info.rewards.get(&currency_id)
+        .map(|reward_info| reward_info.acc_reward_per_share)
+        .unwrap_or_default();
+      Self::deposit_event(RawEvent::RewardAccrued(*pool, currency_id, balance_change, acc_reward_per_share));
     }
     <Pools<T>>::mutate(pool, |info| {
       *info = pool_info.clone();
@@ -257,10 +866,21 @@ impl<T: Trait> Module<T> {
     Ok(pool_info)
  }
 
+  /// settle the rewards of the pool with the current status, then update the status
+  fn set_pool_status(pool: T::PoolId, from: PoolStatus, to: PoolStatus) -> DispatchResult {
+    let mut state = Self::pool_states(pool);
+    ensure!(state.status == from, Error::<T>::InvalidPoolStatus);
+
+    Self::update_pool_reward(&pool)?;
+    state.status = to;
+    <PoolStates<T>>::insert(pool, state);
+    Ok(())
+  }
+
   /// update the pool reward at the specified block height
   fn calc_pool_reward(
     pool: &T::PoolId,
-  ) -> Result<(PoolInfo<Share, Balance, T::BlockNumber>, Balance), DispatchError> {
+  ) -> Result<(PoolInfo<Share, Balance, T::BlockNumber>, Vec<(CurrencyId, Balance)>), DispatchError> {
     let pool_info = Self::get_pool(pool);
     let cur_block = <frame_system::Module<T>>::block_number();
     Self::calc_pool_reward_at_block(pool, &pool_info, &cur_block)
@@ -270,85 +890,152 @@ impl<T: Trait> Module<T> {
     pool: &T::PoolId,
     pool_info: &PoolInfo<Share, Balance, T::BlockNumber>,
     cur_block: &T::BlockNumber
-  ) -> Result<(PoolInfo<Share, Balance, T::BlockNumber>, Balance), DispatchError> {
+  ) -> Result<(PoolInfo<Share, Balance, T::BlockNumber>, Vec<(CurrencyId, Balance)>), DispatchError> {
     let last_update_block  = pool_info.last_update_block;
     if cur_block <= &last_update_block {
       debug::info!("ignore update pool reward: {:?} at block: {:?}, already updated at: {:?}", pool, cur_block, last_update_block);
 
-      return Ok((pool_info.clone(), 0));
+      return Ok((pool_info.clone(), vec![]));
     }
 
-    let reward = T::Handler::caculate_reward(pool, &pool_info.total_shares, last_update_block, cur_block.clone());
-
     let mut new_info = pool_info.clone();
     new_info.last_update_block = cur_block.clone();
 
-    // reward is zero, this is a valid case
-    // it's not necessary to update the storage in this case
-    if reward == 0 {
-      debug::warn!("0 reward: {:?}, pool: {:?}, between {:?} - {:?}", reward, pool, last_update_block, cur_block);
-      return Ok((new_info, 0));
+    // no one could get the rewards if there is no shares in the pool
+    if new_info.total_shares.is_zero() {
+      debug::info!("ignore rewards of pool: {:?} with no shares, between {:?} - {:?}", pool, last_update_block, cur_block);
+      return Ok((new_info, vec![]));
     }
 
-    new_info.total_rewards = new_info.total_rewards.checked_add(reward).ok_or(Error::<T>::RewardCaculationError)?;
-    new_info.total_rewards_useable = new_info.total_rewards_useable.checked_add(reward).ok_or(Error::<T>::RewardCaculationError)?;
+    // rewards are only accrued in the schedule while the pool is active
+    let state = Self::pool_states(pool);
+    if state.status != PoolStatus::Active {
+      debug::info!("ignore rewards of {:?} pool: {:?}, between {:?} - {:?}", state.status, pool, last_update_block, cur_block);
+      return Ok((new_info, vec![]));
+    }
+    let from = cmp::max(last_update_block, state.start_block);
+    let to = state.end_block.map_or(*cur_block, |end_block| cmp::min(end_block, *cur_block));
+    if to <= from {
+      return Ok((new_info, vec![]));
+    }
+
+    let rewards = T::Handler::caculate_reward(pool, &pool_info.total_shares, from, to);
 
-    Ok((new_info, reward))
+    let mut balance_changes = vec![];
+    for (currency_id, reward) in rewards {
+      // reward is zero, this is a valid case
+      // it's not necessary to update the storage in this case
+      if reward.is_zero() {
+        continue;
+      }
+
+      let reward_per_share = Self::calc_reward_per_share(reward, new_info.total_shares)?;
+      let reward_info = new_info.rewards.entry(currency_id).or_default();
+      reward_info.acc_reward_per_share = reward_info.acc_reward_per_share.checked_add(reward_per_share).ok_or(Error::<T>::RewardCaculationError)?;
+      reward_info.total_rewards_useable = reward_info.total_rewards_useable.checked_add(reward).ok_or(Error::<T>::RewardCaculationError)?;
+      balance_changes.push((currency_id, reward));
+    }
+
+    if balance_changes.is_empty() {
+      debug::warn!("0 reward, pool: {:?}, between {:?} - {:?}", pool, last_update_block, cur_block);
+    }
+
+    Ok((new_info, balance_changes))
+  }
+
+  /// shares of the account without the boost shares of the locks
+  fn staked_shares(account_info: &PoolAccountInfo<Share>,
+                   locks: &[ShareLock<Share, T::BlockNumber>]) -> Share {
+    let boost_shares = locks.iter().fold(Zero::zero(), |acc: Share, lock| acc.saturating_add(lock.boost_shares));
+    account_info.shares.saturating_sub(boost_shares)
+  }
+
+  /// shares of the account which are not locked
+  fn free_shares(account_info: &PoolAccountInfo<Share>,
+                 locks: &[ShareLock<Share, T::BlockNumber>]) -> Share {
+    let locked_shares = locks.iter().fold(Zero::zero(), |acc: Share, lock| acc.saturating_add(lock.shares));
+    Self::staked_shares(account_info, locks).saturating_sub(locked_shares)
+  }
+
+  /// release the expired locks of the account, the boost shares are removed from the pool
+  /// note: the boost shares keep accruing rewards until the locks are released,
+  /// which happens on every share change or claim of the account, or by `release_locks` of anyone
+  fn release_expired_locks(who: &T::AccountId, pool: T::PoolId) -> DispatchResult {
+    let now = <frame_system::Module<T>>::block_number();
+    let locks = Self::pool_account_locks(&pool, who);
+    if locks.iter().all(|lock| lock.unlock_block > now) {
+      return Ok(());
+    }
+
+    let (expired, locks): (Vec<_>, Vec<_>) = locks.into_iter().partition(|lock| lock.unlock_block <= now);
+    let boost_shares = expired.iter().fold(Zero::zero(), |acc: Share, lock| acc.saturating_add(lock.boost_shares));
+    if !boost_shares.is_zero() {
+      let rewards = Self::do_remove_share(who, pool, boost_shares)?;
+      Self::pay_rewards(who, pool, &rewards)?;
+    }
+
+    if locks.is_empty() {
+      <PoolAccountLocks<T>>::remove(&pool, who);
+    } else {
+      <PoolAccountLocks<T>>::insert(&pool, who, locks);
+    }
+
+    for lock in expired {
+      Self::deposit_event(RawEvent::SharesUnlocked(who.clone(), pool, lock.shares));
+    }
+    Ok(())
   }
-}
 
-impl<T: Trait> RewardPoolOps<T::AccountId, T::PoolId, Share, Balance> for Module<T> {
   /// add shares to the reward pool
   /// note: should call this function insdie a storage transaction
   /// steps:
   /// 1. update the rewards
-  /// 2. caculate the share price in the pool
-  /// 3. calculate native currency amount needs to add to the pool to balance the share price
-  /// 4. the native currency amount is user "borrowed" which should repay back when user
-  ///    removes shares from the reward pool
+  /// 2. add the shares to the pool and the account
+  /// 3. increase the reward debt of the account by the accumulated rewards of the new shares,
+  ///    so the new shares only get the rewards accrued after they're added
+  /// the pending rewards of the account are not changed
   /// the rewards are allocated at (block_add, block_remove]
-  fn add_share(who: &T::AccountId, pool: T::PoolId, amount: Share) -> Result<Share, DispatchError> {
+  fn do_add_share(who: &T::AccountId, pool: T::PoolId, amount: Share) -> Result<Share, DispatchError> {
     if amount.is_zero() {
       return Err(Error::<T>::InvalidAmount.into());
     }
 
-    let mut pool_info = Self::update_pool_reward(&pool)?;
-
-    let price = if pool_info.total_shares.is_zero() {
-      Ok(Price::zero())
-    } else {
-      Price::checked_from_rational(pool_info.total_rewards, pool_info.total_shares)
-        .ok_or(Error::<T>::RewardCaculationError)
-    }?;
+    // the pool is created implicitly by the first shares if it's not created explicitly
+    if !<Pools<T>>::contains_key(&pool) && !<PoolStates<T>>::contains_key(&pool) {
+      let now = <frame_system::Module<T>>::block_number();
+      Self::deposit_event(RawEvent::PoolCreated(pool, now, None));
+    }
 
-    let virtual_reward_amount = price
-      .checked_mul_int(amount)
-      .ok_or(Error::<T>::RewardCaculationError)?;
+    let mut pool_info = Self::update_pool_reward(&pool)?;
 
     pool_info.total_shares = pool_info.total_shares.checked_add(amount)
       .ok_or(Error::<T>::RewardCaculationError)?;
-    pool_info.total_rewards = pool_info.total_rewards.checked_add(virtual_reward_amount.into())
-      .ok_or(Error::<T>::RewardCaculationError)?;
-    //
-    // the account need to "borrow" the amount of native currencies to balance the reward pool
     <Pools<T>>::mutate(pool, |info| {
-      *info = pool_info;
+      *info = pool_info.clone();
     });
 
     let mut total_shares = 0;
     <PoolAccountData<T>>::try_mutate(pool, who, |data| -> DispatchResult {
       data.shares = data.shares.checked_add(amount).ok_or(Error::<T>::RewardCaculationError)?;
-      // record the virtual rewards that the account 'borrowed'
-      data.borrowed_amount = data.borrowed_amount.checked_add(virtual_reward_amount.into()).ok_or(Error::<T>::RewardCaculationError)?;
+      for (currency_id, reward_info) in pool_info.rewards.iter() {
+        let debt = Self::calc_reward_debt(&amount, &reward_info.acc_reward_per_share, U256::zero())?;
+        if debt.is_zero() {
+          continue;
+        }
+        let reward_debt = data.reward_debt.entry(*currency_id).or_default();
+        *reward_debt = reward_debt.checked_add(debt).ok_or(Error::<T>::RewardCaculationError)?;
+      }
       total_shares = data.shares;
       Ok(())
     })?;
 
+    Self::deposit_event(RawEvent::ShareAdded(who.clone(), pool, amount, total_shares, pool_info.total_shares));
     Ok(total_shares)
   }
 
   /// remove shares from reward pool
-  fn remove_share(who: &T::AccountId, pool: T::PoolId, amount: Share) -> Result<Share, DispatchError>{
+  /// all the pending rewards of the account are settled, returns the rewards to send to the account
+  fn do_remove_share(who: &T::AccountId, pool: T::PoolId, amount: Share) -> Result<Vec<(CurrencyId, Balance)>, DispatchError>{
     let pool_info = Self::update_pool_reward(&pool)?;
     let account_info = <Module<T>>::pool_account_data(&pool, &who);
     // don't have sufficient shares
@@ -357,110 +1044,574 @@ impl<T: Trait> RewardPoolOps<T::AccountId, T::PoolId, Share, Balance> for Module
       return Err(Error::<T>::InsufficientShares.into());
     }
 
-    let (pool_info, account_info, reward) = Self::get_rewards_by_account_shares(pool_info, account_info, amount)?;
+    let (pool_info, account_info, rewards) = Self::get_rewards_by_account_shares(pool_info, account_info, amount)?;
+    Self::deposit_event(RawEvent::ShareRemoved(who.clone(), pool, amount, account_info.shares, pool_info.total_shares));
 
     <Pools<T>>::mutate(pool, |info| {
-      *info = pool_info;
+      *info = pool_info.clone();
     });
 
     <PoolAccountData<T>>::mutate(pool, &who, |data| {
-      *data = account_info.clone();
+      *data = account_info;
     });
 
+    Self::settle_dust(who, pool, rewards, false)
+  }
+
+  /// add the dust of the account to the settled rewards, returns the rewards to send,
+  /// the dust of all the currencies is settled, not only the currencies of the rewards,
+  /// the rewards which are still less than the existential reward are kept as the dust unless `flush` is set
+  fn settle_dust(who: &T::AccountId,
+                 pool: T::PoolId,
+                 rewards: Vec<(CurrencyId, Balance)>,
+                 flush: bool) -> Result<Vec<(CurrencyId, Balance)>, DispatchError> {
+    let pending = Self::pending_dust(&pool, who);
+    let mut settled = pending.clone();
+    for (currency_id, reward) in rewards {
+      let amount = settled.entry(currency_id).or_default();
+      *amount = amount.checked_add(reward).ok_or(Error::<T>::RewardCaculationError)?;
+    }
+
+    let mut dust = BTreeMap::new();
+    let mut payable = vec![];
+    for (currency_id, amount) in settled {
+      if !flush && amount < T::ExistentialReward::get() {
+        debug::info!("reward {:?} of {:?} is less than existential reward, keep it as dust", amount, currency_id);
+        dust.insert(currency_id, amount);
+        if pending.get(&currency_id) != Some(&amount) {
+          Self::deposit_event(RawEvent::DustAccumulated(who.clone(), pool, currency_id, amount));
+        }
+      } else {
+        payable.push((currency_id, amount));
+        Self::deposit_event(RawEvent::RewardClaimed(who.clone(), pool, currency_id, amount));
+      }
+    }
+
+    if dust.is_empty() {
+      <PendingDust<T>>::remove(&pool, who);
+    } else {
+      <PendingDust<T>>::insert(&pool, who, dust);
+    }
+    Ok(payable)
+  }
+
+  /// send the rewards from the pool account to the account,
+  /// the vesting part of the rewards is kept in the pool account and added to the vesting schedules
+  fn pay_rewards(who: &T::AccountId, pool: T::PoolId, rewards: &[(CurrencyId, Balance)]) -> DispatchResult {
     let sub_account = Self::sub_account_id(pool);
-    T::Currency::transfer(T::GetNativeCurrencyId::get(), &sub_account, &who, reward)?;
+    let config = Self::vesting_configs(&pool);
+    let mut vesting = vec![];
+    for (currency_id, reward) in rewards {
+      let locked = config.as_ref()
+        .map(|c| c.vesting_ratio.saturating_mul_int(*reward))
+        .unwrap_or_else(Zero::zero);
+      let liquid = reward.saturating_sub(locked);
+      if !liquid.is_zero() {
+        T::Currency::transfer(*currency_id, &sub_account, who, liquid)?;
+      }
+      if !locked.is_zero() {
+        vesting.push((*currency_id, locked));
+      }
+    }
+
+    if let Some(config) = config {
+      if !vesting.is_empty() {
+        Self::add_vesting(who, pool, &config, vesting)?;
+      }
+    }
+    Ok(())
+  }
+
+  /// returns the rewards of the schedule which are vested at the block
+  fn vested_amount(schedule: &VestingSchedule<Balance, T::BlockNumber>, now: T::BlockNumber) -> Balance {
+    if now >= schedule.end_block {
+      return schedule.locked;
+    }
+    if now <= schedule.start_block {
+      return Zero::zero();
+    }
+
+    let elapsed: u128 = now.saturating_sub(schedule.start_block).unique_saturated_into();
+    let duration: u128 = schedule.end_block.saturating_sub(schedule.start_block).unique_saturated_into();
+    let vested = U256::from(schedule.locked).saturating_mul(U256::from(elapsed)) / U256::from(duration);
+    Balance::try_from(vested).unwrap_or(schedule.locked)
+  }
+
+  /// add rewards to the vesting schedules of the account,
+  /// the vested rewards are released first, then the locked rewards and the new rewards vest from now
+  /// until the end block weighted by their amounts, rounded up:
+  /// now + (locked * (end_block - now) + amount * vesting_period) / (locked + amount)
+  /// so the locked rewards are not restarted by a new period, and the end block is between the old one
+  /// and the end of the new period
+  fn add_vesting(who: &T::AccountId,
+                 pool: T::PoolId,
+                 config: &VestingConfig<T::BlockNumber>,
+                 rewards: Vec<(CurrencyId, Balance)>) -> DispatchResult {
+    Self::do_release_vested(who, pool)?;
+
+    let now = <frame_system::Module<T>>::block_number();
+    let vesting_period: u128 = config.vesting_period.unique_saturated_into();
+    let mut schedules = Self::vesting_schedules(&pool, who);
+    for (currency_id, amount) in rewards {
+      let schedule = schedules.entry(currency_id).or_insert_with(Default::default);
+      let locked = schedule.locked.checked_add(amount).ok_or(Error::<T>::RewardCaculationError)?;
+      let remaining_blocks: u128 = schedule.end_block.saturating_sub(now).unique_saturated_into();
+      let weighted_blocks = U256::from(schedule.locked).saturating_mul(U256::from(remaining_blocks))
+        .saturating_add(U256::from(amount).saturating_mul(U256::from(vesting_period)));
+      let blocks = weighted_blocks.saturating_add(U256::from(locked))
+        .saturating_sub(U256::one())
+        .checked_div(U256::from(locked))
+        .and_then(|blocks| u128::try_from(blocks).ok())
+        .unwrap_or(vesting_period);
+      let blocks: T::BlockNumber = blocks.unique_saturated_into();
+
+      schedule.locked = locked;
+      schedule.start_block = now;
+      schedule.end_block = now.saturating_add(blocks);
+      Self::deposit_event(RawEvent::RewardVesting(who.clone(), pool, currency_id, amount));
+    }
+    <VestingSchedules<T>>::insert(&pool, who, schedules);
 
-    Ok(account_info.shares)
+    Ok(())
   }
 
-  /// weight: 1 db read
+  /// send the vested rewards to the account, returns the released rewards
+  fn do_release_vested(who: &T::AccountId, pool: T::PoolId) -> Result<Vec<(CurrencyId, Balance)>, DispatchError> {
+    let schedules = Self::vesting_schedules(&pool, who);
+    if schedules.is_empty() {
+      return Ok(vec![]);
+    }
+
+    let now = <frame_system::Module<T>>::block_number();
+    let sub_account = Self::sub_account_id(pool);
+    let mut remaining = BTreeMap::new();
+    let mut released = vec![];
+    for (currency_id, mut schedule) in schedules {
+      let vested = Self::vested_amount(&schedule, now);
+      if !vested.is_zero() {
+        T::Currency::transfer(currency_id, &sub_account, who, vested)?;
+        schedule.locked = schedule.locked.saturating_sub(vested);
+        schedule.start_block = now;
+        released.push((currency_id, vested));
+        Self::deposit_event(RawEvent::RewardVested(who.clone(), pool, currency_id, vested));
+      }
+      if !schedule.locked.is_zero() {
+        remaining.insert(currency_id, schedule);
+      }
+    }
+
+    if remaining.is_empty() {
+      <VestingSchedules<T>>::remove(&pool, who);
+    } else {
+      <VestingSchedules<T>>::insert(&pool, who, remaining);
+    }
+
+    Ok(released)
+  }
+
+  /// pay all the vesting rewards of the account, the penalty is redistributed to the shares in the pool,
+  /// no penalty is charged if there're no shares in the pool
+  fn do_forfeit_vesting(who: &T::AccountId, pool: T::PoolId) -> Result<Vec<(CurrencyId, Balance)>, DispatchError> {
+    Self::do_release_vested(who, pool)?;
+    let schedules = <VestingSchedules<T>>::take(&pool, who);
+    if schedules.is_empty() {
+      return Ok(vec![]);
+    }
+
+    let penalty_ratio = Self::vesting_configs(&pool)
+      .map(|c| c.forfeit_penalty)
+      .unwrap_or_else(Zero::zero);
+    let mut pool_info = Self::update_pool_reward(&pool)?;
+    let sub_account = Self::sub_account_id(pool);
+    let mut paid = vec![];
+    for (currency_id, schedule) in schedules {
+      let penalty = if pool_info.total_shares.is_zero() {
+        Zero::zero()
+      } else {
+        penalty_ratio.saturating_mul_int(schedule.locked)
+      };
+      let amount = schedule.locked.saturating_sub(penalty);
+
+      if !penalty.is_zero() {
+        let reward_per_share = Self::calc_reward_per_share(penalty, pool_info.total_shares)?;
+        let reward_info = pool_info.rewards.entry(currency_id).or_insert_with(Default::default);
+        reward_info.acc_reward_per_share = reward_info.acc_reward_per_share.checked_add(reward_per_share)
+          .ok_or(Error::<T>::RewardCaculationError)?;
+        reward_info.total_rewards_useable = reward_info.total_rewards_useable.checked_add(penalty)
+          .ok_or(Error::<T>::RewardCaculationError)?;
+      }
+      if !amount.is_zero() {
+        T::Currency::transfer(currency_id, &sub_account, who, amount)?;
+        paid.push((currency_id, amount));
+      }
+      Self::deposit_event(RawEvent::VestingForfeited(who.clone(), pool, currency_id, amount, penalty));
+    }
+
+    <Pools<T>>::insert(&pool, pool_info);
+    Ok(paid)
+  }
+}
+
+/// Reward handler which caps the emission of the inner handler for each pool and reward currency in an era,
+/// the blocks of an update are split at the era boundaries and the rewards of each part are capped in its era
+pub struct CappedRewards<T, H, C>(PhantomData<(T, H, C)>);
+
+impl<T, H, C> RewardHandler<T::AccountId, T::BlockNumber, Balance, Share, T::PoolId, CurrencyId> for CappedRewards<T, H, C>
+where
+  T: Trait,
+  H: RewardHandler<T::AccountId, T::BlockNumber, Balance, Share, T::PoolId, CurrencyId>,
+  C: EmissionCap<T::PoolId, CurrencyId, T::BlockNumber, Balance>,
+{
+  fn caculate_reward(pool_id: &T::PoolId,
+                     total_share: &Share,
+                     last_update_block: T::BlockNumber,
+                     now: T::BlockNumber) -> Vec<(CurrencyId, Balance)> {
+    let era_length = C::era_length();
+    if era_length.is_zero() {
+      return H::caculate_reward(pool_id, total_share, last_update_block, now);
+    }
+
+    let mut rewards = BTreeMap::<CurrencyId, Balance>::new();
+    let mut from = last_update_block;
+    while from < now {
+      let era = from / era_length;
+      let to = cmp::min(now, era.saturating_add(One::one()).saturating_mul(era_length));
+      for (currency_id, reward) in H::caculate_reward(pool_id, total_share, from, to) {
+        let reward = match C::max_emission(pool_id, &currency_id) {
+          Some(max_emission) => {
+            let (last_era, emitted) = <EraEmissions<T>>::get(pool_id, currency_id);
+            let emitted = if last_era == era { emitted } else { Zero::zero() };
+            let reward = reward.min(max_emission.saturating_sub(emitted));
+            <EraEmissions<T>>::insert(pool_id, currency_id, (era, emitted.saturating_add(reward)));
+            if reward.is_zero() {
+              debug::info!("emission of pool {:?}, currency {:?} reached the cap in era {:?}", pool_id, currency_id, era);
+            }
+            reward
+          },
+          None => reward,
+        };
+        if !reward.is_zero() {
+          let total = rewards.entry(currency_id).or_default();
+          *total = total.saturating_add(reward);
+        }
+      }
+      from = to;
+    }
+    rewards.into_iter().collect()
+  }
+}
+
+impl<T: Trait> RewardPoolOps<T::AccountId, T::PoolId, CurrencyId, Share, Balance, T::BlockNumber> for Module<T> {
+  /// add shares to the reward pool
+  /// note: should call this function insdie a storage transaction
+  fn add_share(who: &T::AccountId, pool: T::PoolId, amount: Share) -> Result<Share, DispatchError> {
+    ensure!(!Self::is_pool_closed(&pool), Error::<T>::PoolClosed);
+    Self::release_expired_locks(who, pool)?;
+    Self::do_add_share(who, pool, amount)?;
+    Ok(Self::get_account_shares(who, &pool))
+  }
+
+  /// remove shares from reward pool, the locked shares can't be removed
+  /// if the pool has an unbonding period, the shares and the settled rewards are moved to the
+  /// unbonding queue, they stop accruing rewards and are paid by `withdraw_unbonded` later
+  /// note: should call this function insdie a storage transaction
+  fn remove_share(who: &T::AccountId, pool: T::PoolId, amount: Share) -> Result<Share, DispatchError> {
+    Self::release_expired_locks(who, pool)?;
+    let account_info = Self::get_pool_account_info(&pool, who);
+    let locks = Self::pool_account_locks(&pool, who);
+    if Self::staked_shares(&account_info, &locks) < amount {
+      return Err(Error::<T>::InsufficientShares.into());
+    }
+    if Self::free_shares(&account_info, &locks) < amount {
+      return Err(Error::<T>::SharesStillLocked.into());
+    }
+
+    let unbonding_period = Self::unbonding_periods(&pool);
+    if unbonding_period.is_zero() {
+      let rewards = Self::do_remove_share(who, pool, amount)?;
+      Self::pay_rewards(who, pool, &rewards)?;
+    } else {
+      let mut chunks = Self::unbondings(&pool, who);
+      if chunks.len() >= T::MaxUnbondingChunks::get() as usize {
+        return Err(Error::<T>::TooManyUnbondingChunks.into());
+      }
+
+      let rewards = Self::do_remove_share(who, pool, amount)?;
+      let unbonded_block = <frame_system::Module<T>>::block_number().saturating_add(unbonding_period);
+      chunks.push(UnbondingChunk { shares: amount, rewards, unbonded_block, });
+      <Unbondings<T>>::insert(&pool, who, chunks);
+
+      Self::deposit_event(RawEvent::SharesUnbonding(who.clone(), pool, amount, unbonded_block));
+    }
+
+    Ok(Self::get_account_shares(who, &pool))
+  }
+
+  /// withdraw the unbonded shares and pay the rewards settled while removing them,
+  /// returns the withdrawn shares
+  fn withdraw_unbonded(who: &T::AccountId, pool: T::PoolId) -> Result<Share, DispatchError> {
+    let now = <frame_system::Module<T>>::block_number();
+    let chunks = Self::unbondings(&pool, who);
+    let (unbonded, chunks): (Vec<_>, Vec<_>) = chunks.into_iter().partition(|chunk| chunk.unbonded_block <= now);
+    if unbonded.is_empty() {
+      return Ok(Zero::zero());
+    }
+
+    if chunks.is_empty() {
+      <Unbondings<T>>::remove(&pool, who);
+    } else {
+      <Unbondings<T>>::insert(&pool, who, chunks);
+    }
+
+    let mut shares: Share = Zero::zero();
+    for chunk in unbonded {
+      Self::pay_rewards(who, pool, &chunk.rewards)?;
+      shares = shares.saturating_add(chunk.shares);
+    }
+    // the shares of the staking pools are the staked balance
+    if let Some(currency_id) = T::StakingCurrency::convert(pool) {
+      let remaining = T::Currency::unreserve(currency_id, who, shares);
+      ensure!(remaining.is_zero(), Error::<T>::InsufficientStakedBalance);
+    }
+
+    Self::deposit_event(RawEvent::SharesWithdrawn(who.clone(), pool, shares));
+    Ok(shares)
+  }
+
+  fn stake(who: &T::AccountId, pool: T::PoolId, amount: Balance) -> Result<Share, DispatchError> {
+    with_transaction_result(|| Self::do_stake(who, pool, amount))
+  }
+
+  fn unstake(who: &T::AccountId, pool: T::PoolId, amount: Balance) -> Result<Share, DispatchError> {
+    with_transaction_result(|| Self::do_unstake(who, pool, amount))
+  }
+
+  /// settle the rewards of the pool up to now, the pools which don't exist are ignored
+  fn update_pool_rewards(pool: &T::PoolId) -> DispatchResult {
+    if <Pools<T>>::contains_key(pool) {
+      Self::update_pool_reward(pool)?;
+    }
+    Ok(())
+  }
+
+  /// remove all the shares of the account without computing the rewards,
+  /// the pending rewards are left in the pool account, returns the staked shares
+  /// the unbonding shares are withdrawn as well, the pending rewards at the last update of the pool,
+  /// the unbonding rewards and the dust are forfeited and redistributed to the shares in the pool,
+  /// they're left in the pool account if there are no shares in the pool.
+  /// only the staking pools are allowed, the shares of the other pools are managed by other modules.
+  fn emergency_withdraw(who: &T::AccountId, pool: T::PoolId) -> Result<Share, DispatchError> {
+    ensure!(Self::emergency_withdraw_enabled(pool), Error::<T>::EmergencyWithdrawDisabled);
+    ensure!(T::StakingCurrency::convert(pool).is_some(), Error::<T>::PoolNotStakeable);
+
+    let account_info = <PoolAccountData<T>>::take(&pool, who);
+    let locks = <PoolAccountLocks<T>>::take(&pool, who);
+    let unbondings = <Unbondings<T>>::take(&pool, who);
+    let mut forfeited = <PendingDust<T>>::take(&pool, who);
+    if account_info.shares.is_zero() && unbondings.is_empty() && forfeited.is_empty() {
+      return Ok(Zero::zero());
+    }
+
+    let mut shares = Self::staked_shares(&account_info, &locks);
+    for chunk in unbondings {
+      shares = shares.saturating_add(chunk.shares);
+      for (currency_id, reward) in chunk.rewards {
+        let amount = forfeited.entry(currency_id).or_default();
+        *amount = amount.saturating_add(reward);
+      }
+    }
+
+    // the settled rewards are moved back to the pool
+    let mut pool_info = Self::get_pool(&pool);
+    pool_info.total_shares = pool_info.total_shares.saturating_sub(account_info.shares);
+    for (currency_id, amount) in forfeited.iter() {
+      let reward_info = pool_info.rewards.entry(*currency_id).or_default();
+      reward_info.total_rewards_useable = reward_info.total_rewards_useable.saturating_add(*amount);
+    }
+
+    for (currency_id, reward_info) in pool_info.rewards.iter_mut() {
+      let reward_debt = account_info.reward_debt.get(currency_id).cloned().unwrap_or_default();
+      let pending = Self::calc_reward_by_shares(&account_info.shares, &reward_info.acc_reward_per_share, &reward_debt)
+        .map(|(reward, _)| reward)
+        .unwrap_or_else(|_| Zero::zero());
+      let amount = forfeited.get(currency_id).cloned().unwrap_or_default()
+        .saturating_add(pending)
+        .min(reward_info.total_rewards_useable);
+      if amount.is_zero() {
+        continue;
+      }
+
+      if pool_info.total_shares.is_zero() {
+        reward_info.total_rewards_useable = reward_info.total_rewards_useable.saturating_sub(amount);
+      } else {
+        let reward_per_share = Self::calc_reward_per_share(amount, pool_info.total_shares)?;
+        reward_info.acc_reward_per_share = reward_info.acc_reward_per_share.checked_add(reward_per_share)
+          .ok_or(Error::<T>::RewardCaculationError)?;
+      }
+      Self::deposit_event(RawEvent::EmergencyRewardForfeited(who.clone(), pool, *currency_id, amount));
+    }
+    <Pools<T>>::insert(&pool, pool_info);
+
+    Self::deposit_event(RawEvent::EmergencyWithdrawn(who.clone(), pool, shares));
+    Ok(shares)
+  }
+
+  /// returns the unbonding chunks of the account: (shares, rewards, unbonded block)
+  fn get_unbondings(who: &T::AccountId, pool: &T::PoolId) -> Vec<(Share, Vec<(CurrencyId, Balance)>, T::BlockNumber)> {
+    Self::unbondings(pool, who)
+      .into_iter()
+      .map(|chunk| (chunk.shares, chunk.rewards, chunk.unbonded_block))
+      .collect()
+  }
+
+  /// lock shares of the account for `lock_period` blocks,
+  /// the boost shares granted by the multiplier of the lock period are added to the pool
+  /// note: should call this function insdie a storage transaction
+  fn lock_share(who: &T::AccountId, pool: T::PoolId, amount: Share, lock_period: T::BlockNumber) -> DispatchResult {
+    if amount.is_zero() {
+      return Err(Error::<T>::InvalidAmount.into());
+    }
+    ensure!(Self::pool_states(pool).status != PoolStatus::Closed, Error::<T>::PoolClosed);
+    let multiplier = T::LockSchedule::lock_multiplier(&pool, &lock_period)
+      .ok_or(Error::<T>::InvalidLockPeriod)?;
+
+    Self::release_expired_locks(who, pool)?;
+    let account_info = Self::get_pool_account_info(&pool, who);
+    let mut locks = Self::pool_account_locks(&pool, who);
+    if locks.len() >= T::MaxShareLocks::get() as usize {
+      return Err(Error::<T>::TooManyLocks.into());
+    }
+    if Self::free_shares(&account_info, &locks) < amount {
+      return Err(Error::<T>::InsufficientShares.into());
+    }
+
+    let boost_shares = multiplier.saturating_mul_int(amount).saturating_sub(amount);
+    if !boost_shares.is_zero() {
+      Self::do_add_share(who, pool, boost_shares)?;
+    }
+
+    let unlock_block = <frame_system::Module<T>>::block_number().saturating_add(lock_period);
+    locks.push(ShareLock { shares: amount, boost_shares, unlock_block, });
+    <PoolAccountLocks<T>>::insert(&pool, who, locks);
+
+    Self::deposit_event(RawEvent::SharesLocked(who.clone(), pool, amount, boost_shares, unlock_block));
+    Ok(())
+  }
+
+  /// weight: 2 db read
+  /// returns the shares added to the pool, the boost shares of the locks are not included
   fn get_account_shares(who: &T::AccountId, pool: &T::PoolId)  -> Share {
-    let PoolAccountInfo { shares, ..} = Self::get_pool_account_info(&pool, who);
-    shares
+    let account_info = Self::get_pool_account_info(&pool, who);
+    let locks = Self::pool_account_locks(&pool, who);
+    Self::staked_shares(&account_info, &locks)
+  }
+
+  fn is_pool_closed(pool: &T::PoolId) -> bool {
+    Self::pool_states(pool).status == PoolStatus::Closed
   }
 
   /// calculate accumlated rewards which haven't been claimed
   /// this is a readonly api and should not write the storage
-  fn get_accumlated_rewards(who: &T::AccountId, pool: &T::PoolId) -> Balance {
+  fn get_accumlated_rewards(who: &T::AccountId, pool: &T::PoolId) -> Vec<(CurrencyId, Balance)> {
     let account_info  = Self::get_pool_account_info(&pool, who);
+    let dust = Self::pending_dust(pool, who);
     if account_info.shares.is_zero() {
-      return 0;
+      return dust.into_iter().collect();
     }
 
-    let calc_reward = || -> Result<Balance, DispatchError> {
+    let calc_reward = || -> Result<Vec<(CurrencyId, Balance)>, DispatchError> {
       // update the pool info to now
       let (pool_info, _) = Self::calc_pool_reward(pool)?;
-      let shares = account_info.shares.clone();
-      let (_, _, reward) = Self::get_rewards_by_account_shares(pool_info, account_info, shares)?;
-      Ok(reward)
+      let mut rewards = dust.clone();
+      for (currency_id, reward_info) in pool_info.rewards.iter() {
+        let reward_debt = account_info.reward_debt.get(currency_id).cloned().unwrap_or_default();
+        let (reward, _) = Self::calc_reward_by_shares(&account_info.shares, &reward_info.acc_reward_per_share, &reward_debt)?;
+        let amount = rewards.entry(*currency_id).or_default();
+        *amount = amount.saturating_add(reward);
+      }
+      Ok(rewards.into_iter().filter(|(_, reward)| !reward.is_zero()).collect())
     };
-    match calc_reward() {
-      Ok(reward) => reward,
+    // the reward handlers could record their state while calculating the rewards,
+    // which is reverted as the query should not write the storage
+    match with_transaction(|| TransactionOutcome::Rollback(calc_reward())) {
+      Ok(rewards) => rewards,
       Err(e) => {
         debug::error!("failed to calculate reward for account: {:?}, pool: {:?}, error: {:?}", who, pool, e);
-        Zero::zero()
+        vec![]
       }
     }
   }
 
-  fn claim_rewards(who: &T::AccountId, pool: &T::PoolId) -> Result<Balance, DispatchError> {
+  /// claim the rewards of all the reward currencies in the pool,
+  /// either all the rewards are paid out or none of them
+  #[transactional]
+  fn claim_rewards(who: &T::AccountId, pool: &T::PoolId) -> Result<Vec<(CurrencyId, Balance)>, DispatchError> {
+    Self::release_expired_locks(who, *pool)?;
     // update accumlated rewards for the pool
-    let pool_info = Self::update_pool_reward(&pool)?;
-    let account_info  = Self::get_pool_account_info(&pool, who);
+    let mut pool_info = Self::update_pool_reward(&pool)?;
+    let mut account_info  = Self::get_pool_account_info(&pool, who);
 
+    // no more rewards are accrued without shares, all the dust is sent
     if account_info.shares.is_zero() {
-      return Ok(Zero::zero());
+      let rewards = Self::settle_dust(who, *pool, vec![], true)?;
+      Self::pay_rewards(who, *pool, &rewards)?;
+      return Ok(rewards);
     }
 
-    let reward_with_virtual = Self::calc_reward_by_shares(&pool_info, &account_info.shares)?;
+    let mut rewards = vec![];
 
-    let PoolInfo { total_rewards_useable, ..} = pool_info;
-    let PoolAccountInfo { borrowed_amount, ..} = account_info;
-
-    let actual_reward = reward_with_virtual.checked_sub(borrowed_amount)
-      .ok_or(Error::<T>::RewardCaculationError)?;
-    // don't have enough rewards to claim
-    if actual_reward < T::ExistentialReward::get() {
-      return Ok(Zero::zero());
-    }
-    let total_rewards_useable = total_rewards_useable.checked_sub(actual_reward)
-      .ok_or(Error::<T>::RewardCaculationError)?;
+    for (currency_id, reward_info) in pool_info.rewards.iter_mut() {
+      let reward_debt = account_info.reward_debt.get(currency_id).cloned().unwrap_or_default();
+      let (reward, remainder) = Self::calc_reward_by_shares(&account_info.shares, &reward_info.acc_reward_per_share, &reward_debt)?;
 
-    // another check, total rewards should be greater than borrowed amount
-    if borrowed_amount > reward_with_virtual {
-      return Err(Error::<T>::RewardCaculationError.into());
-    }
-    // since we've claimed all available rewards, we should borrow the reward from the pool, the claimable rewards is zero
-    let borrowed_amount = reward_with_virtual;
+      if reward.is_zero() {
+        continue;
+      }
+      reward_info.total_rewards_useable = reward_info.total_rewards_useable.checked_sub(reward)
+        .ok_or(Error::<T>::RewardCaculationError)?;
 
-    let sub_account = Self::sub_account_id(pool.clone());
+      // all the pending rewards are paid, only the remainder is kept for the account
+      let reward_debt = Self::calc_reward_debt(&account_info.shares, &reward_info.acc_reward_per_share, remainder)?;
+      account_info.reward_debt.insert(*currency_id, reward_debt);
 
-    T::Currency::transfer(T::GetNativeCurrencyId::get(), &sub_account, &who, actual_reward.unique_saturated_into())?;
+      rewards.push((*currency_id, reward));
+    }
 
     <Pools<T>>::mutate(pool, |info| {
-      info.total_rewards_useable = total_rewards_useable;
+      *info = pool_info;
     });
 
     <PoolAccountData<T>>::mutate(pool, who, |data| {
-      data.borrowed_amount = borrowed_amount;
+      *data = account_info;
     });
 
-    Ok(actual_reward)
+    let rewards = Self::settle_dust(who, *pool, rewards, false)?;
+    Self::pay_rewards(who, *pool, &rewards)?;
+    Ok(rewards)
   }
 
-  fn get_all_pools() -> vec::Vec<(T::PoolId, Share, Balance)> {
+  /// this is a readonly api and should not write the storage
+  fn get_all_pools() -> vec::Vec<(T::PoolId, Share, vec::Vec<(CurrencyId, Balance)>)> {
     let cur_block = <frame_system::Module<T>>::block_number();
-    <Pools<T>>::iter()
-      .map(|(pool_id, info)| {
-        let result = Self::calc_pool_reward_at_block(&pool_id, &info, &cur_block);
-        match result {
-          Ok((new_info, _)) => (pool_id, new_info.total_shares, new_info.total_rewards_useable),
-          Err(e) => {
-            debug::error!("failed to get pool info for {:?}, error: {:?}", pool_id, e);
-            (pool_id, info.total_shares, Zero::zero())
-          },
-        }
-      }).collect()
+    let calc_pools = || {
+      <Pools<T>>::iter()
+        .map(|(pool_id, info)| {
+          let result = Self::calc_pool_reward_at_block(&pool_id, &info, &cur_block);
+          match result {
+            Ok((new_info, _)) => {
+              let rewards = new_info.rewards.iter()
+                .map(|(currency_id, reward_info)| (*currency_id, reward_info.total_rewards_useable))
+                .collect();
+              (pool_id, new_info.total_shares, rewards)
+            },
+            Err(e) => {
+              debug::error!("failed to get pool info for {:?}, error: {:?}", pool_id, e);
+              (pool_id, info.total_shares, vec![])
+            },
+          }
+        }).collect()
+    };
+    // the reward handlers could record their state while calculating the rewards, which is reverted
+    with_transaction(|| TransactionOutcome::Rollback(calc_pools()))
   }
 }
-
@@ -0,0 +1,78 @@
+//! Storage migrations of the reward pool module
+
+use super::*;
+use frame_support::storage::{IterableStorageDoubleMap, IterableStorageMap, StorageValue};
+
+/// the pool info before V2_0_0, the rewards are paid in the native currency
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, Default)]
+pub struct OldPoolInfo<Share: HasCompact, Balance: HasCompact, Block: HasCompact> {
+  #[codec(compact)]
+  pub total_shares: Share,
+  /// including the virtual rewards added while adding shares
+  #[codec(compact)]
+  pub total_rewards: Balance,
+  #[codec(compact)]
+  pub total_rewards_useable: Balance,
+  #[codec[compact]]
+  pub last_update_block: Block,
+}
+
+/// the account info before V2_0_0
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, Default)]
+pub struct OldPoolAccountInfo<Share: HasCompact, Balance: HasCompact> {
+  #[codec(compact)]
+  pub shares: Share,
+  #[codec(compact)]
+  pub borrowed_amount: Balance,
+}
+
+/// migrate the virtual rewards of the pools to the accumulated reward per share of the native currency,
+/// the pending rewards of the accounts are kept:
+/// shares * total_rewards / total_shares - borrowed_amount
+/// = (shares * acc_reward_per_share - reward_debt) / ACC_REWARD_PRECISION
+/// with acc_reward_per_share = total_rewards * ACC_REWARD_PRECISION / total_shares
+/// and reward_debt = borrowed_amount * ACC_REWARD_PRECISION
+pub fn migrate_to_v2<T: Trait>() -> Weight {
+  let precision = U256::from(ACC_REWARD_PRECISION);
+  let native_currency_id = T::GetNativeCurrencyId::get();
+  let mut pools: u64 = 0;
+  let mut accounts: u64 = 0;
+
+  <Pools<T>>::translate::<OldPoolInfo<Share, Balance, T::BlockNumber>, _>(|pool_id, old| {
+    pools += 1;
+    let total_shares = old.total_shares;
+    let acc_reward_per_share = if total_shares.is_zero() {
+      U256::zero()
+    } else {
+      U256::from(old.total_rewards).saturating_mul(precision) / U256::from(total_shares)
+    };
+    let mut rewards = BTreeMap::new();
+    rewards.insert(native_currency_id, PoolRewardInfo {
+      acc_reward_per_share,
+      total_rewards_useable: old.total_rewards_useable,
+    });
+    debug::info!("migrated reward pool: {:?}", pool_id);
+
+    Some(PoolInfo {
+      total_shares,
+      rewards,
+      last_update_block: old.last_update_block,
+    })
+  });
+
+  <PoolAccountData<T>>::translate::<OldPoolAccountInfo<Share, Balance>, _>(|_, _, old| {
+    accounts += 1;
+    let mut reward_debt = BTreeMap::new();
+    reward_debt.insert(native_currency_id, U256::from(old.borrowed_amount).saturating_mul(precision));
+
+    Some(PoolAccountInfo {
+      shares: old.shares,
+      reward_debt,
+    })
+  });
+
+  StorageVersion::put(Releases::V2_0_0);
+
+  let count = pools.saturating_add(accounts);
+  T::DbWeight::get().reads_writes(count, count.saturating_add(1))
+}
@@ -4,20 +4,23 @@ use frame_support::{
   impl_outer_event, impl_outer_origin, parameter_types,
   traits::{OnFinalize, OnInitialize},
 };
+use frame_system::EnsureRoot;
 use sp_core::H256;
-use sp_runtime::{testing::Header, traits::IdentityLookup, Perbill};
+use sp_std::cell::RefCell;
+use sp_runtime::{testing::Header, traits::IdentityLookup};
 pub use pallet_balances::Call as BalancesCall;
 
 pub use primitives::{
   AccountId, AccountIndex, Amount, Balance,
   CurrencyId,
   EraIndex, Hash, Index, Moment,
-  Rate, Share,
+  Rate, Ratio, Share,
   Signature,
   currency::*,
 };
 
 use orml_currencies::{BasicCurrencyAdapter};
+use orml_traits::parameter_type_with_key;
 
 pub type BlockNumber = u64;
 
@@ -44,9 +47,6 @@ impl_outer_origin! {
 
 parameter_types! {
   pub const BlockHashCount: u64 = 250;
-  pub const MaximumBlockWeight: u32 = 1024;
-  pub const MaximumBlockLength: u32 = 2 * 1024;
-  pub const AvailableBlockRatio: Perbill = Perbill::one();
 }
 
 impl frame_system::Config for TestRuntime {
@@ -61,20 +61,17 @@ impl frame_system::Config for TestRuntime {
   type Header = Header;
   type Event = TestEvent;
   type BlockHashCount = BlockHashCount;
-  type MaximumBlockWeight = MaximumBlockWeight;
-  type MaximumBlockLength = MaximumBlockLength;
-  type AvailableBlockRatio = AvailableBlockRatio;
+  type BlockWeights = ();
+  type BlockLength = ();
   type Version = ();
   type PalletInfo = ();
   type AccountData = pallet_balances::AccountData<Balance>;
   type OnNewAccount = ();
   type OnKilledAccount = ();
   type DbWeight = ();
-  type BlockExecutionWeight = ();
-  type ExtrinsicBaseWeight = ();
-  type MaximumExtrinsicWeight = ();
   type BaseCallFilter = ();
   type SystemWeightInfo = ();
+  type SS58Prefix = ();
 }
 
 pub type System = frame_system::Module<TestRuntime>;
@@ -98,13 +95,20 @@ impl pallet_balances::Config for TestRuntime {
 
 pub type Balances = pallet_balances::Module<TestRuntime>;
 
+parameter_type_with_key! {
+  pub ExistentialDeposits: |_currency_id: CurrencyId| -> Balance {
+    0
+  };
+}
+
 impl orml_tokens::Config for TestRuntime {
   type Event = TestEvent;
   type Balance = Balance;
   type Amount = Amount;
   type CurrencyId = CurrencyId;
-  type OnReceived = ();
   type WeightInfo = ();
+  type ExistentialDeposits = ExistentialDeposits;
+  type OnDust = ();
 }
 
 pub type Tokens = orml_tokens::Module<TestRuntime>;
@@ -130,23 +134,60 @@ parameter_types! {
 #[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug, Ord, PartialOrd)]
 pub enum PoolId {
   Swap(u64),
+  Stake(CurrencyId),
+}
+
+pub struct StakingCurrency;
+impl Convert<PoolId, Option<CurrencyId>> for StakingCurrency {
+  fn convert(pool_id: PoolId) -> Option<CurrencyId> {
+    match pool_id {
+      PoolId::Swap(_) => None,
+      PoolId::Stake(currency_id) => Some(currency_id),
+    }
+  }
+}
+
+thread_local! {
+  static REWARD_CURRENCIES: RefCell<Vec<CurrencyId>> = RefCell::new(vec![CurrencyId::CLV]);
+}
+
+pub fn set_reward_currencies(currencies: Vec<CurrencyId>) {
+  REWARD_CURRENCIES.with(|v| *v.borrow_mut() = currencies);
 }
 
 pub struct Handler;
-impl RewardHandler<AccountId, BlockNumber, Balance, Share, PoolId> for Handler {
-  // simple reward calculation, 1 block 1 reward
+impl RewardHandler<AccountId, BlockNumber, Balance, Share, PoolId, CurrencyId> for Handler {
+  // simple reward calculation, 1 block 1 reward for each reward currency
   fn caculate_reward(pool_id: &PoolId, total_share: &Share, last_update_block: BlockNumber,
-                     now: BlockNumber) -> Balance {
+                     now: BlockNumber) -> Vec<(CurrencyId, Balance)> {
     println!("calculate reward for pool: {:?}", pool_id);
     if total_share.is_zero() {
       println!("no reward because no share in pool, pool: {:?}", pool_id);
-      0
-    } else {
-      DOLLARS.checked_mul((now - last_update_block).into()).unwrap()
+      return vec![];
     }
+    let reward = DOLLARS.checked_mul((now - last_update_block).into()).unwrap();
+    REWARD_CURRENCIES.with(|v| v.borrow().iter().map(|currency_id| (*currency_id, reward)).collect())
   }
 }
 
+// 10 blocks: 1.5x, 20 blocks: 2x
+pub struct LockSchedule;
+impl LockScheduleProvider<PoolId, BlockNumber> for LockSchedule {
+  fn lock_multiplier(_: &PoolId, lock_period: &BlockNumber) -> Option<Ratio> {
+    match lock_period {
+      10 => Some(Ratio::saturating_from_rational(3, 2)),
+      20 => Some(Ratio::saturating_from_integer(2)),
+      _ => None,
+    }
+  }
+}
+
+parameter_types! {
+  pub const MaxShareLocks: u32 = 2;
+  pub const MaxUnbondingChunks: u32 = 2;
+  pub const MaxClaimPools: u32 = 3;
+}
+
 impl Trait for TestRuntime {
   type Event = TestEvent;
   type Currency = Currencies;
@@ -155,10 +196,32 @@ impl Trait for TestRuntime {
   type PoolId = PoolId;
   type Handler = Handler;
   type ExistentialReward = ExistentialDeposit;
+  type LockSchedule = LockSchedule;
+  type MaxShareLocks = MaxShareLocks;
+  type MaxUnbondingChunks = MaxUnbondingChunks;
+  type MaxClaimPools = MaxClaimPools;
+  type UpdateOrigin = EnsureRoot<AccountId>;
+  type StakingCurrency = StakingCurrency;
+  type WeightInfo = ();
 }
 
 pub type RewardPoolModule = Module<TestRuntime>;
 
+#[cfg(feature = "runtime-benchmarks")]
+impl crate::benchmarking::Config for TestRuntime {
+  fn pool(index: u32) -> PoolId {
+    PoolId::Swap(index.into())
+  }
+
+  fn staking_pool() -> PoolId {
+    PoolId::Stake(CurrencyId::DOT)
+  }
+
+  fn lock_period() -> BlockNumber {
+    10
+  }
+}
+
 pub const ALICE: [u8; 32] = [0u8; 32];
 pub const BOB: [u8; 32] = [1u8; 32];
 pub const DAVE: [u8; 32] = [2u8; 32];
@@ -169,6 +232,7 @@ pub const CETH: CurrencyId = CurrencyId::CETH;
 
 pub struct ExtBuilder {
   endowed_accounts: Vec<(AccountId, CurrencyId, Balance)>,
+  reward_currencies: Vec<CurrencyId>,
 }
 
 impl Default for ExtBuilder {
@@ -187,12 +251,20 @@ impl Default for ExtBuilder {
         (alice.clone(), CETH, 1_000_000_000_000_000_000u128),
         (bob.clone(), CETH, 1_000_000_000_000_000_000u128),
       ],
+      reward_currencies: vec![CLV],
     }
   }
 }
 
 impl ExtBuilder {
+  pub fn reward_currencies(mut self, currencies: Vec<CurrencyId>) -> Self {
+    self.reward_currencies = currencies;
+    self
+  }
+
   pub fn build(self) -> sp_io::TestExternalities {
+    set_reward_currencies(self.reward_currencies.clone());
+
     let mut t = frame_system::GenesisConfig::default()
       .build_storage::<TestRuntime>()
       .unwrap();
@@ -218,6 +290,9 @@ impl ExtBuilder {
     }
     .assimilate_storage(&mut t).unwrap();
 
+    reward_pool::GenesisConfig::default()
+      .assimilate_storage::<TestRuntime>(&mut t).unwrap();
+
     t.into()
   }
 }
@@ -1,9 +1,14 @@
 #![cfg(test)]
 
 use super::*;
+use frame_support::{
+  assert_noop, assert_ok,
+  storage::{unhashed, StorageDoubleMap, StorageMap, StorageValue},
+  traits::OnRuntimeUpgrade,
+};
 use mock::{
-  ALICE, BOB, DAVE, Currencies, PoolId, RewardPoolModule,
-  run_to_block,
+  ALICE, BOB, DAVE, CLV, DOT, Currencies, Handler, MaxClaimPools, Origin, PoolId, RewardPoolModule, System, TestEvent, TestRuntime,
+  run_to_block, set_reward_currencies,
   ExtBuilder,
 };
 
@@ -12,17 +17,28 @@ pub use primitives::{ AccountId, currency::*, };
 use RewardPoolModule as RPM;
 
 fn check_pool_data (pool_id: &PoolId, account: &AccountId,
-                    total_shares: Share,
-                    total_rewards: Balance, total_rewards_useable: Balance,
-                    alice_shares: Share, alice_borrow: Balance,) {
+                    total_shares: Share, total_rewards_useable: Balance,
+                    alice_shares: Share, alice_pending: Balance,) {
+  check_pool_currency_data(pool_id, account, CurrencyId::CLV, total_shares,
+                           total_rewards_useable, alice_shares, alice_pending);
+}
+
+fn check_pool_currency_data (pool_id: &PoolId, account: &AccountId,
+                             currency_id: CurrencyId,
+                             total_shares: Share, total_rewards_useable: Balance,
+                             alice_shares: Share, alice_pending: Balance,) {
   let pool_info = RPM::get_pool_info(&pool_id);
+  let reward_info = pool_info.rewards.get(&currency_id).cloned().unwrap_or_default();
   assert_eq!(pool_info.total_shares, total_shares);
-  assert_eq!(pool_info.total_rewards, total_rewards);
-  assert_eq!(pool_info.total_rewards_useable, total_rewards_useable);
+  assert_eq!(reward_info.total_rewards_useable, total_rewards_useable);
 
   let alice_info = RPM::get_pool_account_info(&pool_id, &account);
   assert_eq!(alice_info.shares, alice_shares);
-  assert_eq!(alice_info.borrowed_amount, alice_borrow);
+  let pending = RPM::get_accumlated_rewards(&account, &pool_id).into_iter()
+    .find(|(c, _)| *c == currency_id)
+    .map(|(_, reward)| reward)
+    .unwrap_or_default();
+  assert_eq!(pending, alice_pending);
 }
 
 
@@ -35,8 +51,7 @@ fn test_reward_calc_on_no_shares() {
     assert!(r.is_ok());
     let pool_info = RPM::get_pool_info(&pool_id);
     assert_eq!(pool_info.total_shares, 0, "should be no shares");
-    assert_eq!(pool_info.total_rewards, 0, "should be no rewards");
-    assert_eq!(pool_info.total_rewards_useable, 0, "should be no rewards usable");
+    assert!(pool_info.rewards.is_empty(), "should be no rewards");
 
     // sometime passed...
     run_to_block(20);
@@ -44,8 +59,7 @@ fn test_reward_calc_on_no_shares() {
     assert_eq!(r.is_ok(), true);
     let pool_info = RPM::get_pool_info(&pool_id);
     assert_eq!(pool_info.total_shares, 0, "should be no shares");
-    assert_eq!(pool_info.total_rewards, 0, "should be no rewards");
-    assert_eq!(pool_info.total_rewards_useable, 0, "should be no rewards usable");
+    assert!(pool_info.rewards.is_empty(), "should be no rewards");
   });
 }
 
@@ -63,27 +77,28 @@ fn test_reward_single_account() {
     assert!(RPM::update_pool_reward(&pool_id).is_ok());
     let pool_info = RPM::get_pool_info(&pool_id);
     assert_eq!(pool_info.last_update_block, 20);
-    check_pool_data(&pool_id, &alice, 100, 10 * DOLLARS, 10 * DOLLARS, 100, 0);
+    check_pool_data(&pool_id, &alice, 100, 10 * DOLLARS, 100, 10 * DOLLARS);
 
     run_to_block(30);
     assert!(RPM::add_share(&alice, pool_id, 100).is_ok(), "should add shares to the pool");
     let pool_info = RPM::get_pool_info(&pool_id);
     assert_eq!(pool_info.last_update_block, 30);
-    check_pool_data(&pool_id, &alice, 200, 40 * DOLLARS, 20 * DOLLARS, 200, 20 * DOLLARS);
+    check_pool_data(&pool_id, &alice, 200, 20 * DOLLARS, 200, 20 * DOLLARS);
 
     run_to_block(40);
 
-    // before remove, total: 50, useable: 30
+    // before remove, useable: 30, all the pending rewards are sent to alice
     let r = RPM::remove_share(&alice, pool_id, 100);
     assert!(r.is_ok(), "should add shares to the pool");
 
-    check_pool_data(&pool_id, &alice, 100, 25 * DOLLARS, 15 * DOLLARS, 100, 10 * DOLLARS);
+    check_pool_data(&pool_id, &alice, 100, 0, 100, 0);
 
-    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &pool_account), 15 * DOLLARS);
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &pool_account), 0);
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_balance + 30 * DOLLARS);
 
     run_to_block(50);
     assert!(RPM::remove_share(&alice, pool_id, 100).is_ok(), "should remove shares to the pool");
-    check_pool_data(&pool_id, &alice, 0, 0, 0, 0, 0);
+    check_pool_data(&pool_id, &alice, 0, 0, 0, 0);
     assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_balance + 40 * DOLLARS);
     assert_eq!(Currencies::total_balance(CurrencyId::CLV, &pool_account), 0);
   });
@@ -93,27 +108,27 @@ fn test_reward_single_account() {
 fn test_reward_single_account_existential() {
   let pool_id = PoolId::Swap(1);
   let alice = AccountId::from(ALICE);
-  let pool_account = RPM::sub_account_id(pool_id.clone());
+  let bob = AccountId::from(BOB);
 
   ExtBuilder::default().build().execute_with(|| {
-    let initial_balance = Currencies::total_balance(CurrencyId::CLV, &alice);
+    let initial_alice = Currencies::total_balance(CurrencyId::CLV, &alice);
+    let initial_bob = Currencies::total_balance(CurrencyId::CLV, &bob);
     run_to_block(10);
-    assert!(RPM::add_share(&alice, pool_id, 1_000_000_000_000).is_ok(), "should add shares to the pool");
-    run_to_block(11);
-    assert!(RPM::remove_share(&alice, pool_id, 1).is_ok());
-    // 1 share's reward is 1, which is too small to send
-    check_pool_data(&pool_id, &alice, 999_999_999_999, 1 * DOLLARS, 1 * DOLLARS, 999_999_999_999, 0);
-
-    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_balance);
-    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &pool_account), 1 * DOLLARS);
-
+    assert!(RPM::add_share(&alice, pool_id, 1_000_000_000_000_000_000).is_ok(), "should add shares to the pool");
+    assert!(RPM::add_share(&bob, pool_id, 1).is_ok(), "should add shares to the pool");
     run_to_block(20);
-    assert!(RPM::remove_share(&alice, pool_id, 999_999_999_999).is_ok());
-    // remove all shares, all reward should send to alice
-    check_pool_data(&pool_id, &alice, 0, 0, 0, 0, 0);
+    assert!(RPM::remove_share(&bob, pool_id, 1).is_ok());
+    // 1 share's reward is 9, which is too small to send, it's kept as the dust of bob
+    check_pool_data(&pool_id, &bob, 1_000_000_000_000_000_000, 10 * DOLLARS - 9, 0, 9);
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &bob), initial_bob);
+    let dust_event = TestEvent::reward_pool(RawEvent::DustAccumulated(bob.clone(), pool_id, CurrencyId::CLV, 9));
+    assert!(System::events().iter().any(|record| record.event == dust_event));
 
-    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_balance + 10 * DOLLARS);
-    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &pool_account), 0);
+    assert!(RPM::remove_share(&alice, pool_id, 1_000_000_000_000_000_000).is_ok());
+    // remove all shares, the rewards of alice's shares should send to alice
+    // the rounding is in favor of the pool, 1 is left in the pool
+    check_pool_data(&pool_id, &alice, 0, 1, 0, 0);
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 10 * DOLLARS - 10);
   });
 }
 
@@ -156,14 +171,13 @@ fn test_multi_account_rewards() {
     run_to_block(500);
     assert!(RPM::remove_share(&bob, pool_id, 50 * DOLLARS).is_ok(), "should remove shares to the pool");
 
-    check_pool_data(&pool_id, &alice, 0, 0, 0, 0, 0);
-    check_pool_data(&pool_id, &bob, 0, 0, 0, 0, 0);
-    check_pool_data(&pool_id, &dave, 0, 0, 0, 0, 0);
+    check_pool_data(&pool_id, &alice, 0, 0, 0, 0);
+    check_pool_data(&pool_id, &bob, 0, 0, 0, 0);
+    check_pool_data(&pool_id, &dave, 0, 0, 0, 0);
     assert_eq!(Currencies::total_balance(CurrencyId::CLV, &pool_account), 0);
     assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 150 * DOLLARS);
-    // rounding issue
-    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &bob), initial_bob + 175 * DOLLARS + 1);
-    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &dave), initial_dave + 75 * DOLLARS - 1);
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &bob), initial_bob + 175 * DOLLARS);
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &dave), initial_dave + 75 * DOLLARS);
   });
 }
 
@@ -207,9 +221,8 @@ fn test_claim_rewards() {
     let alice_balance = initial_alice + 150 * DOLLARS;
     assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), alice_balance);
     check_pool_data(&pool_id, &alice,
-                    400 * DOLLARS,
-                    600 * DOLLARS, 50 * DOLLARS,
-                    100 * DOLLARS, 150 * DOLLARS);
+                    400 * DOLLARS, 50 * DOLLARS,
+                    100 * DOLLARS, 0);
 
     run_to_block(400);
     // block rewards:
@@ -220,19 +233,857 @@ fn test_claim_rewards() {
     assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), alice_balance);
     check_pool_data(&pool_id, &alice,
                     400 * DOLLARS, //shares
-                    700 * DOLLARS, 125 * DOLLARS, // total, useable rewards
-                    100 * DOLLARS, 175 * DOLLARS); // alice shares and borrow
+                    125 * DOLLARS, // useable rewards
+                    100 * DOLLARS, 0); // alice shares and pending rewards
 
     // bob: shares: 150, rewards: 25 + 37.5
-    // borrowed: 200 + 62.5 note: 200 is bob borrowed while adding shares to the pool
     assert!(RPM::claim_rewards(&bob, &pool_id).is_ok(), "should claims rewards from the pool");
     check_pool_data(&pool_id, &bob,
     400 * DOLLARS, //shares
-    700 * DOLLARS, 625 * DOLLARS / 10, // total, useable rewards
-    150 * DOLLARS, 200 * DOLLARS + 625 * DOLLARS / 10); // bob shares and borrow
+    625 * DOLLARS / 10, // useable rewards
+    150 * DOLLARS, 0); // bob shares and pending rewards
 
     let bob_balance = initial_bob + 625 * DOLLARS / 10;
     assert_eq!(Currencies::total_balance(CurrencyId::CLV, &bob), bob_balance);
     assert_eq!(Currencies::total_balance(CurrencyId::CLV, &pool_account), 625 * DOLLARS / 10);
   });
 }
+
+#[test]
+fn test_stake_and_unstake() {
+  let pool_id = PoolId::Stake(CurrencyId::DOT);
+  let alice = AccountId::from(ALICE);
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_clv = Currencies::total_balance(CurrencyId::CLV, &alice);
+    let initial_dot = Currencies::free_balance(CurrencyId::DOT, &alice);
+    run_to_block(10);
+    assert_ok!(RPM::stake(Origin::signed(alice.clone()), pool_id, 100 * DOLLARS));
+    assert_eq!(Currencies::reserved_balance(CurrencyId::DOT, &alice), 100 * DOLLARS);
+    assert_eq!(Currencies::free_balance(CurrencyId::DOT, &alice), initial_dot - 100 * DOLLARS);
+    assert_eq!(RPM::get_account_shares(&alice, &pool_id), 100 * DOLLARS);
+
+    let staked_event = TestEvent::reward_pool(RawEvent::Staked(alice.clone(), pool_id, 100 * DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == staked_event));
+
+    run_to_block(20);
+    assert_noop!(RPM::unstake(Origin::signed(alice.clone()), pool_id, 101 * DOLLARS),
+                 Error::<TestRuntime>::InsufficientShares);
+    assert_ok!(RPM::unstake(Origin::signed(alice.clone()), pool_id, 100 * DOLLARS));
+    assert_eq!(Currencies::reserved_balance(CurrencyId::DOT, &alice), 0);
+    assert_eq!(Currencies::free_balance(CurrencyId::DOT, &alice), initial_dot);
+    assert_eq!(RPM::get_account_shares(&alice, &pool_id), 0);
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_clv + 10 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_stake_invalid_pool() {
+  let alice = AccountId::from(ALICE);
+
+  ExtBuilder::default().build().execute_with(|| {
+    run_to_block(10);
+    assert_noop!(RPM::stake(Origin::signed(alice.clone()), PoolId::Swap(1), 100 * DOLLARS),
+                 Error::<TestRuntime>::PoolNotStakeable);
+    assert_noop!(RPM::stake(Origin::signed(alice.clone()), PoolId::Stake(CurrencyId::DOT), 0),
+                 Error::<TestRuntime>::InvalidAmount);
+  });
+}
+
+#[test]
+fn test_claim_extrinsics() {
+  let dot_pool = PoolId::Stake(CurrencyId::DOT);
+  let cusdt_pool = PoolId::Stake(CurrencyId::CUSDT);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_alice = Currencies::total_balance(CurrencyId::CLV, &alice);
+    let initial_bob = Currencies::total_balance(CurrencyId::CLV, &bob);
+    run_to_block(10);
+    assert_ok!(RPM::stake(Origin::signed(alice.clone()), dot_pool, 100 * DOLLARS));
+    assert_ok!(RPM::stake(Origin::signed(alice.clone()), cusdt_pool, 100 * DOLLARS));
+    assert_ok!(RPM::stake(Origin::signed(bob.clone()), dot_pool, 100 * DOLLARS));
+
+    run_to_block(20);
+    // alice: 5 from dot pool
+    assert_ok!(RPM::claim(Origin::signed(alice.clone()), dot_pool));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 5 * DOLLARS);
+
+    run_to_block(30);
+    // alice: 5 from dot pool, 20 from cusdt pool
+    assert_ok!(RPM::claim_all(Origin::signed(alice.clone()), vec![dot_pool, cusdt_pool]));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 30 * DOLLARS);
+
+    // bob: 10 from dot pool
+    assert_ok!(RPM::claim_all(Origin::signed(bob.clone()), vec![dot_pool, cusdt_pool]));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &bob), initial_bob + 10 * DOLLARS);
+
+    let claimed_event = TestEvent::reward_pool(RawEvent::RewardClaimed(bob.clone(), dot_pool, CurrencyId::CLV, 10 * DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == claimed_event));
+  });
+}
+
+#[test]
+fn test_claim_all_max_pools() {
+  let alice = AccountId::from(ALICE);
+
+  let dot_pool = PoolId::Stake(CurrencyId::DOT);
+  let cusdt_pool = PoolId::Stake(CurrencyId::CUSDT);
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_alice = Currencies::total_balance(CurrencyId::CLV, &alice);
+    run_to_block(10);
+    assert_ok!(RPM::stake(Origin::signed(alice.clone()), dot_pool, 100 * DOLLARS));
+    assert_ok!(RPM::stake(Origin::signed(alice.clone()), cusdt_pool, 100 * DOLLARS));
+    // the pools of the other accounts don't count in the max pools
+    for i in 0..MaxClaimPools::get() {
+      assert_ok!(RPM::add_share(&AccountId::from(BOB), PoolId::Swap(i as u64), 100));
+    }
+
+    run_to_block(20);
+    let pools = vec![dot_pool; MaxClaimPools::get() as usize + 1];
+    assert_noop!(RPM::claim_all(Origin::signed(alice.clone()), pools), Error::<TestRuntime>::TooManyPools);
+    assert_ok!(RPM::claim_all(Origin::signed(alice.clone()), vec![dot_pool, cusdt_pool]));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 20 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_reward_in_other_currency() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let pool_account = RPM::sub_account_id(pool_id.clone());
+
+  ExtBuilder::default().reward_currencies(vec![CurrencyId::CETH]).build().execute_with(|| {
+    let initial_clv = Currencies::total_balance(CurrencyId::CLV, &alice);
+    let initial_ceth = Currencies::total_balance(CurrencyId::CETH, &alice);
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 100));
+    run_to_block(20);
+    assert_ok!(RPM::update_pool_reward(&pool_id));
+    check_pool_currency_data(&pool_id, &alice, CurrencyId::CETH, 100, 10 * DOLLARS, 100, 10 * DOLLARS);
+    assert!(RPM::get_pool_info(&pool_id).rewards.get(&CurrencyId::CLV).is_none());
+    assert_eq!(Currencies::total_balance(CurrencyId::CETH, &pool_account), 10 * DOLLARS);
+    assert_eq!(RPM::get_all_pools(), vec![(pool_id, 100, vec![(CurrencyId::CETH, 10 * DOLLARS)])]);
+
+    assert_ok!(RPM::remove_share(&alice, pool_id, 100));
+    assert_eq!(Currencies::total_balance(CurrencyId::CETH, &alice), initial_ceth + 10 * DOLLARS);
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_clv);
+  });
+}
+
+#[test]
+fn test_multiple_reward_currencies() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+  let pool_account = RPM::sub_account_id(pool_id.clone());
+
+  //block 10        20          30
+  //       |---------|-----------|
+  //  clv: alice     alice(1/2)
+  //                 bob(1/2)
+  //  cusdt:         alice(1/2)
+  //                 bob(1/2)
+  // rewards:
+  //  alice: clv 10 + 5 = 15, cusdt 5
+  //  bob: clv 5, cusdt 5
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_clv = Currencies::total_balance(CurrencyId::CLV, &bob);
+    let initial_cusdt = Currencies::total_balance(CurrencyId::CUSDT, &bob);
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 100));
+
+    run_to_block(20);
+    assert_ok!(RPM::update_pool_reward(&pool_id));
+    // the pool is co-incentivized by cusdt from now on
+    set_reward_currencies(vec![CurrencyId::CLV, CurrencyId::CUSDT]);
+    assert_ok!(RPM::add_share(&bob, pool_id, 100));
+    // bob doesn't own the clv rewards accrued before
+    check_pool_currency_data(&pool_id, &bob, CurrencyId::CLV, 200, 10 * DOLLARS, 100, 0);
+    check_pool_currency_data(&pool_id, &bob, CurrencyId::CUSDT, 200, 0, 100, 0);
+
+    run_to_block(30);
+    assert_eq!(RPM::get_accumlated_rewards(&alice, &pool_id),
+               vec![(CurrencyId::CLV, 15 * DOLLARS), (CurrencyId::CUSDT, 5 * DOLLARS)]);
+    assert_eq!(RPM::claim_rewards(&alice, &pool_id),
+               Ok(vec![(CurrencyId::CLV, 15 * DOLLARS), (CurrencyId::CUSDT, 5 * DOLLARS)]));
+    check_pool_currency_data(&pool_id, &alice, CurrencyId::CLV, 200, 5 * DOLLARS, 100, 0);
+    check_pool_currency_data(&pool_id, &alice, CurrencyId::CUSDT, 200, 5 * DOLLARS, 100, 0);
+
+    assert_ok!(RPM::remove_share(&bob, pool_id, 100));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &bob), initial_clv + 5 * DOLLARS);
+    assert_eq!(Currencies::total_balance(CurrencyId::CUSDT, &bob), initial_cusdt + 5 * DOLLARS);
+    check_pool_currency_data(&pool_id, &bob, CurrencyId::CLV, 100, 0, 0, 0);
+    check_pool_currency_data(&pool_id, &bob, CurrencyId::CUSDT, 100, 0, 0, 0);
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &pool_account), 0);
+    assert_eq!(Currencies::total_balance(CurrencyId::CUSDT, &pool_account), 0);
+  });
+}
+
+#[test]
+fn test_migrate_to_v2() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+
+  ExtBuilder::default().build().execute_with(|| {
+    run_to_block(30);
+    // alice: 100 shares, bob: 100 shares with 10 borrowed
+    let old_pool = migrations::OldPoolInfo::<Share, Balance, u64> {
+      total_shares: 200,
+      total_rewards: 30 * DOLLARS,
+      total_rewards_useable: 20 * DOLLARS,
+      last_update_block: 30,
+    };
+    unhashed::put(&<Pools<TestRuntime>>::hashed_key_for(pool_id), &old_pool);
+
+    let old_alice = migrations::OldPoolAccountInfo::<Share, Balance> { shares: 100, borrowed_amount: 0 };
+    unhashed::put(&<PoolAccountData<TestRuntime>>::hashed_key_for(pool_id, &alice), &old_alice);
+
+    let old_bob = migrations::OldPoolAccountInfo::<Share, Balance> { shares: 100, borrowed_amount: 10 * DOLLARS };
+    unhashed::put(&<PoolAccountData<TestRuntime>>::hashed_key_for(pool_id, &bob), &old_bob);
+
+    StorageVersion::put(Releases::V1_0_0);
+    RPM::on_runtime_upgrade();
+    assert_eq!(RPM::storage_version(), Releases::V2_0_0);
+
+    // the pending rewards are kept
+    check_pool_data(&pool_id, &alice, 200, 20 * DOLLARS, 100, 15 * DOLLARS);
+    check_pool_data(&pool_id, &bob, 200, 20 * DOLLARS, 100, 5 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_no_migration_on_new_chain() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+
+  ExtBuilder::default().build().execute_with(|| {
+    // the genesis starts with the latest version
+    assert_eq!(RPM::storage_version(), Releases::V2_0_0);
+
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 100));
+    run_to_block(20);
+    assert_ok!(RPM::add_share(&bob, pool_id, 100));
+    let pool_info = RPM::get_pool(&pool_id);
+    let alice_info = RPM::pool_account_data(&pool_id, &alice);
+    let bob_info = RPM::pool_account_data(&pool_id, &bob);
+    assert!(!pool_info.rewards.is_empty());
+
+    // the pools of the latest version are not migrated again
+    RPM::on_runtime_upgrade();
+    assert_eq!(RPM::storage_version(), Releases::V2_0_0);
+    assert_eq!(RPM::get_pool(&pool_id), pool_info);
+    assert_eq!(RPM::pool_account_data(&pool_id, &alice), alice_info);
+    assert_eq!(RPM::pool_account_data(&pool_id, &bob), bob_info);
+  });
+}
+
+#[test]
+fn test_lock_shares() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+
+  //block 10        30          40
+  //       |---------|-----------|
+  //     alice(2x, 1/2)  alice(1/3)
+  //     bob(1/2)        bob(2/3)
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_alice = Currencies::total_balance(CurrencyId::CLV, &alice);
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 100));
+    assert_ok!(RPM::add_share(&bob, pool_id, 200));
+    assert_ok!(RPM::lock(Origin::signed(alice.clone()), pool_id, 100, 20));
+
+    // the boost shares accrue rewards, but they're not staked shares
+    assert_eq!(RPM::get_account_shares(&alice, &pool_id), 100);
+    assert_eq!(RPM::get_pool_account_info(&pool_id, &alice).shares, 200);
+    assert_eq!(RPM::get_pool_info(&pool_id).total_shares, 400);
+    let locked_event = TestEvent::reward_pool(RawEvent::SharesLocked(alice.clone(), pool_id, 100, 100, 30));
+    assert!(System::events().iter().any(|record| record.event == locked_event));
+
+    run_to_block(20);
+    assert_noop!(RPM::remove_share(&alice, pool_id, 1), Error::<TestRuntime>::SharesStillLocked);
+
+    run_to_block(30);
+    check_pool_data(&pool_id, &alice, 400, 0, 200, 10 * DOLLARS);
+    // the lock is expired, the boost shares are removed while claiming
+    assert_ok!(RPM::claim_rewards(&alice, &pool_id));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 10 * DOLLARS);
+    assert!(RPM::pool_account_locks(&pool_id, &alice).is_empty());
+    check_pool_data(&pool_id, &alice, 300, 10 * DOLLARS, 100, 0);
+    check_pool_data(&pool_id, &bob, 300, 10 * DOLLARS, 200, 10 * DOLLARS);
+
+    run_to_block(40);
+    assert_ok!(RPM::remove_share(&alice, pool_id, 100));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 10 * DOLLARS + 10 * DOLLARS / 3);
+    assert_eq!(RPM::get_account_shares(&alice, &pool_id), 0);
+  });
+}
+
+#[test]
+fn test_release_expired_locks() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+
+  //block 10        30          40
+  //       |---------|-----------|
+  //     alice(2x, 1/2)  alice(1/3)
+  //     bob(1/2)        bob(2/3)
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_alice = Currencies::total_balance(CurrencyId::CLV, &alice);
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 100));
+    assert_ok!(RPM::add_share(&bob, pool_id, 200));
+    assert_ok!(RPM::lock(Origin::signed(alice.clone()), pool_id, 100, 20));
+
+    run_to_block(20);
+    assert_noop!(RPM::release_locks(Origin::signed(bob.clone()), alice.clone(), pool_id),
+                 Error::<TestRuntime>::NoExpiredLocks);
+
+    run_to_block(30);
+    // anyone could release the expired lock, the rewards are paid to alice
+    assert_ok!(RPM::release_locks(Origin::signed(bob.clone()), alice.clone(), pool_id));
+    assert!(RPM::pool_account_locks(&pool_id, &alice).is_empty());
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 10 * DOLLARS);
+    let unlocked_event = TestEvent::reward_pool(RawEvent::SharesUnlocked(alice.clone(), pool_id, 100));
+    assert!(System::events().iter().any(|record| record.event == unlocked_event));
+    assert_noop!(RPM::release_locks(Origin::signed(bob.clone()), alice.clone(), pool_id),
+                 Error::<TestRuntime>::NoExpiredLocks);
+
+    // the boost shares stop accruing rewards after the lock is released
+    run_to_block(40);
+    check_pool_data(&pool_id, &alice, 300, 10 * DOLLARS, 100, 10 * DOLLARS / 3);
+    check_pool_data(&pool_id, &bob, 300, 10 * DOLLARS, 200, 10 * DOLLARS + 20 * DOLLARS / 3);
+  });
+}
+
+#[test]
+fn test_lock_shares_failed() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+
+  ExtBuilder::default().build().execute_with(|| {
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 100));
+    assert_noop!(RPM::lock(Origin::signed(alice.clone()), pool_id, 100, 15),
+                 Error::<TestRuntime>::InvalidLockPeriod);
+    assert_noop!(RPM::lock(Origin::signed(alice.clone()), pool_id, 101, 10),
+                 Error::<TestRuntime>::InsufficientShares);
+
+    assert_ok!(RPM::lock(Origin::signed(alice.clone()), pool_id, 40, 10));
+    assert_ok!(RPM::lock(Origin::signed(alice.clone()), pool_id, 40, 20));
+    assert_noop!(RPM::lock(Origin::signed(alice.clone()), pool_id, 20, 10),
+                 Error::<TestRuntime>::TooManyLocks);
+    // 1.5x for 40 shares, 2x for 40 shares
+    assert_eq!(RPM::get_pool_account_info(&pool_id, &alice).shares, 100 + 20 + 40);
+    assert_noop!(RPM::remove_share(&alice, pool_id, 21), Error::<TestRuntime>::SharesStillLocked);
+    assert_ok!(RPM::remove_share(&alice, pool_id, 20));
+  });
+}
+
+#[test]
+fn test_unbonding_shares() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_alice = Currencies::total_balance(CurrencyId::CLV, &alice);
+    run_to_block(10);
+    assert_noop!(RPM::set_unbonding_period(Origin::signed(alice.clone()), pool_id, 10), DispatchError::BadOrigin);
+    assert_ok!(RPM::set_unbonding_period(Origin::root(), pool_id, 10));
+    let updated_event = TestEvent::reward_pool(RawEvent::UnbondingPeriodUpdated(pool_id, 10));
+    assert!(System::events().iter().any(|record| record.event == updated_event));
+
+    assert_ok!(RPM::add_share(&alice, pool_id, 200));
+    assert_ok!(RPM::add_share(&bob, pool_id, 200));
+
+    run_to_block(20);
+    // the rewards are settled but not paid, the unbonding shares stop accruing rewards
+    assert_ok!(RPM::remove_share(&alice, pool_id, 100));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice);
+    assert_eq!(RPM::get_account_shares(&alice, &pool_id), 100);
+    assert_eq!(RPM::get_pool_info(&pool_id).total_shares, 300);
+    assert_eq!(RPM::get_unbondings(&alice, &pool_id), vec![(100, vec![(CurrencyId::CLV, 5 * DOLLARS)], 30)]);
+    let unbonding_event = TestEvent::reward_pool(RawEvent::SharesUnbonding(alice.clone(), pool_id, 100, 30));
+    assert!(System::events().iter().any(|record| record.event == unbonding_event));
+
+    run_to_block(26);
+    // alice: 6 * 1 / 3 for the remaining 100 shares
+    assert_ok!(RPM::remove_share(&alice, pool_id, 100));
+    assert_eq!(RPM::get_unbondings(&alice, &pool_id).len(), 2);
+    assert_eq!(RPM::get_accumlated_rewards(&bob, &pool_id), vec![(CurrencyId::CLV, 9 * DOLLARS)]);
+
+    run_to_block(29);
+    assert_eq!(<RPM as RewardPoolOps<_, _, _, _, _, _>>::withdraw_unbonded(&alice, pool_id), Ok(0));
+
+    run_to_block(30);
+    assert_eq!(<RPM as RewardPoolOps<_, _, _, _, _, _>>::withdraw_unbonded(&alice, pool_id), Ok(100));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 5 * DOLLARS);
+    assert_eq!(RPM::get_unbondings(&alice, &pool_id), vec![(100, vec![(CurrencyId::CLV, 2 * DOLLARS)], 36)]);
+
+    run_to_block(36);
+    assert_eq!(<RPM as RewardPoolOps<_, _, _, _, _, _>>::withdraw_unbonded(&alice, pool_id), Ok(100));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 7 * DOLLARS);
+    assert!(RPM::get_unbondings(&alice, &pool_id).is_empty());
+    let withdrawn_event = TestEvent::reward_pool(RawEvent::SharesWithdrawn(alice.clone(), pool_id, 100));
+    assert!(System::events().iter().any(|record| record.event == withdrawn_event));
+  });
+}
+
+#[test]
+fn test_unbonding_staked_currency() {
+  let pool_id = PoolId::Stake(CurrencyId::DOT);
+  let alice = AccountId::from(ALICE);
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_dot = Currencies::free_balance(CurrencyId::DOT, &alice);
+    assert_ok!(RPM::set_unbonding_period(Origin::root(), pool_id, 10));
+
+    run_to_block(10);
+    assert_ok!(RPM::stake(Origin::signed(alice.clone()), pool_id, 100 * DOLLARS));
+
+    run_to_block(20);
+    assert_ok!(RPM::unstake(Origin::signed(alice.clone()), pool_id, 30 * DOLLARS));
+    assert_ok!(RPM::unstake(Origin::signed(alice.clone()), pool_id, 30 * DOLLARS));
+    assert_noop!(RPM::unstake(Origin::signed(alice.clone()), pool_id, 30 * DOLLARS),
+                 Error::<TestRuntime>::TooManyUnbondingChunks);
+    // the staked currency is kept reserved while unbonding
+    assert_eq!(Currencies::reserved_balance(CurrencyId::DOT, &alice), 100 * DOLLARS);
+
+    run_to_block(30);
+    assert_ok!(RPM::withdraw_unbonded(Origin::signed(alice.clone()), pool_id));
+    assert_eq!(Currencies::reserved_balance(CurrencyId::DOT, &alice), 40 * DOLLARS);
+    assert_eq!(Currencies::free_balance(CurrencyId::DOT, &alice), initial_dot - 40 * DOLLARS);
+
+    // the removed shares are released immediately without unbonding period
+    assert_ok!(RPM::set_unbonding_period(Origin::root(), pool_id, 0));
+    assert_ok!(RPM::unstake(Origin::signed(alice.clone()), pool_id, 40 * DOLLARS));
+    assert_eq!(Currencies::reserved_balance(CurrencyId::DOT, &alice), 0);
+    assert_eq!(Currencies::free_balance(CurrencyId::DOT, &alice), initial_dot);
+  });
+}
+
+#[test]
+fn test_vesting_rewards() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let config = VestingConfig {
+    vesting_ratio: Ratio::saturating_from_rational(1, 2),
+    vesting_period: 10,
+    forfeit_penalty: Ratio::saturating_from_rational(1, 2),
+  };
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_alice = Currencies::total_balance(CurrencyId::CLV, &alice);
+    let invalid_config = VestingConfig { vesting_ratio: Ratio::saturating_from_rational(3, 2), ..config.clone() };
+    assert_noop!(RPM::set_vesting_config(Origin::root(), pool_id, Some(invalid_config)),
+                 Error::<TestRuntime>::InvalidVestingConfig);
+    let invalid_config = VestingConfig { vesting_period: 0, ..config.clone() };
+    assert_noop!(RPM::set_vesting_config(Origin::root(), pool_id, Some(invalid_config)),
+                 Error::<TestRuntime>::InvalidVestingConfig);
+    assert_ok!(RPM::set_vesting_config(Origin::root(), pool_id, Some(config)));
+
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 100));
+
+    run_to_block(20);
+    // 5 liquid, 5 vesting from block 20 to block 30
+    assert_eq!(RPM::claim_rewards(&alice, &pool_id), Ok(vec![(CurrencyId::CLV, 10 * DOLLARS)]));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 5 * DOLLARS);
+    let vesting_event = TestEvent::reward_pool(RawEvent::RewardVesting(alice.clone(), pool_id, CurrencyId::CLV, 5 * DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == vesting_event));
+
+    run_to_block(25);
+    assert_ok!(RPM::vest(Origin::signed(alice.clone()), pool_id));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 75 * DOLLARS / 10);
+    let schedule = RPM::vesting_schedules(&pool_id, &alice).get(&CurrencyId::CLV).cloned().unwrap();
+    assert_eq!(schedule, VestingSchedule { locked: 25 * DOLLARS / 10, start_block: 25, end_block: 30 });
+
+    // 2.5 liquid, 2.5 vesting in 10 blocks, merged with the 2.5 locked until block 30,
+    // the merged schedule ends at the weighted block 25 + (2.5 * 5 + 2.5 * 10) / 5 = 32.5, rounded up
+    assert_eq!(RPM::claim_rewards(&alice, &pool_id), Ok(vec![(CurrencyId::CLV, 5 * DOLLARS)]));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 10 * DOLLARS);
+    let schedule = RPM::vesting_schedules(&pool_id, &alice).get(&CurrencyId::CLV).cloned().unwrap();
+    assert_eq!(schedule, VestingSchedule { locked: 5 * DOLLARS, start_block: 25, end_block: 33 });
+
+    run_to_block(29);
+    // the locked rewards are not restarted: 5 * 4 / 8 vested
+    assert_ok!(RPM::vest(Origin::signed(alice.clone()), pool_id));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 125 * DOLLARS / 10);
+    let schedule = RPM::vesting_schedules(&pool_id, &alice).get(&CurrencyId::CLV).cloned().unwrap();
+    assert_eq!(schedule, VestingSchedule { locked: 25 * DOLLARS / 10, start_block: 29, end_block: 33 });
+
+    run_to_block(50);
+    assert_ok!(RPM::vest(Origin::signed(alice.clone()), pool_id));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 15 * DOLLARS);
+    assert!(RPM::vesting_schedules(&pool_id, &alice).is_empty());
+  });
+}
+
+#[test]
+fn test_forfeit_vesting() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+  let config = VestingConfig {
+    vesting_ratio: Ratio::saturating_from_rational(1, 2),
+    vesting_period: 10,
+    forfeit_penalty: Ratio::saturating_from_rational(1, 2),
+  };
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_alice = Currencies::total_balance(CurrencyId::CLV, &alice);
+    assert_ok!(RPM::set_vesting_config(Origin::root(), pool_id, Some(config)));
+
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 100));
+    assert_ok!(RPM::add_share(&bob, pool_id, 100));
+
+    run_to_block(20);
+    assert_ok!(RPM::claim_rewards(&alice, &pool_id));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 25 * DOLLARS / 10);
+
+    // 1.25 paid, 1.25 redistributed to alice and bob
+    assert_ok!(RPM::forfeit_vesting(Origin::signed(alice.clone()), pool_id));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 375 * DOLLARS / 100);
+    assert!(RPM::vesting_schedules(&pool_id, &alice).is_empty());
+    let forfeited_event = TestEvent::reward_pool(
+      RawEvent::VestingForfeited(alice.clone(), pool_id, CurrencyId::CLV, 125 * DOLLARS / 100, 125 * DOLLARS / 100));
+    assert!(System::events().iter().any(|record| record.event == forfeited_event));
+
+    assert_eq!(RPM::get_accumlated_rewards(&alice, &pool_id), vec![(CurrencyId::CLV, 625 * DOLLARS / 1000)]);
+    assert_eq!(RPM::get_accumlated_rewards(&bob, &pool_id), vec![(CurrencyId::CLV, 5625 * DOLLARS / 1000)]);
+  });
+}
+
+#[test]
+fn test_pool_schedule() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+
+  ExtBuilder::default().build().execute_with(|| {
+    run_to_block(5);
+    assert_noop!(RPM::create_pool(Origin::signed(alice.clone()), pool_id, 20, Some(40)), DispatchError::BadOrigin);
+    assert_noop!(RPM::create_pool(Origin::root(), pool_id, 20, Some(20)), Error::<TestRuntime>::InvalidPoolSchedule);
+    assert_ok!(RPM::create_pool(Origin::root(), pool_id, 20, Some(40)));
+    assert_noop!(RPM::create_pool(Origin::root(), pool_id, 20, None), Error::<TestRuntime>::PoolAlreadyExists);
+    let created_event = TestEvent::reward_pool(RawEvent::PoolCreated(pool_id, 20, Some(40)));
+    assert!(System::events().iter().any(|record| record.event == created_event));
+
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 100));
+
+    run_to_block(20);
+    assert!(RPM::get_accumlated_rewards(&alice, &pool_id).is_empty());
+
+    run_to_block(30);
+    check_pool_data(&pool_id, &alice, 100, 0, 100, 10 * DOLLARS);
+    assert_ok!(RPM::pause_pool(Origin::root(), pool_id));
+    assert_noop!(RPM::pause_pool(Origin::root(), pool_id), Error::<TestRuntime>::InvalidPoolStatus);
+
+    run_to_block(35);
+    check_pool_data(&pool_id, &alice, 100, 10 * DOLLARS, 100, 10 * DOLLARS);
+    assert_ok!(RPM::resume_pool(Origin::root(), pool_id));
+
+    run_to_block(50);
+    // 10 before pausing, 5 after resuming until the end block
+    check_pool_data(&pool_id, &alice, 100, 10 * DOLLARS, 100, 15 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_close_pool() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_alice = Currencies::total_balance(CurrencyId::CLV, &alice);
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 100));
+
+    run_to_block(20);
+    assert_ok!(RPM::close_pool(Origin::root(), pool_id));
+    assert_noop!(RPM::close_pool(Origin::root(), pool_id), Error::<TestRuntime>::InvalidPoolStatus);
+    assert_noop!(RPM::resume_pool(Origin::root(), pool_id), Error::<TestRuntime>::InvalidPoolStatus);
+    let closed_event = TestEvent::reward_pool(RawEvent::PoolClosed(pool_id));
+    assert!(System::events().iter().any(|record| record.event == closed_event));
+
+    run_to_block(30);
+    assert_noop!(RPM::add_share(&alice, pool_id, 100), Error::<TestRuntime>::PoolClosed);
+    check_pool_data(&pool_id, &alice, 100, 10 * DOLLARS, 100, 10 * DOLLARS);
+    assert_ok!(RPM::remove_share(&alice, pool_id, 100));
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_alice + 10 * DOLLARS);
+  });
+}
+
+#[test]
+fn test_emergency_withdraw() {
+  let pool_id = PoolId::Stake(CurrencyId::DOT);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_clv = Currencies::total_balance(CurrencyId::CLV, &alice);
+    let initial_dot = Currencies::free_balance(CurrencyId::DOT, &alice);
+    run_to_block(10);
+    assert_ok!(RPM::stake(Origin::signed(alice.clone()), pool_id, 100 * DOLLARS));
+    assert_ok!(RPM::stake(Origin::signed(bob.clone()), pool_id, 100 * DOLLARS));
+    assert_ok!(RPM::lock(Origin::signed(alice.clone()), pool_id, 50 * DOLLARS, 20));
+
+    run_to_block(20);
+    assert_noop!(RPM::emergency_withdraw(Origin::signed(alice.clone()), pool_id),
+                 Error::<TestRuntime>::EmergencyWithdrawDisabled);
+    assert_noop!(RPM::set_emergency_withdraw(Origin::signed(alice.clone()), pool_id, true), DispatchError::BadOrigin);
+    assert_ok!(RPM::set_emergency_withdraw(Origin::root(), pool_id, true));
+
+    // the locked and boost shares are removed as well, the rewards are forfeited
+    assert_ok!(RPM::emergency_withdraw(Origin::signed(alice.clone()), pool_id));
+    assert_eq!(Currencies::reserved_balance(CurrencyId::DOT, &alice), 0);
+    assert_eq!(Currencies::free_balance(CurrencyId::DOT, &alice), initial_dot);
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &alice), initial_clv);
+    assert_eq!(RPM::get_account_shares(&alice, &pool_id), 0);
+    assert!(RPM::pool_account_locks(&pool_id, &alice).is_empty());
+    assert_eq!(RPM::get_pool_info(&pool_id).total_shares, 100 * DOLLARS);
+    let withdrawn_event = TestEvent::reward_pool(RawEvent::EmergencyWithdrawn(alice.clone(), pool_id, 100 * DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == withdrawn_event));
+
+    // the pool rewards are not updated by the withdraw, bob receives all the rewards since block 10
+    run_to_block(30);
+    assert_eq!(RPM::get_accumlated_rewards(&bob, &pool_id), vec![(CurrencyId::CLV, 20 * DOLLARS)]);
+  });
+}
+
+#[test]
+fn test_emergency_withdraw_forfeited_rewards() {
+  let pool_id = PoolId::Stake(CurrencyId::DOT);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_dot = Currencies::free_balance(CurrencyId::DOT, &alice);
+    run_to_block(10);
+    assert_ok!(RPM::set_unbonding_period(Origin::root(), pool_id, 10));
+    assert_ok!(RPM::stake(Origin::signed(alice.clone()), pool_id, 100 * DOLLARS));
+    assert_ok!(RPM::stake(Origin::signed(bob.clone()), pool_id, 100 * DOLLARS));
+
+    run_to_block(20);
+    // 50 shares unbonding with 5 rewards
+    assert_ok!(RPM::unstake(Origin::signed(alice.clone()), pool_id, 50 * DOLLARS));
+
+    run_to_block(26);
+    // alice: 2 pending, bob: 4 claimed
+    assert_ok!(RPM::claim_rewards(&bob, &pool_id));
+    assert_ok!(RPM::set_emergency_withdraw(Origin::root(), pool_id, true));
+    assert_ok!(RPM::set_emergency_withdraw(Origin::root(), PoolId::Swap(1), true));
+    assert_ok!(RPM::add_share(&alice, PoolId::Swap(1), 100));
+    assert_noop!(RPM::emergency_withdraw(Origin::signed(alice.clone()), PoolId::Swap(1)),
+                 Error::<TestRuntime>::PoolNotStakeable);
+
+    // the unbonding shares are withdrawn, the unbonding and pending rewards are redistributed to bob
+    assert_ok!(RPM::emergency_withdraw(Origin::signed(alice.clone()), pool_id));
+    assert_eq!(Currencies::reserved_balance(CurrencyId::DOT, &alice), 0);
+    assert_eq!(Currencies::free_balance(CurrencyId::DOT, &alice), initial_dot);
+    assert!(RPM::unbondings(&pool_id, &alice).is_empty());
+    let withdrawn_event = TestEvent::reward_pool(RawEvent::EmergencyWithdrawn(alice.clone(), pool_id, 100 * DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == withdrawn_event));
+    let forfeited_event = TestEvent::reward_pool(
+      RawEvent::EmergencyRewardForfeited(alice.clone(), pool_id, CurrencyId::CLV, 7 * DOLLARS));
+    assert!(System::events().iter().any(|record| record.event == forfeited_event));
+    assert_eq!(RPM::get_accumlated_rewards(&bob, &pool_id), vec![(CurrencyId::CLV, 7 * DOLLARS)]);
+    assert!(RPM::check_invariants().is_empty());
+
+    run_to_block(30);
+    assert_eq!(RPM::get_accumlated_rewards(&bob, &pool_id), vec![(CurrencyId::CLV, 11 * DOLLARS)]);
+  });
+}
+
+#[test]
+fn test_dust_carried_to_next_claim() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_bob = Currencies::total_balance(CurrencyId::CLV, &bob);
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 1_000_000_000_000_000_000));
+    assert_ok!(RPM::add_share(&bob, pool_id, 10));
+
+    run_to_block(11);
+    // 10 * 1e18 / (1e18 + 10) = 9, which is kept as dust
+    assert_eq!(RPM::claim_rewards(&bob, &pool_id), Ok(vec![]));
+    assert_eq!(RPM::pending_dust(&pool_id, &bob).get(&CurrencyId::CLV), Some(&9));
+    assert_eq!(RPM::get_accumlated_rewards(&bob, &pool_id), vec![(CurrencyId::CLV, 9)]);
+
+    run_to_block(70);
+    // the dust is sent with the rewards of the removal
+    let rewards = RPM::get_accumlated_rewards(&bob, &pool_id);
+    assert_ok!(RPM::remove_share(&bob, pool_id, 10));
+    assert!(RPM::pending_dust(&pool_id, &bob).is_empty());
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &bob), initial_bob + rewards[0].1);
+    assert!(rewards[0].1 >= 500);
+  });
+}
+
+#[test]
+fn test_dust_paid_without_shares() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+
+  ExtBuilder::default().build().execute_with(|| {
+    let initial_bob = Currencies::total_balance(CurrencyId::CLV, &bob);
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 1_000_000_000_000_000_000));
+    assert_ok!(RPM::add_share(&bob, pool_id, 1));
+
+    run_to_block(20);
+    // the reward of the only share is kept as dust after all the shares are removed
+    assert_ok!(RPM::remove_share(&bob, pool_id, 1));
+    assert_eq!(RPM::pending_dust(&pool_id, &bob).get(&CurrencyId::CLV), Some(&9));
+    assert_eq!(RPM::get_accumlated_rewards(&bob, &pool_id), vec![(CurrencyId::CLV, 9)]);
+
+    run_to_block(30);
+    // no more rewards for bob, the dust is sent even it's less than the existential reward
+    assert_eq!(RPM::claim_rewards(&bob, &pool_id), Ok(vec![(CurrencyId::CLV, 9)]));
+    assert!(RPM::pending_dust(&pool_id, &bob).is_empty());
+    assert!(RPM::get_accumlated_rewards(&bob, &pool_id).is_empty());
+    assert_eq!(Currencies::total_balance(CurrencyId::CLV, &bob), initial_bob + 9);
+  });
+}
+
+#[test]
+fn test_share_and_reward_events() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+
+  ExtBuilder::default().build().execute_with(|| {
+    run_to_block(10);
+    assert_ok!(RPM::add_share(&alice, pool_id, 100));
+    assert_ok!(RPM::add_share(&bob, pool_id, 300));
+    let created_event = TestEvent::reward_pool(RawEvent::PoolCreated(pool_id, 10, None));
+    assert_eq!(System::events().iter().filter(|record| record.event == created_event).count(), 1);
+    let added_event = TestEvent::reward_pool(RawEvent::ShareAdded(bob.clone(), pool_id, 300, 300, 400));
+    assert!(System::events().iter().any(|record| record.event == added_event));
+
+    run_to_block(20);
+    assert_ok!(RPM::remove_share(&alice, pool_id, 40));
+    // 10 rewards for 400 shares
+    let acc_reward_per_share = U256::from(10 * DOLLARS / 400) * U256::from(ACC_REWARD_PRECISION);
+    let accrued_event = TestEvent::reward_pool(
+      RawEvent::RewardAccrued(pool_id, CurrencyId::CLV, 10 * DOLLARS, acc_reward_per_share));
+    assert!(System::events().iter().any(|record| record.event == accrued_event));
+    let removed_event = TestEvent::reward_pool(RawEvent::ShareRemoved(alice.clone(), pool_id, 40, 60, 360));
+    assert!(System::events().iter().any(|record| record.event == removed_event));
+    let claimed_event = TestEvent::reward_pool(RawEvent::RewardClaimed(alice.clone(), pool_id, CurrencyId::CLV, 25 * DOLLARS / 10));
+    assert!(System::events().iter().any(|record| record.event == claimed_event));
+
+    assert_ok!(RPM::claim_rewards(&bob, &pool_id));
+    let claimed_event = TestEvent::reward_pool(RawEvent::RewardClaimed(bob.clone(), pool_id, CurrencyId::CLV, 75 * DOLLARS / 10));
+    assert!(System::events().iter().any(|record| record.event == claimed_event));
+  });
+}
+
+#[test]
+fn test_check_invariants() {
+  let pool_id = PoolId::Swap(1);
+  let alice = AccountId::from(ALICE);
+  let bob = AccountId::from(BOB);
+  let dave = AccountId::from(DAVE);
+  let pool_account = RPM::sub_account_id(pool_id);
+
+  ExtBuilder::default().build().execute_with(|| {
+    run_to_block(10);
+    assert_ok!(RPM::set_unbonding_period(Origin::root(), pool_id, 10));
+    assert_ok!(RPM::set_vesting_config(Origin::root(), pool_id, Some(VestingConfig {
+      vesting_ratio: Ratio::saturating_from_rational(1, 2),
+      vesting_period: 10,
+      forfeit_penalty: Ratio::saturating_from_rational(1, 2),
+    })));
+    assert_ok!(RPM::add_share(&alice, pool_id, 1_000_000_000_000_000_000));
+    assert_ok!(RPM::add_share(&bob, pool_id, 10));
+    assert_ok!(RPM::add_share(&dave, pool_id, 300));
+    assert_ok!(RPM::lock(Origin::signed(dave.clone()), pool_id, 100, 10));
+
+    run_to_block(11);
+    // bob has dust, alice has vesting rewards, dave has unbonding shares
+    assert_ok!(RPM::claim_rewards(&bob, &pool_id));
+    assert_ok!(RPM::claim_rewards(&alice, &pool_id));
+    run_to_block(15);
+    assert_ok!(RPM::remove_share(&dave, pool_id, 100));
+    assert!(!RPM::pending_dust(&pool_id, &bob).is_empty());
+    assert!(!RPM::vesting_schedules(&pool_id, &alice).is_empty());
+    assert!(!RPM::unbondings(&pool_id, &dave).is_empty());
+    assert_eq!(RPM::check_invariants(), vec![]);
+
+    // take away one more than the spare balance of the pool account
+    let reward_balance = Currencies::free_balance(CurrencyId::CLV, &pool_account);
+    let reward_info = RPM::get_pool_info(&pool_id).rewards.get(&CurrencyId::CLV).cloned().unwrap();
+    let vesting = RPM::vesting_schedules(&pool_id, &alice).get(&CurrencyId::CLV).unwrap().locked;
+    let unbonding = RPM::unbondings(&pool_id, &dave)[0].rewards[0].1;
+    let dust = *RPM::pending_dust(&pool_id, &bob).get(&CurrencyId::CLV).unwrap();
+    let held = reward_info.total_rewards_useable + vesting + unbonding + dust;
+    assert_ok!(Currencies::update_balance(CurrencyId::CLV, &pool_account, -((reward_balance - held + 1) as i128)));
+    assert_eq!(RPM::check_invariants(), vec![(pool_id, InvariantViolation::InsufficientRewardBalance(CurrencyId::CLV))]);
+
+    <PoolAccountData<TestRuntime>>::mutate(&pool_id, &bob, |info| info.shares += 1);
+    assert_eq!(RPM::check_invariants(), vec![
+      (pool_id, InvariantViolation::TotalSharesMismatch),
+      (pool_id, InvariantViolation::InsufficientRewardBalance(CurrencyId::CLV)),
+    ]);
+  });
+}
+
+// 5 CLV each 10 blocks, the other currencies aren't capped
+pub struct TestCap;
+impl EmissionCap<PoolId, CurrencyId, u64, Balance> for TestCap {
+  fn era_length() -> u64 {
+    10
+  }
+
+  fn max_emission(_: &PoolId, currency_id: &CurrencyId) -> Option<Balance> {
+    match currency_id {
+      CurrencyId::CLV => Some(5 * DOLLARS),
+      _ => None,
+    }
+  }
+}
+
+#[test]
+fn test_composed_reward_handlers() {
+  type Summed = (Handler, Handler, ());
+  type Capped = CappedRewards<TestRuntime, Summed, TestCap>;
+  let pool_id = PoolId::Swap(1);
+
+  ExtBuilder::default().reward_currencies(vec![DOT, CLV]).build().execute_with(|| {
+    let summed = <Summed as RewardHandler<AccountId, u64, Balance, Share, PoolId, CurrencyId>>::caculate_reward(&pool_id, &100, 0, 2);
+    assert_eq!(summed, vec![(CLV, 4 * DOLLARS), (DOT, 4 * DOLLARS)]);
+
+    let capped = |from: u64, to: u64| {
+      <Capped as RewardHandler<AccountId, u64, Balance, Share, PoolId, CurrencyId>>::caculate_reward(&pool_id, &100, from, to)
+    };
+    assert_eq!(capped(0, 2), vec![(CLV, 4 * DOLLARS), (DOT, 4 * DOLLARS)]);
+    assert_eq!(capped(2, 4), vec![(CLV, DOLLARS), (DOT, 4 * DOLLARS)]);
+    assert_eq!(capped(4, 6), vec![(DOT, 4 * DOLLARS)]);
+    assert_eq!(RPM::era_emissions(&pool_id, CLV), (0, 5 * DOLLARS));
+
+    // the blocks are split at the era boundary, the blocks of the capped era emit nothing
+    // and the cap is reset in the next era
+    assert_eq!(capped(6, 14), vec![(CLV, 5 * DOLLARS), (DOT, 16 * DOLLARS)]);
+    assert_eq!(RPM::era_emissions(&pool_id, CLV), (1, 5 * DOLLARS));
+    assert_eq!(capped(14, 22), vec![(CLV, 4 * DOLLARS), (DOT, 16 * DOLLARS)]);
+    assert_eq!(RPM::era_emissions(&pool_id, CLV), (2, 4 * DOLLARS));
+  });
+}
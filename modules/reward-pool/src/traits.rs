@@ -1,13 +1,84 @@
 //! traits for reward pool
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use primitives::Ratio;
+use sp_runtime::traits::Saturating;
+use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+
 /// Hooks to manage reward pool
-pub trait RewardHandler<AccountId, BlockNumber, Balance, Share, PoolId> {
+pub trait RewardHandler<AccountId, BlockNumber, Balance, Share, PoolId, CurrencyId> {
   /// Accumulate rewards
+  /// returns the reward amount of each reward currency,
+  /// the storage changes of the handler are reverted when it's called by the readonly queries
   fn caculate_reward(
     pool_id: &PoolId,
     shares: &Share,
     last_update_block: BlockNumber,
     now: BlockNumber,
-  ) -> Balance;
+  ) -> Vec<(CurrencyId, Balance)>;
+}
+
+/// no rewards
+impl<AccountId, BlockNumber, Balance, Share, PoolId, CurrencyId>
+  RewardHandler<AccountId, BlockNumber, Balance, Share, PoolId, CurrencyId> for () {
+  fn caculate_reward(_: &PoolId, _: &Share, _: BlockNumber, _: BlockNumber) -> Vec<(CurrencyId, Balance)> {
+    Vec::new()
+  }
+}
+
+/// the tuple of handlers sums the rewards of each handler by the reward currency
+macro_rules! impl_reward_handler_for_tuple {
+  ($($handler:ident),+) => {
+    impl<AccountId, BlockNumber, Balance, Share, PoolId, CurrencyId, $($handler),+>
+      RewardHandler<AccountId, BlockNumber, Balance, Share, PoolId, CurrencyId> for ($($handler,)+)
+    where
+      BlockNumber: Copy,
+      Balance: Saturating + Default,
+      CurrencyId: Ord,
+      $($handler: RewardHandler<AccountId, BlockNumber, Balance, Share, PoolId, CurrencyId>),+
+    {
+      fn caculate_reward(
+        pool_id: &PoolId,
+        shares: &Share,
+        last_update_block: BlockNumber,
+        now: BlockNumber,
+      ) -> Vec<(CurrencyId, Balance)> {
+        let mut rewards = BTreeMap::<CurrencyId, Balance>::new();
+        $(
+          for (currency_id, reward) in $handler::caculate_reward(pool_id, shares, last_update_block, now) {
+            let total = rewards.entry(currency_id).or_default();
+            *total = total.saturating_add(reward);
+          }
+        )+
+        rewards.into_iter().collect()
+      }
+    }
+  };
+}
+
+impl_reward_handler_for_tuple!(A, B);
+impl_reward_handler_for_tuple!(A, B, C);
+impl_reward_handler_for_tuple!(A, B, C, D);
+impl_reward_handler_for_tuple!(A, B, C, D, E);
+
+/// Emission caps of the pools, used by `CappedRewards`
+pub trait EmissionCap<PoolId, CurrencyId, BlockNumber, Balance> {
+  /// the length of an era in blocks, the emission isn't capped if it's zero
+  fn era_length() -> BlockNumber;
+
+  /// the max emission of the reward currency of the pool in an era, `None` if it's not capped
+  fn max_emission(pool_id: &PoolId, currency_id: &CurrencyId) -> Option<Balance>;
+}
+
+/// Lock periods and reward multipliers of the pools
+pub trait LockScheduleProvider<PoolId, BlockNumber> {
+  /// returns the reward multiplier of the shares locked for `lock_period` blocks,
+  /// `None` if the pool doesn't support the lock period
+  fn lock_multiplier(pool_id: &PoolId, lock_period: &BlockNumber) -> Option<Ratio>;
+}
+
+impl<PoolId, BlockNumber> LockScheduleProvider<PoolId, BlockNumber> for () {
+  fn lock_multiplier(_: &PoolId, _: &BlockNumber) -> Option<Ratio> {
+    None
+  }
 }
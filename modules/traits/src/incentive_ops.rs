@@ -4,19 +4,42 @@ use sp_runtime::{
 
 use sp_std::vec;
 
-pub struct IncentivePoolAccountInfo<Share, Balance> {
+pub struct IncentivePoolAccountInfo<CurrencyId, Share, Balance> {
   pub shares: Share,
-  pub accumlated_rewards: Balance,
+  /// accumlated rewards of each reward currency
+  pub accumlated_rewards: vec::Vec<(CurrencyId, Balance)>,
 }
 
-pub trait IncentiveOps<AccountId, CurrencyId, Share, Balance> {
+/// the pools are identified by the currency pair, both currencies of a single asset pool are the staking currency,
+/// the queries of all the pools return the pool id instead, so the stable swap pools are included
+pub trait IncentiveOps<AccountId, CurrencyId, Share, Balance, BlockNumber> {
+  /// the identifier of the incentive pools, the dex pairs, the single asset pools and the stable swap pools
+  type PoolId;
+
   fn add_share(who: &AccountId, left: &CurrencyId, right: &CurrencyId, amount: &Share) -> Result<Share, DispatchError>;
   fn remove_share(who: &AccountId, left: &CurrencyId, right: &CurrencyId, amount: &Share) -> Result<Share, DispatchError>;
+  /// the shares of the stable swap pools are identified by the pool index instead of a currency pair,
+  /// the rewards of them are claimed from the reward pool directly
+  fn add_stable_share(who: &AccountId, pool_index: u32, amount: &Share) -> Result<Share, DispatchError>;
+  fn remove_stable_share(who: &AccountId, pool_index: u32, amount: &Share) -> Result<Share, DispatchError>;
+  /// stakes the currency to its single asset pool, the staked balance is reserved, returns the added shares
+  fn stake(who: &AccountId, currency_id: &CurrencyId, amount: &Balance) -> Result<Share, DispatchError>;
+  /// unstakes the currency from its single asset pool, the balance is unreserved after the unbonding period
+  fn unstake(who: &AccountId, currency_id: &CurrencyId, amount: &Balance) -> Result<Share, DispatchError>;
+  /// withdraws the unbonded shares of the pool, returns the withdrawn shares
+  fn withdraw_unbonded(who: &AccountId, left: &CurrencyId, right: &CurrencyId) -> Result<Share, DispatchError>;
 
   fn get_account_shares(who: &AccountId, left: &CurrencyId, right: &CurrencyId) -> Share;
-  fn get_accumlated_rewards(who: &AccountId, left: &CurrencyId, right: &CurrencyId) -> Balance;
-  fn get_account_info(who: &AccountId, left: &CurrencyId, right: &CurrencyId) -> IncentivePoolAccountInfo<Share, Balance>;
-  fn claim_rewards(who: &AccountId, left: &CurrencyId, right: &CurrencyId) -> Result<Balance, DispatchError>;
+  fn get_accumlated_rewards(who: &AccountId, left: &CurrencyId, right: &CurrencyId) -> vec::Vec<(CurrencyId, Balance)>;
+  fn get_account_info(who: &AccountId, left: &CurrencyId, right: &CurrencyId) -> IncentivePoolAccountInfo<CurrencyId, Share, Balance>;
+  fn claim_rewards(who: &AccountId, left: &CurrencyId, right: &CurrencyId) -> Result<vec::Vec<(CurrencyId, Balance)>, DispatchError>;
+
+  /// returns (pool id, total shares, rewards useable of each reward currency)
+  fn get_all_incentive_pools() -> vec::Vec<(Self::PoolId, Share, vec::Vec<(CurrencyId, Balance)>)>;
+
+  /// returns (pool id, reward per block of each reward currency at the current block)
+  fn get_pool_reward_rates() -> vec::Vec<(Self::PoolId, vec::Vec<(CurrencyId, Balance)>)>;
 
-  fn get_all_incentive_pools() -> vec::Vec<(CurrencyId, CurrencyId, Share, Balance)>;
+  /// returns (pool id, unbonding shares, rewards of each reward currency, unbonded block)
+  fn get_pending_unbondings(who: &AccountId) -> vec::Vec<(Self::PoolId, Share, vec::Vec<(CurrencyId, Balance)>, BlockNumber)>;
 }
@@ -1,13 +1,38 @@
 use sp_runtime::{
-  DispatchError,
+  DispatchError, DispatchResult,
 };
 use sp_std::vec;
 
-pub trait RewardPoolOps<AccountId, PoolId, Share, Balance> {
+pub trait RewardPoolOps<AccountId, PoolId, CurrencyId, Share, Balance, BlockNumber> {
   fn add_share(who: &AccountId, pool: PoolId, amount: Share) -> Result<Share, DispatchError>;
+  /// removes shares from the pool, the shares are moved to the unbonding queue
+  /// if the pool has an unbonding period
   fn remove_share(who: &AccountId, pool: PoolId, amount: Share) -> Result<Share, DispatchError>;
+  /// stakes the staking currency of the pool, the balance is reserved and added as shares
+  fn stake(who: &AccountId, pool: PoolId, amount: Balance) -> Result<Share, DispatchError>;
+  /// unstakes the staking currency of the pool, the balance is unreserved once the shares are withdrawn
+  fn unstake(who: &AccountId, pool: PoolId, amount: Balance) -> Result<Share, DispatchError>;
+  /// withdraws the unbonded shares and pays their rewards, the staked currency is unreserved,
+  /// returns the withdrawn shares
+  fn withdraw_unbonded(who: &AccountId, pool: PoolId) -> Result<Share, DispatchError>;
+  /// settles the rewards of the pool up to now, should be called before the reward rate changes
+  fn update_pool_rewards(pool: &PoolId) -> DispatchResult;
+  /// removes all the shares of a staking pool, including the unbonding shares, without computing the rewards
+  /// if it's enabled for the pool, the unclaimed rewards are forfeited, returns the removed shares
+  fn emergency_withdraw(who: &AccountId, pool: PoolId) -> Result<Share, DispatchError>;
+  /// returns the unbonding shares: (shares, rewards of each reward currency, unbonded block)
+  fn get_unbondings(who: &AccountId, pool: &PoolId) -> vec::Vec<(Share, vec::Vec<(CurrencyId, Balance)>, BlockNumber)>;
+  /// locks shares for `lock_period` blocks in exchange for a reward multiplier,
+  /// the locked shares can't be removed before the lock is expired
+  fn lock_share(who: &AccountId, pool: PoolId, amount: Share, lock_period: BlockNumber) -> DispatchResult;
   fn get_account_shares(who: &AccountId, pool: &PoolId) -> Share;
-  fn get_accumlated_rewards(who: &AccountId, pool: &PoolId) -> Balance;
-  fn claim_rewards(who: &AccountId, pool: &PoolId) -> Result<Balance, DispatchError>;
-  fn get_all_pools() -> vec::Vec<(PoolId, Share, Balance)>;
+  /// returns true if the pool is closed, no shares could be added to a closed pool
+  fn is_pool_closed(pool: &PoolId) -> bool;
+  /// returns the accumlated rewards of each reward currency
+  fn get_accumlated_rewards(who: &AccountId, pool: &PoolId) -> vec::Vec<(CurrencyId, Balance)>;
+  /// claims the rewards of all the reward currencies, returns the claimed amount of each currency,
+  /// including the part which is added to the vesting schedule
+  fn claim_rewards(who: &AccountId, pool: &PoolId) -> Result<vec::Vec<(CurrencyId, Balance)>, DispatchError>;
+  /// returns (pool id, total shares, rewards useable of each reward currency)
+  fn get_all_pools() -> vec::Vec<(PoolId, Share, vec::Vec<(CurrencyId, Balance)>)>;
 }
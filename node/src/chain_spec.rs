@@ -4,12 +4,12 @@ use clover_runtime::{
   AccountId, BabeConfig, Balance, AuthorityDiscoveryConfig, BalancesConfig, ContractsConfig, IndicesConfig, GenesisConfig, ImOnlineId,
   GrandpaConfig, SessionConfig, SessionKeys, StakingConfig, SudoConfig, SystemConfig, WASM_BINARY,
   Signature, StakerStatus,
-  EVMConfig, EthereumConfig, DOLLARS
+  EVMConfig, EthereumConfig, TokensConfig, IncentivesConfig, DexConfig, CurrencyId, Rate, DOLLARS
 };
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use sp_consensus_babe::AuthorityId as BabeId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
-use sp_runtime::{traits::{IdentifyAccount, Verify}, Perbill};
+use sp_runtime::{traits::{IdentifyAccount, Verify}, FixedPointNumber, Perbill};
 use sc_service::ChainType;
 use hex_literal::hex;
 use sc_telemetry::TelemetryEndpoints;
@@ -405,6 +405,15 @@ fn testnet_genesis(
             .chain(initial_authorities.iter().map(|x| (x.0.clone(), AUTHOR_BALANCE)))
             .collect(),
     }),
+    orml_tokens: Some(TokensConfig {
+      endowed_accounts: endowed_accounts.iter()
+        .flat_map(|k| vec![
+          (k.clone(), CurrencyId::CUSDT, ENDOWMENT),
+          (k.clone(), CurrencyId::DOT, ENDOWMENT),
+          (k.clone(), CurrencyId::CETH, ENDOWMENT),
+        ])
+        .collect(),
+    }),
     pallet_contracts: Some(ContractsConfig {
       current_schedule: pallet_contracts::Schedule {
         enable_println, // this should only be enabled on development chains
@@ -459,5 +468,18 @@ fn testnet_genesis(
     pallet_elections_phragmen: Some(Default::default()),
     pallet_membership_Instance1: Some(Default::default()),
     pallet_vesting: Some(Default::default()),
+    reward_pool: Some(Default::default()),
+    clover_incentives: Some(IncentivesConfig {
+      dex_rewards: vec![],
+      dex_rewards_per_second: vec![],
+    }),
+    clover_dex: Some(DexConfig {
+      exchange_fee: Rate::saturating_from_rational(3, 1000),
+      trading_pairs: vec![
+        (CurrencyId::CLV, CurrencyId::CUSDT),
+        (CurrencyId::CUSDT, CurrencyId::DOT),
+        (CurrencyId::CUSDT, CurrencyId::CETH),
+      ],
+    }),
   }
 }
@@ -126,6 +126,15 @@ pub fn run() -> sc_cli::Result<()> {
         Ok((cmd.run(client, backend), task_manager))
       })
     }
+    Some(Subcommand::Benchmark(cmd)) => {
+      if cfg!(feature = "runtime-benchmarks") {
+        let runner = cli.create_runner(cmd)?;
+        runner.sync_run(|config| cmd.run::<clover_runtime::opaque::Block, service::Executor>(config))
+      } else {
+        Err("Benchmarking wasn't enabled when building the node. \
+        You can enable it with `--features runtime-benchmarks`.".into())
+      }
+    }
     None => {
       let runner = cli.create_runner(&cli.run)?;
       runner.run_node_until_exit(|config| async {
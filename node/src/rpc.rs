@@ -6,7 +6,8 @@
 #![warn(missing_docs)]
 use std::sync::Arc;
 
-use primitives::{Block, BlockNumber, AccountId, Index, Balance, Hash, };
+use primitives::{Block, BlockNumber, AccountId, CurrencyId, Index, Balance, Hash, Rate, Share, };
+use clover_runtime::IncentivePoolId;
 use fc_rpc_core::types::{PendingTransactions, FilterPool};
 use sc_consensus_babe::{Config, Epoch};
 use sc_consensus_babe_rpc::BabeRpcHandler;
@@ -108,6 +109,10 @@ pub fn create_full<C, P, SC, B>(
   C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
   C::Api: BabeApi<Block>,
   C::Api: BlockBuilder<Block>,
+  C::Api: clover_rpc::balance::CurrencyBalanceRuntimeApi<Block, AccountId, CurrencyId, Balance>,
+  C::Api: clover_rpc::pair::CurrencyPairRuntimeApi<Block>,
+  C::Api: clover_rpc::exchange::CurrencyExchangeRuntimeApi<Block, AccountId, CurrencyId, Balance, Rate, Share>,
+  C::Api: clover_rpc::incentive_pool::IncentivePoolRuntimeApi<Block, AccountId, CurrencyId, Balance, Share, IncentivePoolId>,
   P: TransactionPool<Block=Block> + 'static,
   SC: SelectChain<Block> +'static,
   B: sc_client_api::Backend<Block> + Send + Sync + 'static,
@@ -120,6 +125,13 @@ pub fn create_full<C, P, SC, B>(
   use substrate_frame_rpc_system::{FullSystem, SystemApi};
   use pallet_contracts_rpc::{Contracts, ContractsApi};
   use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+  use clover_rpc::{
+    balance::{CurrencyBalance, CurrencyBalanceRpc},
+    currency::{Currency, CurrencyRpc},
+    exchange::{CurrencyExchange, CurrencyExchangeRpc},
+    incentive_pool::{IncentivePool, IncentivePoolRpc},
+    pair::{CurrencyPair, CurrencyPairRpc},
+  };
 
   let mut io = jsonrpc_core::IoHandler::default();
   let FullDeps {
@@ -157,6 +169,11 @@ pub fn create_full<C, P, SC, B>(
     TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone()))
   );
   io.extend_with(ContractsApi::to_delegate(Contracts::new(client.clone())));
+  io.extend_with(CurrencyRpc::to_delegate(Currency));
+  io.extend_with(CurrencyBalanceRpc::to_delegate(CurrencyBalance::new(client.clone())));
+  io.extend_with(CurrencyPairRpc::to_delegate(CurrencyPair::new(client.clone())));
+  io.extend_with(CurrencyExchangeRpc::to_delegate(CurrencyExchange::new(client.clone())));
+  io.extend_with(IncentivePoolRpc::to_delegate(IncentivePool::new(client.clone())));
   io.extend_with(
     sc_consensus_babe_rpc::BabeApi::to_delegate(
       BabeRpcHandler::new(
@@ -21,6 +21,7 @@ native_executor_instance!(
   pub Executor,
   clover_runtime::api::dispatch,
   clover_runtime::native_version,
+  frame_benchmarking::benchmarking::HostFunctions,
 );
 
 type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
@@ -74,6 +74,12 @@ pub enum CurrencyId {
 	  CETH = 3,
 }
 
+impl Default for CurrencyId {
+  fn default() -> Self {
+    CurrencyId::CLV
+  }
+}
+
 /// dex related types
 pub type Rate = FixedU128;
 pub type Ratio = FixedU128;
@@ -25,14 +25,27 @@ sp_api::decl_runtime_apis! {
 		fn get_liquidity(account: Option<AccountId>) -> sp_std::vec::Vec<(CurrencyId, CurrencyId, Balance, Balance, Balance, Balance, Balance)>;
 		fn get_exchange_rate() -> Rate;
 		fn to_add_liquidity(source: CurrencyId, target: CurrencyId, source_amount: Balance, target_amount: Balance) -> (Share, Share);
-    fn get_staking_info(account: AccountId, currency_first: CurrencyId, currency_second: CurrencyId) -> (Share, Balance);
+    fn get_staking_info(account: AccountId, currency_first: CurrencyId, currency_second: CurrencyId) -> (Share, sp_std::vec::Vec<(CurrencyId, Balance)>);
 	}
 
-  pub trait IncentivePoolApi<AccountId, CurrencyId, Balance, Share> where
+  pub trait IncentivePoolApi<AccountId, CurrencyId, Balance, Share, PoolId> where
     AccountId: codec::Codec,
     CurrencyId: codec::Codec,
     Balance: codec::Codec,
-    Share: codec::Codec, {
-    fn get_all_incentive_pools() -> sp_std::vec::Vec<(CurrencyId, CurrencyId, Share, Balance)>;
+    Share: codec::Codec,
+    PoolId: codec::Codec, {
+    // Get all the incentive pools including the stable pools: (pool id, total shares, rewards)
+    fn get_all_incentive_pools() -> sp_std::vec::Vec<(PoolId, Share, sp_std::vec::Vec<(CurrencyId, Balance)>)>;
+    // Get the unbonding shares of the account: (pool id, shares, rewards, unbonded block)
+    fn get_pending_unbondings(account: AccountId) -> sp_std::vec::Vec<(PoolId, Share, sp_std::vec::Vec<(CurrencyId, Balance)>, primitives::BlockNumber)>;
+    // Get the reward per block of each reward currency of the pools at the current block: (pool id, rewards)
+    fn get_pool_reward_rates() -> sp_std::vec::Vec<(PoolId, sp_std::vec::Vec<(CurrencyId, Balance)>)>;
+  }
+
+  pub trait RewardPoolInvariantApi<PoolId, InvariantViolation> where
+    PoolId: codec::Codec,
+    InvariantViolation: codec::Codec, {
+    // Check the accounting invariants of all the reward pools, returns the violations
+    fn check_reward_pool_invariants() -> sp_std::vec::Vec<(PoolId, InvariantViolation)>;
   }
 }
@@ -38,7 +38,7 @@ pub trait CurrencyExchangeRpc<BlockHash, AccountId, CurrencyId, Balance, Rate, S
                               account: AccountId,
                               currency_first: CurrencyId,
                               currency_second: CurrencyId,
-                              at: Option<BlockHash>) -> Result<(String, String)>;
+                              at: Option<BlockHash>) -> Result<(String, Vec<(CurrencyId, String)>)>;
 }
 
 pub struct CurrencyExchange<C, M> {
@@ -142,7 +142,7 @@ where
   fn get_account_staking_info(&self, account: AccountId,
                               currency_left: CurrencyId,
                               currency_right: CurrencyId,
-                              at: Option<<Block as BlockT>::Hash>) -> Result<(String, String)> {
+                              at: Option<<Block as BlockT>::Hash>) -> Result<(String, Vec<(CurrencyId, String)>)> {
     let api = self.client.runtime_api();
     let at = BlockId::hash(at.unwrap_or_else(||
                                              // If the block hash is not supplied assume the best block.
@@ -152,8 +152,11 @@ where
       code: ErrorCode::ServerError(Error::RuntimeError.into()),
       message: "Unable to get value.".into(),
       data: Some(format!("{:?}", e).into()),
-    }).map(|(s1, s2)| {
-      (format!("{}", s1), format!("{}", s2))
+    }).map(|(shares, rewards)| {
+      let rewards = rewards.into_iter()
+        .map(|(currency_id, reward)| (currency_id, format!("{}", reward)))
+        .collect();
+      (format!("{}", shares), rewards)
     })
   }
 }
@@ -1,6 +1,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 use super::*;
 use codec::{Codec, };
+use clover_primitives::BlockNumber;
 use std::fmt::Display;
 
 pub use clover_rpc_runtime_api::IncentivePoolApi as IncentivePoolRuntimeApi;
@@ -20,22 +21,29 @@ impl<C, B> IncentivePool<C, B> {
 }
 
 #[rpc]
-pub trait IncentivePoolRpc<BlockHash, AccountId, CurrencyId, Balance, Share> {
+pub trait IncentivePoolRpc<BlockHash, AccountId, CurrencyId, Balance, Share, PoolId> {
   #[rpc(name = "incentive_getAllPools")]
-  fn get_all_incentive_pools(&self, at: Option<BlockHash>) -> Result<Vec<(CurrencyId, CurrencyId, String, String)>>;
+  fn get_all_incentive_pools(&self, at: Option<BlockHash>) -> Result<Vec<(PoolId, String, Vec<(CurrencyId, String)>)>>;
+
+  #[rpc(name = "incentive_getPendingUnbondings")]
+  fn get_pending_unbondings(&self, account: AccountId, at: Option<BlockHash>) -> Result<Vec<(PoolId, String, Vec<(CurrencyId, String)>, BlockNumber)>>;
+
+  #[rpc(name = "incentive_getPoolRewardRates")]
+  fn get_pool_reward_rates(&self, at: Option<BlockHash>) -> Result<Vec<(PoolId, Vec<(CurrencyId, String)>)>>;
 }
 
-impl<C, Block, AccountId, CurrencyId, Balance, Share> IncentivePoolRpc<<Block as BlockT>::Hash, AccountId, CurrencyId, Balance, Share> for IncentivePool<C, Block>
+impl<C, Block, AccountId, CurrencyId, Balance, Share, PoolId> IncentivePoolRpc<<Block as BlockT>::Hash, AccountId, CurrencyId, Balance, Share, PoolId> for IncentivePool<C, Block>
 where
   Block: BlockT,
   C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-  C::Api: IncentivePoolRuntimeApi<Block, AccountId, CurrencyId, Balance, Share>,
+  C::Api: IncentivePoolRuntimeApi<Block, AccountId, CurrencyId, Balance, Share, PoolId>,
   AccountId: Codec,
   CurrencyId: Codec,
   Balance: Codec + Display,
-  Share: Codec + Display, {
+  Share: Codec + Display,
+  PoolId: Codec, {
   fn get_all_incentive_pools(&self,
-                             at: Option<<Block as BlockT>::Hash>) -> Result<Vec<(CurrencyId, CurrencyId, String, String)>> {
+                             at: Option<<Block as BlockT>::Hash>) -> Result<Vec<(PoolId, String, Vec<(CurrencyId, String)>)>> {
     let api = self.client.runtime_api();
     let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
 
@@ -44,10 +52,51 @@ where
       message: "Unable to get value.".into(),
       data: Some(format!("{:?}", e).into()),
     }).map(|data|
-           data.into_iter().map(|(c1, c2, share, balance)| {
-             (c1, c2, format!("{}", share), format!("{}", balance))
+           data.into_iter().map(|(pool_id, share, rewards)| {
+             let rewards = rewards.into_iter()
+               .map(|(reward_currency, balance)| (reward_currency, format!("{}", balance)))
+               .collect();
+             (pool_id, format!("{}", share), rewards)
            })
            .collect())
   }
-}
 
+  fn get_pending_unbondings(&self,
+                            account: AccountId,
+                            at: Option<<Block as BlockT>::Hash>) -> Result<Vec<(PoolId, String, Vec<(CurrencyId, String)>, BlockNumber)>> {
+    let api = self.client.runtime_api();
+    let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+    api.get_pending_unbondings(&at, account).map_err(|e| RpcError {
+      code: ErrorCode::ServerError(Error::RuntimeError.into()),
+      message: "Unable to get value.".into(),
+      data: Some(format!("{:?}", e).into()),
+    }).map(|data|
+           data.into_iter().map(|(pool_id, share, rewards, unbonded_block)| {
+             let rewards = rewards.into_iter()
+               .map(|(reward_currency, balance)| (reward_currency, format!("{}", balance)))
+               .collect();
+             (pool_id, format!("{}", share), rewards, unbonded_block)
+           })
+           .collect())
+  }
+
+  fn get_pool_reward_rates(&self,
+                           at: Option<<Block as BlockT>::Hash>) -> Result<Vec<(PoolId, Vec<(CurrencyId, String)>)>> {
+    let api = self.client.runtime_api();
+    let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+    api.get_pool_reward_rates(&at).map_err(|e| RpcError {
+      code: ErrorCode::ServerError(Error::RuntimeError.into()),
+      message: "Unable to get value.".into(),
+      data: Some(format!("{:?}", e).into()),
+    }).map(|data|
+           data.into_iter().map(|(pool_id, rates)| {
+             let rates = rates.into_iter()
+               .map(|(reward_currency, balance)| (reward_currency, format!("{}", balance)))
+               .collect();
+             (pool_id, rates)
+           })
+           .collect())
+  }
+}
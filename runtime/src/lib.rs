@@ -20,7 +20,7 @@ use sp_runtime::{
   OpaqueExtrinsic
 };
 use sp_runtime::traits::{
-  BlakeTwo256, Block as BlockT, Convert, ConvertInto, NumberFor, OpaqueKeys, SaturatedConversion,
+  AccountIdConversion, BlakeTwo256, Block as BlockT, Convert, ConvertInto, NumberFor, OpaqueKeys, SaturatedConversion,
   StaticLookup,
 };
 use sp_runtime::curve::PiecewiseLinear;
@@ -70,6 +70,12 @@ use pallet_evm::{
   EnsureAddressTruncated, Runner,
 };
 use evm_accounts::EvmAddressMapping;
+use orml_currencies::BasicCurrencyAdapter;
+pub use orml_traits::{MultiCurrency, MultiCurrencyExtended};
+use orml_traits::parameter_type_with_key;
+use clover_traits::IncentiveOps;
+pub use clover_incentives::PoolId as IncentivePoolId;
+use enum_iterator::IntoEnumIterator;
 use fp_rpc::{TransactionStatus};
 
 pub use primitives::{
@@ -118,10 +124,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
   spec_name: create_runtime_str!("clover"),
   impl_name: create_runtime_str!("clover"),
   authoring_version: 1,
-  spec_version: 10,
+  spec_version: 13,
   impl_version: 1,
   apis: RUNTIME_API_VERSIONS,
-  transaction_version: 1,
+  transaction_version: 3,
 };
 
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -554,6 +560,121 @@ impl pallet_balances::Config for Runtime {
   type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
 }
 
+parameter_type_with_key! {
+  /// the tokens have the same existential deposit as the native currency
+  pub ExistentialDeposits: |_currency_id: CurrencyId| -> Balance {
+    ExistentialDeposit::get()
+  };
+}
+
+parameter_types! {
+  pub TreasuryAccount: AccountId = TreasuryModuleId::get().into_account();
+}
+
+/// the dust of the tokens below the existential deposit is sent to the treasury
+impl orml_tokens::Config for Runtime {
+  type Event = Event;
+  type Balance = Balance;
+  type Amount = Amount;
+  type CurrencyId = CurrencyId;
+  type WeightInfo = ();
+  type ExistentialDeposits = ExistentialDeposits;
+  type OnDust = orml_tokens::TransferDust<Runtime, TreasuryAccount>;
+}
+
+parameter_types! {
+  pub const GetNativeCurrencyId: CurrencyId = CurrencyId::CLV;
+}
+
+/// the native currency is routed to the balances module, the others are kept in the tokens module
+impl orml_currencies::Config for Runtime {
+  type Event = Event;
+  type MultiCurrency = Tokens;
+  type NativeCurrency = BasicCurrencyAdapter<Runtime, Balances, Amount, BlockNumber>;
+  type GetNativeCurrencyId = GetNativeCurrencyId;
+  type WeightInfo = ();
+}
+
+parameter_types! {
+  pub const RewardPoolModuleId: ModuleId = ModuleId(*b"clv/repm");
+  pub const ExistentialReward: u128 = 100;
+  pub const MaxShareLocks: u32 = 10;
+  pub const MaxUnbondingChunks: u32 = 10;
+  pub const MaxClaimPools: u32 = 50;
+}
+
+impl reward_pool::Trait for Runtime {
+  type Event = Event;
+  type PoolId = clover_incentives::PoolId;
+  type ModuleId = RewardPoolModuleId;
+  type Handler = (Incentives, clover_incentives::TimestampRewards<Runtime>);
+  type Currency = Currencies;
+  type GetNativeCurrencyId = GetNativeCurrencyId;
+  type ExistentialReward = ExistentialReward;
+  type LockSchedule = Incentives;
+  type MaxShareLocks = MaxShareLocks;
+  type MaxUnbondingChunks = MaxUnbondingChunks;
+  type MaxClaimPools = MaxClaimPools;
+  type UpdateOrigin = EnsureRootOrHalfCouncil;
+  type StakingCurrency = clover_incentives::StakingCurrency;
+  type WeightInfo = weights::reward_pool::WeightInfo<Runtime>;
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+impl reward_pool::benchmarking::Config for Runtime {
+  fn pool(index: u32) -> clover_incentives::PoolId {
+    clover_incentives::PoolId::Stable(index)
+  }
+
+  fn staking_pool() -> clover_incentives::PoolId {
+    clover_incentives::PoolId::Single(CurrencyId::DOT)
+  }
+
+  fn lock_period() -> BlockNumber {
+    let lock_period = 7 * DAYS;
+    clover_incentives::LockSchedules::<Runtime>::insert(
+      Self::staking_pool(),
+      vec![(lock_period, primitives::Ratio::saturating_from_rational(3, 2))],
+    );
+    lock_period
+  }
+}
+
+parameter_types! {
+  pub const MaxEmissionSteps: u32 = 32;
+  pub const MaxAllocatedPools: u32 = 50;
+}
+
+impl clover_incentives::Trait for Runtime {
+  type Event = Event;
+  type RewardPool = RewardPool;
+  type UnixTime = Timestamp;
+  type ExpectedBlockTime = ExpectedBlockTime;
+  type MaxEmissionSteps = MaxEmissionSteps;
+  type MaxAllocatedPools = MaxAllocatedPools;
+  type UpdateOrigin = EnsureRootOrHalfCouncil;
+  type WeightInfo = weights::incentives::WeightInfo<Runtime>;
+}
+
+parameter_types! {
+  pub const DexModuleId: ModuleId = ModuleId(*b"clv/dexm");
+  pub const MaxSwapHops: u32 = 3;
+  pub const MinAmplificationRampBlocks: BlockNumber = 1 * DAYS;
+  pub const MaxStablePoolsPerPair: u32 = 4;
+}
+
+impl clover_dex::Trait for Runtime {
+  type Event = Event;
+  type Currency = Currencies;
+  type ModuleId = DexModuleId;
+  type IncentiveOps = Incentives;
+  type MaxSwapHops = MaxSwapHops;
+  type MinAmplificationRampBlocks = MinAmplificationRampBlocks;
+  type MaxStablePoolsPerPair = MaxStablePoolsPerPair;
+  type UpdateOrigin = EnsureRootOrHalfCouncil;
+  type WeightInfo = weights::dex::WeightInfo<Runtime>;
+}
+
 parameter_types! {
   pub const SessionDuration: BlockNumber = EPOCH_DURATION_IN_BLOCKS as _;
   pub const ImOnlineUnsignedPriority: TransactionPriority = TransactionPriority::max_value();
@@ -1069,6 +1190,15 @@ construct_runtime!(
 
     // account module
     EvmAccounts: evm_accounts::{Module, Call, Storage, Event<T>},
+
+    // defi modules
+    RewardPool: reward_pool::{Module, Call, Storage, Event<T>, Config},
+    Incentives: clover_incentives::{Module, Call, Storage, Event<T>, Config},
+    Dex: clover_dex::{Module, Call, Storage, Event<T>, Config},
+
+    // multi currency modules, appended to keep the indices of the existing modules
+    Tokens: orml_tokens::{Module, Storage, Event<T>, Config<T>},
+    Currencies: orml_currencies::{Module, Call, Event<T>},
   }
 );
 
@@ -1305,6 +1435,111 @@ impl_runtime_apis! {
     }
   }
 
+  impl clover_rpc_runtime_api::CurrencyBalanceApi<Block, AccountId, CurrencyId, Balance> for Runtime {
+    fn account_balance(account: AccountId, currency_id: Option<CurrencyId>) -> Vec<(CurrencyId, Balance)> {
+      let currencies = match currency_id {
+        Some(currency_id) => vec![currency_id],
+        None => CurrencyId::into_enum_iter().collect(),
+      };
+      currencies.into_iter()
+        .map(|currency_id| (currency_id, <Currencies as MultiCurrency<_>>::free_balance(currency_id, &account)))
+        .collect()
+    }
+  }
+
+  impl clover_rpc_runtime_api::CurrencyPairApi<Block> for Runtime {
+    fn currency_pair() -> Vec<(CurrencyId, CurrencyId)> {
+      Dex::get_trading_pairs()
+    }
+  }
+
+  impl clover_rpc_runtime_api::CurrencyExchangeApi<Block, AccountId, CurrencyId, Balance, Rate, Share> for Runtime {
+    fn target_amount_available(source: CurrencyId, target: CurrencyId, amount: Balance) -> (Balance, Vec<CurrencyId>) {
+      Dex::target_amount_available(source, target, amount)
+    }
+
+    fn supply_amount_needed(source: CurrencyId, target: CurrencyId, amount: Balance) -> (Balance, Vec<CurrencyId>) {
+      Dex::supply_amount_needed(source, target, amount)
+    }
+
+    // (left, right, left balance, right balance, total shares, account shares, account shares in the incentive pool)
+    fn get_liquidity(account: Option<AccountId>) -> Vec<(CurrencyId, CurrencyId, Balance, Balance, Balance, Balance, Balance)> {
+      Dex::get_pools_liquidity(account.as_ref()).into_iter()
+        .map(|(left, right, left_balance, right_balance, total_shares, account_shares)| {
+          let incentive_shares = account.as_ref()
+            .map(|account| Incentives::get_account_shares(account, &left, &right))
+            .unwrap_or_default();
+          (left, right, left_balance, right_balance, total_shares, account_shares, incentive_shares)
+        })
+        .collect()
+    }
+
+    fn get_exchange_rate() -> Rate {
+      Dex::exchange_fee()
+    }
+
+    fn to_add_liquidity(source: CurrencyId, target: CurrencyId, source_amount: Balance, target_amount: Balance) -> (Share, Share) {
+      Dex::to_add_liquidity(source, target, source_amount, target_amount)
+    }
+
+    fn get_staking_info(account: AccountId, currency_first: CurrencyId, currency_second: CurrencyId) -> (Share, Vec<(CurrencyId, Balance)>) {
+      let info = Incentives::get_account_info(&account, &currency_first, &currency_second);
+      (info.shares, info.accumlated_rewards)
+    }
+  }
+
+  impl clover_rpc_runtime_api::IncentivePoolApi<Block, AccountId, CurrencyId, Balance, Share, clover_incentives::PoolId> for Runtime {
+    fn get_all_incentive_pools() -> Vec<(clover_incentives::PoolId, Share, Vec<(CurrencyId, Balance)>)> {
+      Incentives::get_all_incentive_pools()
+    }
+
+    fn get_pending_unbondings(account: AccountId) -> Vec<(clover_incentives::PoolId, Share, Vec<(CurrencyId, Balance)>, BlockNumber)> {
+      Incentives::get_pending_unbondings(&account)
+    }
+
+    fn get_pool_reward_rates() -> Vec<(clover_incentives::PoolId, Vec<(CurrencyId, Balance)>)> {
+      <Incentives as IncentiveOps<AccountId, CurrencyId, Share, Balance, BlockNumber>>::get_pool_reward_rates()
+    }
+  }
+
+  impl clover_rpc_runtime_api::RewardPoolInvariantApi<Block, clover_incentives::PoolId, reward_pool::InvariantViolation<AccountId>> for Runtime {
+    fn check_reward_pool_invariants() -> Vec<(clover_incentives::PoolId, reward_pool::InvariantViolation<AccountId>)> {
+      RewardPool::check_invariants()
+    }
+  }
+
+  #[cfg(feature = "runtime-benchmarks")]
+  impl frame_benchmarking::Benchmark<Block> for Runtime {
+    fn dispatch_benchmark(
+      config: frame_benchmarking::BenchmarkConfig
+    ) -> Result<Vec<frame_benchmarking::BenchmarkBatch>, sp_runtime::RuntimeString> {
+      use frame_benchmarking::{Benchmarking, BenchmarkBatch, add_benchmark, TrackedStorageKey};
+
+      let whitelist: Vec<TrackedStorageKey> = vec![
+        // Block Number
+        hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac").to_vec().into(),
+        // Total Issuance
+        hex_literal::hex!("c2261276cc9d1f8598ea4b6a74b15c2f57c875e4cff74148e4628f264b974c80").to_vec().into(),
+        // Execution Phase
+        hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef7ff553b5a9862a516939d82b3d3d8661a").to_vec().into(),
+        // Event Count
+        hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef70a98fdbe9ce6c55837576c60c7af3850").to_vec().into(),
+        // System Events
+        hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef780d41e5e16056765bc8461851072c9d7").to_vec().into(),
+      ];
+
+      let mut batches = Vec::<BenchmarkBatch>::new();
+      let params = (&config, &whitelist);
+
+      add_benchmark!(params, batches, clover_dex, Dex);
+      add_benchmark!(params, batches, reward_pool, RewardPool);
+      add_benchmark!(params, batches, clover_incentives, Incentives);
+
+      if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
+      Ok(batches)
+    }
+  }
+
   impl fp_rpc::EthereumRuntimeRPCApi<Block> for Runtime {
     fn chain_id() -> u64 {
         <Runtime as pallet_evm::Config>::ChainId::get()
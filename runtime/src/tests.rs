@@ -22,3 +22,26 @@ fn test_balance() {
       assert_eq!(<Currencies as MultiCurrency<_>>::free_balance(CLV, &AccountId::from(ALICE)), 1500);
     });
 }
+
+#[test]
+fn test_transfer() {
+  ExtBuilder::default()
+    .balances(vec![
+      (AccountId::from(ALICE), CLV, 1000),
+      (AccountId::from(ALICE), CUSDT, 2000),
+    ])
+    .build()
+    .execute_with(|| {
+      let alice = AccountId::from(ALICE);
+      let bob = AccountId::from(BOB);
+      assert!(Currencies::transfer(Origin::signed(alice.clone()), bob.clone().into(), CUSDT, 600).is_ok());
+      assert_eq!(<Currencies as MultiCurrency<_>>::free_balance(CUSDT, &alice), 1400);
+      assert_eq!(<Currencies as MultiCurrency<_>>::free_balance(CUSDT, &bob), 600);
+      assert!(Currencies::transfer(Origin::signed(alice.clone()), bob.clone().into(), CUSDT, 1401).is_err());
+
+      // the native currency is kept in the balances module, which has the existential deposit
+      assert!(Currencies::transfer_native_currency(Origin::signed(alice.clone()), bob.clone().into(), 10).is_err());
+      assert_eq!(Balances::free_balance(&alice), 500);
+      assert_eq!(Balances::free_balance(&bob), 0);
+    });
+}
@@ -0,0 +1,73 @@
+//! Weights for clover_dex, estimated from the storage access of each call,
+//! to be replaced by the benchmark output of `scripts/benchmark.sh`.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for clover_dex.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> clover_dex::WeightInfo for WeightInfo<T> {
+	fn add_liquidity() -> Weight {
+		(180_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(11 as Weight))
+			.saturating_add(T::DbWeight::get().writes(10 as Weight))
+	}
+	fn remove_liquidity() -> Weight {
+		(180_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(11 as Weight))
+			.saturating_add(T::DbWeight::get().writes(10 as Weight))
+	}
+	fn swap_with_exact_supply(h: u32, s: u32, ) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(h as Weight))
+			.saturating_add((60_000_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(T::DbWeight::get().reads((6 as Weight).saturating_mul(h as Weight)))
+			.saturating_add(T::DbWeight::get().reads(s as Weight))
+			.saturating_add(T::DbWeight::get().writes((5 as Weight).saturating_mul(h as Weight)))
+	}
+	fn swap_with_exact_target(h: u32, s: u32, ) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((100_000_000 as Weight).saturating_mul(h as Weight))
+			.saturating_add((60_000_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(T::DbWeight::get().reads((6 as Weight).saturating_mul(h as Weight)))
+			.saturating_add(T::DbWeight::get().reads(s as Weight))
+			.saturating_add(T::DbWeight::get().writes((5 as Weight).saturating_mul(h as Weight)))
+	}
+	fn list_trading_pair() -> Weight {
+		(25_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn set_exchange_fee() -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn create_stable_pool() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn ramp_amplification() -> Weight {
+		(25_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn stop_amplification_ramp() -> Weight {
+		(25_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn add_stable_liquidity() -> Weight {
+		(400_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
+			.saturating_add(T::DbWeight::get().writes(14 as Weight))
+	}
+	fn remove_stable_liquidity() -> Weight {
+		(250_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
+			.saturating_add(T::DbWeight::get().writes(14 as Weight))
+	}
+}
@@ -1,4 +1,7 @@
 //! A list of the different weight modules for our runtime.
 #![allow(clippy::unnecessary_cast)]
 
-pub mod evm_accounts;
\ No newline at end of file
+pub mod dex;
+pub mod evm_accounts;
+pub mod incentives;
+pub mod reward_pool;
@@ -0,0 +1,23 @@
+#!/usr/bin/env bash
+
+set -e
+
+# usage: ./scripts/benchmark.sh <pallet> <weight file>
+# e.g. ./scripts/benchmark.sh reward_pool reward_pool
+
+echo "*** Building the node with the runtime benchmarks"
+
+cargo build --release -p clover --features runtime-benchmarks
+
+echo "*** Benchmarking $1"
+
+./target/release/clover benchmark \
+  --chain=dev \
+  --steps=50 \
+  --repeat=20 \
+  --pallet="$1" \
+  --extrinsic='*' \
+  --execution=wasm \
+  --wasm-execution=compiled \
+  --heap-pages=4096 \
+  --output="./runtime/src/weights/$2.rs"